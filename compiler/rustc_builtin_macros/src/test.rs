@@ -120,6 +120,18 @@ pub fn expand_test_or_bench(
         return vec![Annotatable::Item(item)];
     }
 
+    // `#[retries]` is consumed here, so that it can be rejected on any other item when checking
+    // attributes. Without the feature, it is left for the feature gate to report.
+    let retries = retries(cx, &item, is_bench);
+    let item = if cx.ecfg.features.map_or(false, |features| features.test_retries) {
+        item.map(|mut item| {
+            item.attrs.retain(|attr| !attr.has_name(sym::retries));
+            item
+        })
+    } else {
+        item
+    };
+
     // has_*_signature will report any errors in the type so compilation
     // will fail. We shouldn't try to expand in this case because the errors
     // would be spurious.
@@ -271,6 +283,15 @@ pub fn expand_test_or_bench(
                                             cx.expr_none(sp)
                                         },
                                     ),
+                                    // retries: Some(N) | None
+                                    field(
+                                        "retries",
+                                        if let Some(retries) = retries {
+                                            cx.expr_some(sp, cx.expr_usize(sp, retries))
+                                        } else {
+                                            cx.expr_none(sp)
+                                        },
+                                    ),
                                     // compile_fail: true | false
                                     field("compile_fail", cx.expr_bool(sp, false)),
                                     // no_run: true | false
@@ -387,6 +408,29 @@ fn should_ignore_message(cx: &ExtCtxt<'_>, i: &ast::Item) -> Option<Symbol> {
     }
 }
 
+fn retries(cx: &ExtCtxt<'_>, i: &ast::Item, is_bench: bool) -> Option<usize> {
+    let attr = cx.sess.find_by_name(&i.attrs, sym::retries)?;
+    if is_bench {
+        cx.sess
+            .parse_sess
+            .span_diagnostic
+            .struct_span_err(attr.span, "`#[retries]` can only be used on `#[test]` functions")
+            .span_label(i.span, "benchmarks are not retried")
+            .emit();
+        return None;
+    }
+    match attr.value_str().and_then(|retries| retries.as_str().parse().ok()) {
+        Some(retries) => Some(retries),
+        None => {
+            cx.sess.parse_sess.span_diagnostic.span_err(
+                attr.span,
+                "`#[retries]` expects the number of retries, e.g. `#[retries = \"3\"]`",
+            );
+            None
+        }
+    }
+}
+
 fn should_panic(cx: &ExtCtxt<'_>, i: &ast::Item) -> ShouldPanic {
     match cx.sess.find_by_name(&i.attrs, sym::should_panic) {
         Some(attr) => {
@@ -408,6 +408,24 @@ fn run_compiler(
             if sess.opts.debugging_opts.print_type_sizes {
                 sess.code_stats.print_type_sizes();
             }
+            if let Some(ref dir) = sess.opts.debugging_opts.emit_type_sizes {
+                // The file is named like the output of save-analysis, so that the crates of a
+                // build which all get the flag through `RUSTFLAGS` don't overwrite each other.
+                let executable = sess.crate_types().contains(&config::CrateType::Executable);
+                let path = dir.join(format!(
+                    "{}{}{}.json",
+                    if executable { "" } else { "lib" },
+                    queries.crate_name()?.peek(),
+                    sess.opts.cg.extra_filename
+                ));
+                if let Err(e) = sess.code_stats.write_type_sizes_json(&path) {
+                    sess.fatal(&format!(
+                        "failed to write type sizes to `{}`: {}",
+                        path.display(),
+                        e
+                    ));
+                }
+            }
 
             let linker = queries.linker()?;
             Ok(Some(linker))
@@ -1307,6 +1325,7 @@ pub(super) fn install() {}
 }
 
 pub fn main() -> ! {
+    rustc_metadata::proc_macro_worker::run_if_requested();
     let start_time = Instant::now();
     let start_rss = get_resident_set_size();
     init_rustc_env_logger();
@@ -29,6 +29,8 @@
 use rustc_serialize::json::{as_json, as_pretty_json};
 use rustc_serialize::{Encodable, Encoder};
 
+pub mod lsp;
+
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,387 @@
+//! An emitter for errors in the shape of the Language Server Protocol's
+//! [`textDocument/publishDiagnostics`] notification, for editor integrations that speak LSP.
+//!
+//! LSP clients replace all diagnostics of a document whenever they receive a notification for
+//! it, so this emitter collects every diagnostic and, once it is dropped, writes one
+//! `PublishDiagnosticsParams` object per file, one per line:
+//!
+//! ```json
+//! {"diagnostics":[{"code":"E0308","message":"mismatched types","range":{...},...}],"uri":"file:///src/main.rs"}
+//! ```
+//!
+//! Diagnostics are mapped onto the LSP `Diagnostic` as follows:
+//!
+//! * the primary span becomes the `range`, and decides which file the diagnostic belongs to;
+//!   the label of the primary span is appended to the message,
+//! * other labelled spans and the spans of child diagnostics become `relatedInformation`,
+//! * child diagnostics without a span are appended to the message,
+//! * error codes link to the error index through their `codeDescription`,
+//! * structured suggestions are listed in `relatedInformation` and, as `WorkspaceEdit`s that
+//!   can be turned into code actions, in `data.fixes`.
+//!
+//! Positions use 0-based lines and count characters in UTF-16 code units, as LSP requires.
+//! Diagnostics without a primary span can't be attached to a document and are left out.
+//!
+//! [`textDocument/publishDiagnostics`]: https://microsoft.github.io/language-server-protocol/specifications/specification-3-17/#textDocument_publishDiagnostics
+
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+
+use crate::emitter::Emitter;
+use crate::structured::{object, Fix, MessageLocation, ERROR_INDEX_URL};
+use crate::{CodeSuggestion, DiagnosticId, Level};
+
+use rustc_data_structures::sync::Lrc;
+use rustc_span::{BytePos, SourceFile, Span};
+use std::env;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use rustc_serialize::json::{Json, ToJson};
+
+#[cfg(test)]
+mod tests;
+
+pub struct LspEmitter {
+    dst: Box<dyn Write + Send>,
+    sm: Lrc<SourceMap>,
+    /// The diagnostics of every file, in the order the files were first reported in.
+    files: Vec<(String, Vec<Json>)>,
+}
+
+impl LspEmitter {
+    pub fn stderr(source_map: Lrc<SourceMap>) -> Self {
+        LspEmitter::new(Box::new(io::BufWriter::new(io::stderr())), source_map)
+    }
+
+    pub fn basic() -> Self {
+        let file_path_mapping = FilePathMapping::empty();
+        LspEmitter::stderr(Lrc::new(SourceMap::new(file_path_mapping)))
+    }
+
+    pub fn new(dst: Box<dyn Write + Send>, source_map: Lrc<SourceMap>) -> Self {
+        LspEmitter { dst, sm: source_map, files: Vec::new() }
+    }
+
+    fn write_notifications(&mut self) -> io::Result<()> {
+        for (uri, diagnostics) in std::mem::take(&mut self.files) {
+            let params =
+                object([("uri", uri.to_json()), ("diagnostics", Json::Array(diagnostics))]);
+            writeln!(&mut self.dst, "{}", params)?;
+        }
+        self.dst.flush()
+    }
+}
+
+impl Emitter for LspEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        // Summaries such as "aborting due to previous error" don't describe a problem in the
+        // code.
+        if diag.level == Level::FailureNote {
+            return;
+        }
+        if let Some((uri, diagnostic)) = LspDiagnostic::from_errors_diagnostic(diag, &self.sm) {
+            let diagnostic = diagnostic.to_json();
+            match self.files.iter_mut().find(|(file_uri, _)| *file_uri == uri) {
+                Some((_, diagnostics)) => diagnostics.push(diagnostic),
+                None => self.files.push((uri, vec![diagnostic])),
+            }
+        }
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for LspEmitter {
+    fn drop(&mut self) {
+        if let Err(e) = self.write_notifications() {
+            // Don't turn an unwinding compiler into an abort.
+            if !std::thread::panicking() {
+                panic!("failed to print diagnostics: {:?}", e);
+            }
+        }
+    }
+}
+
+/// The LSP `DiagnosticSeverity` of a level.
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => 1,
+        Level::Warning => 2,
+        Level::Note | Level::FailureNote => 3,
+        Level::Help | Level::Allow => 4,
+    }
+}
+
+/// The LSP `DiagnosticTag`s for lints that editors commonly render specially, e.g. by fading
+/// out unused code or striking through deprecated items.
+fn tags(code: Option<&DiagnosticId>) -> Vec<u8> {
+    match code {
+        Some(DiagnosticId::Lint { name, .. })
+            if name.starts_with("unused_") || name == "dead_code" || name == "unreachable_code" =>
+        {
+            vec![1]
+        }
+        Some(DiagnosticId::Lint { name, .. }) if name.starts_with("deprecated") => vec![2],
+        _ => vec![],
+    }
+}
+
+// The following data types are provided just for serialisation.
+
+struct LspDiagnostic {
+    range: Range,
+    severity: u8,
+    code: Option<DiagnosticId>,
+    message: String,
+    related_information: Vec<Location>,
+    fixes: Vec<Fix<Range>>,
+}
+
+struct Position {
+    /// 0-based.
+    line: usize,
+    /// 0-based, in UTF-16 code units.
+    character: usize,
+}
+
+struct Range {
+    start: Position,
+    end: Position,
+}
+
+struct Location {
+    uri: String,
+    range: Range,
+    message: String,
+}
+
+impl LspDiagnostic {
+    /// Converts a diagnostic, returning the URI of the file it belongs to along with it.
+    fn from_errors_diagnostic(
+        diag: &crate::Diagnostic,
+        sm: &SourceMap,
+    ) -> Option<(String, LspDiagnostic)> {
+        let (uri, range) = span_location(diag.span.primary_span()?, sm)?;
+
+        let mut message = diag.message();
+        let mut related_information = vec![];
+        let mut primary_label_seen = false;
+        for label in diag.span.span_labels() {
+            if label.is_primary && !primary_label_seen {
+                primary_label_seen = true;
+                if let Some(label) = label.label {
+                    message.push('\n');
+                    message.push_str(&label);
+                }
+            } else if let Some(location) =
+                Location::from_span(label.span, label.label.unwrap_or_else(|| diag.message()), sm)
+            {
+                related_information.push(location);
+            }
+        }
+        for child in &diag.children {
+            let child_message = format!("{}: {}", child.level.to_str(), child.message());
+            let child_locations = Location::from_sub_diagnostic(child, &child_message, sm);
+            if child_locations.is_empty() {
+                message.push('\n');
+                message.push_str(&child_message);
+            }
+            related_information.extend(child_locations);
+        }
+        for suggestion in diag.suggestions.iter().flatten() {
+            related_information.extend(Location::from_suggestion(suggestion, sm));
+        }
+        let fixes = diag
+            .suggestions
+            .iter()
+            .flatten()
+            .flat_map(|sugg| Fix::from_suggestion(sugg, sm, span_location));
+
+        let diagnostic = LspDiagnostic {
+            range,
+            severity: severity(diag.level),
+            code: diag.code.clone(),
+            message,
+            related_information,
+            fixes: fixes.collect(),
+        };
+        Some((uri, diagnostic))
+    }
+}
+
+impl Position {
+    fn from_pos(pos: BytePos, sm: &SourceMap) -> Position {
+        let loc = sm.lookup_char_pos(pos);
+        // `col` counts chars, which take up two UTF-16 code units outside of the BMP.
+        let character = match loc.file.get_line(loc.line - 1) {
+            Some(line) => line.chars().take(loc.col.0).map(char::len_utf16).sum(),
+            // Without the source of the line, the best guess is that every char is in the BMP.
+            None => loc.col.0,
+        };
+        Position { line: loc.line - 1, character }
+    }
+}
+
+/// Returns the URI of the file the span points into, along with the range it covers.
+///
+/// Spans into files that can't be opened, such as the output of proc macros, are mapped to
+/// the place the macro was called from.
+fn span_location(span: Span, sm: &SourceMap) -> Option<(String, Range)> {
+    if span.is_dummy() {
+        return None;
+    }
+    let mut file = sm.lookup_source_file(span.lo());
+    let mut span = span;
+    if !file.is_real_file() {
+        span = span.source_callsite();
+        file = sm.lookup_source_file(span.lo());
+        if !file.is_real_file() {
+            return None;
+        }
+    }
+    let range =
+        Range { start: Position::from_pos(span.lo(), sm), end: Position::from_pos(span.hi(), sm) };
+    Some((file_uri(&file, sm), range))
+}
+
+fn file_uri(file: &SourceFile, sm: &SourceMap) -> String {
+    let path = PathBuf::from(sm.filename_for_diagnostics(&file.name).to_string());
+    let path = if path.is_absolute() {
+        path
+    } else {
+        env::current_dir().map(|dir| dir.join(&path)).unwrap_or(path)
+    };
+    path_to_uri(&path)
+}
+
+/// Converts a path into a `file://` URI, percent-encoding everything but unreserved characters.
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    // Windows paths start with the drive letter instead.
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => write!(uri, "%{:02X}", byte).unwrap(),
+        }
+    }
+    uri
+}
+
+impl MessageLocation for Location {
+    fn from_span(span: Span, message: String, sm: &SourceMap) -> Option<Location> {
+        let (uri, range) = span_location(span, sm)?;
+        Some(Location { uri, range, message })
+    }
+}
+
+impl Location {
+    /// Points to the first part of every substitution of the suggestion, showing the
+    /// replacement in the message if it's a single part.
+    fn from_suggestion(suggestion: &CodeSuggestion, sm: &SourceMap) -> Vec<Location> {
+        suggestion
+            .substitutions
+            .iter()
+            .filter_map(|substitution| {
+                let message = match &substitution.parts[..] {
+                    [part] => format!("help: {}: `{}`", suggestion.msg, part.snippet),
+                    _ => format!("help: {}", suggestion.msg),
+                };
+                Location::from_span(substitution.parts.first()?.span, message, sm)
+            })
+            .collect()
+    }
+}
+
+impl ToJson for LspDiagnostic {
+    fn to_json(&self) -> Json {
+        let (code, code_description) = match &self.code {
+            Some(DiagnosticId::Error(code)) => (
+                code.to_json(),
+                object([("href", format!("{}#{}", ERROR_INDEX_URL, code).to_json())]),
+            ),
+            Some(DiagnosticId::Lint { name, .. }) => (name.to_json(), Json::Null),
+            None => (Json::Null, Json::Null),
+        };
+        let tags = tags(self.code.as_ref());
+        let fixes = if self.fixes.is_empty() {
+            Json::Null
+        } else {
+            object([("fixes", self.fixes.to_json())])
+        };
+        object([
+            ("range", self.range.to_json()),
+            ("severity", self.severity.to_json()),
+            ("code", code),
+            ("codeDescription", code_description),
+            ("source", "rustc".to_json()),
+            ("message", self.message.to_json()),
+            ("tags", if tags.is_empty() { Json::Null } else { tags.to_json() }),
+            (
+                "relatedInformation",
+                if self.related_information.is_empty() {
+                    Json::Null
+                } else {
+                    self.related_information.to_json()
+                },
+            ),
+            ("data", fixes),
+        ])
+    }
+}
+
+impl ToJson for Position {
+    fn to_json(&self) -> Json {
+        object([("line", self.line.to_json()), ("character", self.character.to_json())])
+    }
+}
+
+impl ToJson for Range {
+    fn to_json(&self) -> Json {
+        object([("start", self.start.to_json()), ("end", self.end.to_json())])
+    }
+}
+
+impl ToJson for Location {
+    fn to_json(&self) -> Json {
+        object([
+            ("location", object([("uri", self.uri.to_json()), ("range", self.range.to_json())])),
+            ("message", self.message.to_json()),
+        ])
+    }
+}
+
+impl ToJson for Fix<Range> {
+    fn to_json(&self) -> Json {
+        let changes = self
+            .changes
+            .iter()
+            .map(|(uri, edits)| {
+                let edits = edits
+                    .iter()
+                    .map(|(range, new_text)| {
+                        object([("range", range.to_json()), ("newText", new_text.to_json())])
+                    })
+                    .collect();
+                (uri.clone(), Json::Array(edits))
+            })
+            .collect();
+        object([
+            ("title", self.description.to_json()),
+            ("applicability", format!("{:?}", self.applicability).to_json()),
+            ("edit", object([("changes", Json::Object(changes))])),
+        ])
+    }
+}
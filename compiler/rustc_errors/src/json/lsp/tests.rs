@@ -0,0 +1,146 @@
+use super::*;
+
+use crate::Handler;
+use rustc_lint_defs::Applicability;
+use rustc_serialize::json;
+
+use std::str;
+use std::sync::{Arc, Mutex};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` against a handler backed by an `LspEmitter` and returns the notifications that are
+/// written once the handler is dropped.
+fn with_notifications(code: &str, f: impl FnOnce(&Handler)) -> Vec<Json> {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let le = LspEmitter::new(Box::new(Shared { data: output.clone() }), sm);
+
+        let handler = Handler::with_emitter(true, None, Box::new(le));
+        f(&handler);
+        drop(handler);
+
+        let bytes = output.lock().unwrap();
+        str::from_utf8(&bytes).unwrap().lines().map(|line| json::from_str(line).unwrap()).collect()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+fn assert_range(range: &Json, start: (u64, u64), end: (u64, u64)) {
+    assert_eq!(range["start"]["line"].as_u64(), Some(start.0));
+    assert_eq!(range["start"]["character"].as_u64(), Some(start.1));
+    assert_eq!(range["end"]["line"].as_u64(), Some(end.0));
+    assert_eq!(range["end"]["character"].as_u64(), Some(end.1));
+}
+
+#[test]
+fn utf16_ranges() {
+    // `😀` takes up 4 bytes and 2 UTF-16 code units, `é` 2 bytes and 1 code unit.
+    let notifications = with_notifications("fn main() { \"😀é\"; foo }", |handler| {
+        handler
+            .struct_span_err_with_code(
+                span(22, 25),
+                "cannot find `foo`",
+                DiagnosticId::Error("E0425".into()),
+            )
+            .span_label(span(22, 25), "not found in this scope")
+            .emit();
+        handler.err("no location");
+    });
+
+    assert_eq!(notifications.len(), 1);
+    let uri = notifications[0]["uri"].as_string().unwrap();
+    assert!(uri.starts_with("file:///") && uri.ends_with("/test.rs"), "{}", uri);
+
+    let diagnostics = notifications[0]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_range(&diagnostic["range"], (0, 19), (0, 22));
+    assert_eq!(diagnostic["severity"].as_u64(), Some(1));
+    assert_eq!(diagnostic["source"].as_string(), Some("rustc"));
+    assert_eq!(diagnostic["code"].as_string(), Some("E0425"));
+    assert_eq!(
+        diagnostic["codeDescription"]["href"].as_string(),
+        Some("https://doc.rust-lang.org/error-index.html#E0425")
+    );
+    assert_eq!(
+        diagnostic["message"].as_string(),
+        Some("cannot find `foo`\nnot found in this scope")
+    );
+    assert!(diagnostic.find("relatedInformation").is_none());
+}
+
+#[test]
+fn related_information_and_fixes() {
+    let notifications = with_notifications("fn main() {\n    let x = 1;\n}", |handler| {
+        handler
+            .struct_span_warn(span(20, 21), "unused variable: `x`")
+            .code(DiagnosticId::Lint {
+                name: "unused_variables".into(),
+                has_future_breakage: false,
+                is_force_warn: false,
+            })
+            .span_note(span(0, 2), "declared in this function")
+            .note("`#[warn(unused_variables)]` on by default")
+            .span_suggestion(
+                span(20, 21),
+                "if this is intentional, prefix it with an underscore",
+                "_x".into(),
+                Applicability::MachineApplicable,
+            )
+            .emit();
+    });
+
+    let diagnostic = &notifications[0]["diagnostics"][0];
+    let uri = notifications[0]["uri"].as_string().unwrap();
+    assert_eq!(diagnostic["severity"].as_u64(), Some(2));
+    assert_eq!(diagnostic["code"].as_string(), Some("unused_variables"));
+    assert!(diagnostic.find("codeDescription").is_none());
+    assert_eq!(diagnostic["tags"].as_array().unwrap(), &[Json::U64(1)]);
+    assert_eq!(
+        diagnostic["message"].as_string(),
+        Some("unused variable: `x`\nnote: `#[warn(unused_variables)]` on by default")
+    );
+
+    let related = diagnostic["relatedInformation"].as_array().unwrap();
+    assert_eq!(related.len(), 2);
+    assert_eq!(related[0]["message"].as_string(), Some("note: declared in this function"));
+    assert_range(&related[0]["location"]["range"], (0, 0), (0, 2));
+    assert_eq!(
+        related[1]["message"].as_string(),
+        Some("help: if this is intentional, prefix it with an underscore: `_x`")
+    );
+    assert_range(&related[1]["location"]["range"], (1, 8), (1, 9));
+
+    let fixes = diagnostic["data"]["fixes"].as_array().unwrap();
+    assert_eq!(fixes.len(), 1);
+    assert_eq!(fixes[0]["applicability"].as_string(), Some("MachineApplicable"));
+    let edit = &fixes[0]["edit"]["changes"][uri][0];
+    assert_eq!(edit["newText"].as_string(), Some("_x"));
+    assert_range(&edit["range"], (1, 8), (1, 9));
+}
+
+#[test]
+fn file_uris() {
+    assert_eq!(path_to_uri(Path::new("/src/main.rs")), "file:///src/main.rs");
+    assert_eq!(path_to_uri(Path::new("/my crate/ü.rs")), "file:///my%20crate/%C3%BC.rs");
+    assert_eq!(path_to_uri(Path::new("C:\\src\\lib.rs")), "file:///C%3A/src/lib.rs");
+}
@@ -8,6 +8,7 @@
 #![feature(if_let_guard)]
 #![feature(let_else)]
 #![feature(nll)]
+#![feature(once_cell)]
 #![feature(adt_const_params)]
 #![allow(incomplete_features)]
 #![allow(rustc::potential_query_instability)]
@@ -50,8 +51,11 @@
 pub mod json;
 mod lock;
 pub mod registry;
+pub mod sarif;
 mod snippet;
+mod structured;
 mod styled_buffer;
+pub mod translation;
 pub use snippet::Style;
 
 pub type PResult<'a, T> = Result<T, DiagnosticBuilder<'a, ErrorGuaranteed>>;
@@ -400,12 +404,17 @@ impl error::Error for ExplicitBug {}
 pub use diagnostic::{Diagnostic, DiagnosticId, DiagnosticStyledString, SubDiagnostic};
 pub use diagnostic_builder::DiagnosticBuilder;
 use std::backtrace::Backtrace;
+pub use translation::{
+    DiagnosticArgValue, DiagnosticArgs, DiagnosticMessage, IntoDiagnosticArg, TranslationError,
+    Translator,
+};
 
 /// A handler deals with errors and other compiler output.
 /// Certain errors (fatal, bug, unimpl) may cause immediate exit,
 /// others log errors for later reporting.
 pub struct Handler {
     flags: HandlerFlags,
+    translator: Translator,
     inner: Lock<HandlerInner>,
 }
 
@@ -555,6 +564,7 @@ pub fn with_emitter_and_flags(
     ) -> Self {
         Self {
             flags,
+            translator: Translator::default(),
             inner: Lock::new(HandlerInner {
                 flags,
                 lint_err_count: 0,
@@ -574,6 +584,21 @@ pub fn with_emitter_and_flags(
         }
     }
 
+    /// Translates messages into the language of `translator`, rather than into English.
+    pub fn with_translator(self, translator: Translator) -> Self {
+        Self { translator, ..self }
+    }
+
+    /// Formats `message` with `args` in the language requested with `-Z translate-lang`. If not
+    /// even the English message can be formatted, reports a delayed bug and returns the identifier
+    /// of the message instead.
+    pub fn translate(&self, message: &DiagnosticMessage, args: &DiagnosticArgs) -> String {
+        self.translator.translate(message, args).unwrap_or_else(|e| {
+            self.delay_span_bug(MultiSpan::new(), &e.to_string());
+            message.to_string()
+        })
+    }
+
     // This is here to not allow mutation of flags;
     // as of this writing it's only used in tests in librustc_middle.
     pub fn can_emit_warnings(&self) -> bool {
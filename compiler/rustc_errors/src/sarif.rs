@@ -0,0 +1,363 @@
+//! A SARIF emitter for errors.
+//!
+//! Unlike the JSON emitter, which writes one object per diagnostic, SARIF describes a whole
+//! analysis run in a single log. This emitter therefore collects every diagnostic and only
+//! writes the log out once it is dropped, i.e. when the diagnostic handler goes away at the end
+//! of the session.
+//!
+//! Diagnostics are mapped onto [SARIF 2.1.0] as follows:
+//!
+//! * error codes and lint names become `rules`, with the long explanation of error codes (if
+//!   the registry knows it) as their description,
+//! * primary spans become `locations`, all other labelled spans and the spans of child
+//!   diagnostics become `relatedLocations`,
+//! * child diagnostics without a span are appended to the result message,
+//! * structured suggestions become `fixes`.
+//!
+//! Columns are reported in Unicode code points, just like the columns in the JSON output.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::structured::{object, Fix, MessageLocation, ERROR_INDEX_URL};
+use crate::{DiagnosticId, Level};
+
+use rustc_data_structures::sync::Lrc;
+use rustc_span::Span;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use rustc_serialize::json::{Json, ToJson};
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    /// The rules referenced by `results`, keyed by their id.
+    rules: BTreeMap<String, Rule>,
+    results: Vec<SarifResult>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(registry: Option<Registry>, source_map: Lrc<SourceMap>) -> Self {
+        SarifEmitter::new(Box::new(io::BufWriter::new(io::stderr())), registry, source_map)
+    }
+
+    pub fn basic() -> Self {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(None, Lrc::new(SourceMap::new(file_path_mapping)))
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+    ) -> Self {
+        SarifEmitter { dst, registry, sm: source_map, rules: BTreeMap::new(), results: Vec::new() }
+    }
+
+    fn rule_for_code(&mut self, code: &DiagnosticId) -> String {
+        let (id, is_error_code) = match code {
+            DiagnosticId::Error(s) => (s, true),
+            DiagnosticId::Lint { name, .. } => (name, false),
+        };
+        if !self.rules.contains_key(id) {
+            let description = self
+                .registry
+                .as_ref()
+                .and_then(|registry| registry.try_find_description(id).ok())
+                .flatten();
+            let help_uri = is_error_code.then(|| format!("{}#{}", ERROR_INDEX_URL, id));
+            self.rules.insert(id.clone(), Rule { id: id.clone(), description, help_uri });
+        }
+        id.clone()
+    }
+
+    fn write_log(&mut self) -> io::Result<()> {
+        let log = object([
+            ("$schema", SARIF_SCHEMA.to_json()),
+            ("version", SARIF_VERSION.to_json()),
+            (
+                "runs",
+                Json::Array(vec![object([
+                    (
+                        "tool",
+                        object([(
+                            "driver",
+                            object([
+                                ("name", "rustc".to_json()),
+                                ("informationUri", "https://www.rust-lang.org/".to_json()),
+                                (
+                                    "rules",
+                                    Json::Array(self.rules.values().map(Rule::to_json).collect()),
+                                ),
+                            ]),
+                        )]),
+                    ),
+                    ("columnKind", "unicodeCodePoints".to_json()),
+                    ("results", self.results.to_json()),
+                ])]),
+            ),
+        ]);
+        writeln!(&mut self.dst, "{}", log).and_then(|_| self.dst.flush())
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        // Summaries such as "For more information about this error, ..." don't describe a
+        // finding of their own.
+        if diag.level == Level::FailureNote {
+            return;
+        }
+        let rule_id = diag.code.as_ref().map(|code| self.rule_for_code(code));
+        let result = SarifResult::from_errors_diagnostic(diag, rule_id, &self.sm);
+        self.results.push(result);
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        if let Err(e) = self.write_log() {
+            // Don't turn an unwinding compiler into an abort.
+            if !std::thread::panicking() {
+                panic!("failed to print diagnostics: {:?}", e);
+            }
+        }
+    }
+}
+
+/// A SARIF `message` object.
+fn message(text: &str) -> Json {
+    object([("text", text.to_json())])
+}
+
+fn level_to_sarif(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => "error",
+        Level::Warning => "warning",
+        Level::Note | Level::Help | Level::FailureNote => "note",
+        Level::Allow => "none",
+    }
+}
+
+// The following data types are provided just for serialisation.
+
+struct Rule {
+    /// The error code or lint name.
+    id: String,
+    /// The long explanation of an error code.
+    description: Option<&'static str>,
+    /// Link to the error index for error codes.
+    help_uri: Option<String>,
+}
+
+struct SarifResult {
+    rule_id: Option<String>,
+    /// "error", "warning", "note" or "none".
+    level: &'static str,
+    message: String,
+    locations: Vec<Location>,
+    related_locations: Vec<Location>,
+    fixes: Vec<Fix<Region>>,
+}
+
+struct Location {
+    uri: String,
+    region: Region,
+    /// The label attached to the span, or the message of the child diagnostic it belongs to.
+    message: Option<String>,
+}
+
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    end_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+impl SarifResult {
+    fn from_errors_diagnostic(
+        diag: &crate::Diagnostic,
+        rule_id: Option<String>,
+        sm: &SourceMap,
+    ) -> SarifResult {
+        let mut message = diag.message();
+        let mut locations = vec![];
+        let mut related_locations = vec![];
+        for label in diag.span.span_labels() {
+            if let Some(location) = Location::new(label.span, label.label, sm) {
+                if label.is_primary {
+                    locations.push(location);
+                } else {
+                    related_locations.push(location);
+                }
+            }
+        }
+        for child in &diag.children {
+            let child_message = format!("{}: {}", child.level.to_str(), child.message());
+            let child_locations = Location::from_sub_diagnostic(child, &child_message, sm);
+            if child_locations.is_empty() {
+                message.push('\n');
+                message.push_str(&child_message);
+            }
+            related_locations.extend(child_locations);
+        }
+        let fixes = diag.suggestions.iter().flatten().flat_map(|sugg| {
+            Fix::from_suggestion(sugg, sm, |span, sm| Some(Region::from_span(span, sm)))
+        });
+
+        SarifResult {
+            rule_id,
+            level: level_to_sarif(diag.level),
+            message,
+            locations,
+            related_locations,
+            fixes: fixes.collect(),
+        }
+    }
+}
+
+impl Location {
+    fn new(span: Span, message: Option<String>, sm: &SourceMap) -> Option<Location> {
+        if span.is_dummy() {
+            return None;
+        }
+        let (uri, region) = Region::from_span(span, sm);
+        Some(Location { uri, region, message })
+    }
+}
+
+impl MessageLocation for Location {
+    fn from_span(span: Span, message: String, sm: &SourceMap) -> Option<Location> {
+        Location::new(span, Some(message), sm)
+    }
+}
+
+impl Region {
+    /// Returns the file name the span points into, along with the region it covers.
+    fn from_span(span: Span, sm: &SourceMap) -> (String, Region) {
+        let start = sm.lookup_char_pos(span.lo());
+        let end = sm.lookup_char_pos(span.hi());
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        let region = Region {
+            start_line: start.line,
+            end_line: end.line,
+            start_column: start.col.0 + 1,
+            end_column: end.col.0 + 1,
+            byte_offset: byte_start,
+            byte_length: byte_end - byte_start,
+        };
+        (sm.filename_for_diagnostics(&start.file.name).to_string(), region)
+    }
+}
+
+impl ToJson for Rule {
+    fn to_json(&self) -> Json {
+        object([
+            ("id", self.id.to_json()),
+            // SARIF requires a plain text version of the description, and the Markdown of the
+            // explanations of error codes reads well enough as plain text.
+            (
+                "fullDescription",
+                self.description
+                    .map(|desc| object([("text", desc.to_json()), ("markdown", desc.to_json())]))
+                    .to_json(),
+            ),
+            ("helpUri", self.help_uri.to_json()),
+        ])
+    }
+}
+
+impl ToJson for SarifResult {
+    fn to_json(&self) -> Json {
+        object([
+            ("ruleId", self.rule_id.to_json()),
+            ("level", self.level.to_json()),
+            ("message", message(&self.message)),
+            ("locations", self.locations.to_json()),
+            ("relatedLocations", self.related_locations.to_json()),
+            ("fixes", self.fixes.to_json()),
+        ])
+    }
+}
+
+fn physical_location(uri: &str, region: &Region) -> Json {
+    object([("artifactLocation", object([("uri", uri.to_json())])), ("region", region.to_json())])
+}
+
+impl ToJson for Location {
+    fn to_json(&self) -> Json {
+        object([
+            ("physicalLocation", physical_location(&self.uri, &self.region)),
+            ("message", self.message.as_deref().map(message).to_json()),
+        ])
+    }
+}
+
+impl ToJson for Region {
+    fn to_json(&self) -> Json {
+        object([
+            ("startLine", self.start_line.to_json()),
+            ("startColumn", self.start_column.to_json()),
+            ("endLine", self.end_line.to_json()),
+            ("endColumn", self.end_column.to_json()),
+            ("byteOffset", self.byte_offset.to_json()),
+            ("byteLength", self.byte_length.to_json()),
+        ])
+    }
+}
+
+impl ToJson for Fix<Region> {
+    fn to_json(&self) -> Json {
+        let changes = self
+            .changes
+            .iter()
+            .map(|(uri, replacements)| {
+                let replacements = replacements
+                    .iter()
+                    .map(|(region, snippet)| {
+                        object([
+                            ("deletedRegion", region.to_json()),
+                            ("insertedContent", object([("text", snippet.to_json())])),
+                        ])
+                    })
+                    .collect();
+                object([
+                    ("artifactLocation", object([("uri", uri.to_json())])),
+                    ("replacements", Json::Array(replacements)),
+                ])
+            })
+            .collect();
+        object([
+            ("description", message(&self.description)),
+            ("artifactChanges", Json::Array(changes)),
+            (
+                "properties",
+                object([("applicability", format!("{:?}", self.applicability).to_json())]),
+            ),
+        ])
+    }
+}
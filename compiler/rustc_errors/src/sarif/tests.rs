@@ -0,0 +1,135 @@
+use super::*;
+
+use crate::Handler;
+use rustc_serialize::json;
+use rustc_span::BytePos;
+
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` against a handler backed by a `SarifEmitter` and returns the single run of the log
+/// that is written once the handler is dropped.
+fn with_sarif_run(code: &str, f: impl FnOnce(&Handler)) -> Json {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let registry = Registry::new(&[("E0001", Some("Some explanation."))]);
+        let se = SarifEmitter::new(Box::new(Shared { data: output.clone() }), Some(registry), sm);
+
+        let handler = Handler::with_emitter(true, None, Box::new(se));
+        f(&handler);
+        drop(handler);
+
+        let bytes = output.lock().unwrap();
+        let log = json::from_str(str::from_utf8(&bytes).unwrap()).unwrap();
+        assert_eq!(log["version"].as_string(), Some(SARIF_VERSION));
+        let runs = log["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 1);
+        runs[0].clone()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn error_with_code() {
+    let run = with_sarif_run("fn main() { foo }", |handler| {
+        handler
+            .struct_span_err_with_code(
+                span(12, 15),
+                "cannot find `foo`",
+                DiagnosticId::Error("E0001".into()),
+            )
+            .emit();
+    });
+
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0]["id"].as_string(), Some("E0001"));
+    assert_eq!(rules[0]["fullDescription"]["text"].as_string(), Some("Some explanation."));
+    assert_eq!(rules[0]["fullDescription"]["markdown"].as_string(), Some("Some explanation."));
+    assert_eq!(
+        rules[0]["helpUri"].as_string(),
+        Some("https://doc.rust-lang.org/error-index.html#E0001")
+    );
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    let result = &results[0];
+    assert_eq!(result["ruleId"].as_string(), Some("E0001"));
+    assert_eq!(result["level"].as_string(), Some("error"));
+    assert_eq!(result["message"]["text"].as_string(), Some("cannot find `foo`"));
+
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"].as_string(), Some("test.rs"));
+    let region = &location["region"];
+    assert_eq!(region["startLine"].as_u64(), Some(1));
+    assert_eq!(region["startColumn"].as_u64(), Some(13));
+    assert_eq!(region["endColumn"].as_u64(), Some(16));
+    assert_eq!(region["byteOffset"].as_u64(), Some(12));
+    assert_eq!(region["byteLength"].as_u64(), Some(3));
+}
+
+#[test]
+fn children_and_suggestions() {
+    let run = with_sarif_run("fn main() {\n    let x = 1;\n}", |handler| {
+        handler
+            .struct_span_warn(span(20, 21), "unused variable: `x`")
+            .code(DiagnosticId::Lint {
+                name: "unused_variables".into(),
+                has_future_breakage: false,
+                is_force_warn: false,
+            })
+            .span_note(span(0, 2), "declared in this function")
+            .note("`#[warn(unused_variables)]` on by default")
+            .span_suggestion(
+                span(20, 21),
+                "if this is intentional, prefix it with an underscore",
+                "_x".into(),
+                Applicability::MachineApplicable,
+            )
+            .emit();
+    });
+
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    assert_eq!(rules[0]["id"].as_string(), Some("unused_variables"));
+    assert!(rules[0].find("helpUri").is_none());
+
+    let result = &run["results"][0];
+    assert_eq!(result["level"].as_string(), Some("warning"));
+    assert_eq!(
+        result["message"]["text"].as_string(),
+        Some("unused variable: `x`\nnote: `#[warn(unused_variables)]` on by default")
+    );
+
+    let related = result["relatedLocations"].as_array().unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0]["message"]["text"].as_string(), Some("note: declared in this function"));
+
+    let fixes = result["fixes"].as_array().unwrap();
+    assert_eq!(fixes.len(), 1);
+    assert_eq!(fixes[0]["properties"]["applicability"].as_string(), Some("MachineApplicable"));
+    let replacement = &fixes[0]["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["insertedContent"]["text"].as_string(), Some("_x"));
+    assert_eq!(replacement["deletedRegion"]["startLine"].as_u64(), Some(2));
+    assert_eq!(replacement["deletedRegion"]["startColumn"].as_u64(), Some(9));
+}
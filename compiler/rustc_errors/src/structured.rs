@@ -0,0 +1,97 @@
+//! Helpers shared by the emitters that write diagnostics as structured documents for other tools,
+//! i.e. the SARIF emitter and the LSP emitter.
+//!
+//! The two formats describe positions differently, so the emitters bring their own location
+//! types, and only the way diagnostics are broken down into locations and fixes lives here.
+
+use rustc_span::source_map::SourceMap;
+
+use crate::{CodeSuggestion, SubDiagnostic};
+use rustc_lint_defs::Applicability;
+
+use rustc_span::{MultiSpan, Span};
+
+use rustc_serialize::json::Json;
+
+pub(crate) const ERROR_INDEX_URL: &str = "https://doc.rust-lang.org/error-index.html";
+
+/// Builds a JSON object, leaving out the optional fields that are `null`, since neither format
+/// allows them for most properties.
+pub(crate) fn object<const N: usize>(fields: [(&str, Json); N]) -> Json {
+    Json::Object(
+        fields
+            .into_iter()
+            .filter(|(_, value)| !matches!(value, Json::Null))
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect(),
+    )
+}
+
+/// A span in the format of an emitter, along with a message saying what it points to.
+pub(crate) trait MessageLocation: Sized {
+    /// Returns `None` for spans that can't be reported, e.g. dummy spans.
+    fn from_span(span: Span, message: String, sm: &SourceMap) -> Option<Self>;
+
+    /// The locations of the labelled spans of `msp`, with their labels added to `message`.
+    fn from_multispan(msp: &MultiSpan, message: &str, sm: &SourceMap) -> Vec<Self> {
+        msp.span_labels()
+            .into_iter()
+            .filter_map(|label| {
+                let message = match label.label {
+                    Some(label) => format!("{} ({})", message, label),
+                    None => message.to_owned(),
+                };
+                Self::from_span(label.span, message, sm)
+            })
+            .collect()
+    }
+
+    fn from_sub_diagnostic(diag: &SubDiagnostic, message: &str, sm: &SourceMap) -> Vec<Self> {
+        diag.render_span
+            .as_ref()
+            .map(|sp| Self::from_multispan(sp, message, sm))
+            .unwrap_or_else(|| Self::from_multispan(&diag.span, message, sm))
+    }
+}
+
+/// A way of fixing the problem of a diagnostic, where `R` is the type of the regions the
+/// emitter replaces text in.
+pub(crate) struct Fix<R> {
+    pub description: String,
+    pub applicability: Applicability,
+    /// The replacements of the fix, grouped by the file they apply to, in the order the files
+    /// first appear in the suggestion.
+    pub changes: Vec<(String, Vec<(R, String)>)>,
+}
+
+impl<R> Fix<R> {
+    /// Each substitution of a suggestion is a separate way of fixing the problem, so each of
+    /// them becomes its own fix. `region` returns the file a span points into along with the
+    /// region it covers, or `None` if the span can't be reported.
+    pub(crate) fn from_suggestion(
+        suggestion: &CodeSuggestion,
+        sm: &SourceMap,
+        region: impl Fn(Span, &SourceMap) -> Option<(String, R)>,
+    ) -> Vec<Fix<R>> {
+        suggestion
+            .substitutions
+            .iter()
+            .map(|substitution| {
+                let mut changes: Vec<(String, Vec<(R, String)>)> = vec![];
+                for part in &substitution.parts {
+                    let Some((file, region)) = region(part.span, sm) else { continue };
+                    let replacement = (region, part.snippet.clone());
+                    match changes.iter_mut().find(|(change_file, _)| *change_file == file) {
+                        Some((_, replacements)) => replacements.push(replacement),
+                        None => changes.push((file, vec![replacement])),
+                    }
+                }
+                Fix {
+                    description: suggestion.msg.clone(),
+                    applicability: suggestion.applicability,
+                    changes,
+                }
+            })
+            .collect()
+    }
+}
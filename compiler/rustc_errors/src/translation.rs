@@ -0,0 +1,636 @@
+//! Translation of diagnostic messages.
+//!
+//! Instead of being written out in English where they are emitted, diagnostic messages can be
+//! identified by a [`DiagnosticMessage`] and looked up in a [`MessageCatalog`] of the language
+//! requested with `-Z translate-lang`, falling back to the English messages in
+//! `compiler/rustc_errors/locales/en-US` if the catalog doesn't have them.
+//!
+//! Catalogs are written in a subset of [Fluent](https://projectfluent.org):
+//!
+//! ```text
+//! # Comments start with `#`.
+//! typeck-field-multiply-specified =
+//!     field `{$ident}` specified more than once
+//!     .label = used more than once
+//!     .previous-use-label = first use of `{$ident}`
+//!
+//! typeck-wrong-number-of-args = { $expected ->
+//!     [one] expected {$expected} argument
+//!    *[other] expected {$expected} arguments
+//! }
+//! ```
+//!
+//! Messages can have attributes (like `.label` above), reference the arguments of the
+//! diagnostic with `{$name}`, and select between variants depending on the value of an
+//! argument. Numbers select the variant named after them, or else the variant named after their
+//! plural category in the language of the catalog, i.e. `zero`, `one`, `two`, `few`, `many` or
+//! `other`.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_span::symbol::{Ident, Symbol};
+
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use unic_langid::LanguageIdentifier;
+
+use std::borrow::Cow;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::lazy::SyncLazy;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+/// The English messages, which are used for messages that are missing from the catalog of the
+/// requested language.
+pub static DEFAULT_LOCALE_RESOURCES: &[&str] = &[include_str!("../locales/en-US/typeck.ftl")];
+
+static FALLBACK_CATALOG: SyncLazy<Result<MessageCatalog, CatalogError>> = SyncLazy::new(|| {
+    let mut catalog = MessageCatalog::default();
+    for resource in DEFAULT_LOCALE_RESOURCES {
+        catalog.add_resource(resource)?;
+    }
+    Ok(catalog)
+});
+
+/// Identifies a message in a message catalog, or one of its attributes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DiagnosticMessage {
+    pub id: Cow<'static, str>,
+    pub attr: Option<Cow<'static, str>>,
+}
+
+impl DiagnosticMessage {
+    pub fn new(id: impl Into<Cow<'static, str>>) -> Self {
+        DiagnosticMessage { id: id.into(), attr: None }
+    }
+
+    /// Returns the message identifying the attribute `attr` of this message.
+    pub fn with_attr(&self, attr: impl Into<Cow<'static, str>>) -> Self {
+        DiagnosticMessage { id: self.id.clone(), attr: Some(attr.into()) }
+    }
+}
+
+impl fmt::Display for DiagnosticMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.attr {
+            Some(attr) => write!(f, "{}.{}", self.id, attr),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+/// The value of an argument of a translated message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticArgValue {
+    Str(Cow<'static, str>),
+    Number(i128),
+}
+
+impl fmt::Display for DiagnosticArgValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticArgValue::Str(s) => f.write_str(s),
+            DiagnosticArgValue::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// The named arguments of a translated message.
+pub type DiagnosticArgs = Vec<(Cow<'static, str>, DiagnosticArgValue)>;
+
+/// Converts a value into an argument of a translated message.
+pub trait IntoDiagnosticArg {
+    fn into_diagnostic_arg(self) -> DiagnosticArgValue;
+}
+
+macro_rules! into_diagnostic_arg_as_number {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoDiagnosticArg for $ty {
+                fn into_diagnostic_arg(self) -> DiagnosticArgValue {
+                    DiagnosticArgValue::Number(self as i128)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! into_diagnostic_arg_using_display {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoDiagnosticArg for $ty {
+                fn into_diagnostic_arg(self) -> DiagnosticArgValue {
+                    DiagnosticArgValue::Str(Cow::Owned(self.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+into_diagnostic_arg_as_number!(i8, u8, i16, u16, i32, u32, i64, u64, i128, isize, usize);
+into_diagnostic_arg_using_display!(u128, char, Ident, Symbol);
+
+impl IntoDiagnosticArg for String {
+    fn into_diagnostic_arg(self) -> DiagnosticArgValue {
+        DiagnosticArgValue::Str(Cow::Owned(self))
+    }
+}
+
+impl<'a> IntoDiagnosticArg for &'a str {
+    fn into_diagnostic_arg(self) -> DiagnosticArgValue {
+        DiagnosticArgValue::Str(Cow::Owned(self.to_owned()))
+    }
+}
+
+impl IntoDiagnosticArg for Cow<'static, str> {
+    fn into_diagnostic_arg(self) -> DiagnosticArgValue {
+        DiagnosticArgValue::Str(self)
+    }
+}
+
+/// An error encountered while loading a message catalog.
+#[derive(Debug)]
+pub enum CatalogError {
+    Io(PathBuf, io::Error),
+    Parse(Option<PathBuf>, usize, String),
+    MissingLocale(String, PathBuf),
+    InvalidLocale(String),
+    MissingPluralRules(String),
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogError::Io(path, e) => write!(f, "failed to read `{}`: {}", path.display(), e),
+            CatalogError::Parse(Some(path), line, msg) => {
+                write!(f, "invalid message catalog `{}`, line {}: {}", path.display(), line, msg)
+            }
+            CatalogError::Parse(None, line, msg) => {
+                write!(f, "invalid message catalog, line {}: {}", line, msg)
+            }
+            CatalogError::MissingLocale(locale, dir) => write!(
+                f,
+                "no diagnostic messages for the language `{}` in `{}`",
+                locale,
+                dir.display()
+            ),
+            CatalogError::InvalidLocale(locale) => {
+                write!(f, "invalid language identifier `{}`", locale)
+            }
+            CatalogError::MissingPluralRules(locale) => {
+                write!(f, "no plural rules for the language `{}`", locale)
+            }
+        }
+    }
+}
+
+/// An error encountered while formatting a message.
+#[derive(Debug, PartialEq)]
+pub enum TranslationError {
+    MissingMessage(DiagnosticMessage),
+    MissingArgument(DiagnosticMessage, String),
+    /// The English messages couldn't be loaded.
+    InvalidFallback(String),
+}
+
+impl fmt::Display for TranslationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranslationError::MissingMessage(message) => {
+                write!(f, "missing diagnostic message `{}`", message)
+            }
+            TranslationError::MissingArgument(message, arg) => {
+                write!(f, "missing argument `{}` of diagnostic message `{}`", arg, message)
+            }
+            TranslationError::InvalidFallback(e) => {
+                write!(f, "invalid English diagnostic messages: {}", e)
+            }
+        }
+    }
+}
+
+/// The messages of one language.
+pub struct MessageCatalog {
+    messages: FxHashMap<String, Message>,
+    /// The plural rules of the language, which select the variants for numbers.
+    plural_rules: PluralRules,
+}
+
+impl Default for MessageCatalog {
+    /// An empty catalog of English messages.
+    fn default() -> Self {
+        MessageCatalog::new("en-US").unwrap()
+    }
+}
+
+impl fmt::Debug for MessageCatalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageCatalog")
+            .field("messages", &self.messages)
+            .field("locale", self.plural_rules.get_locale())
+            .finish()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Message {
+    value: Option<Pattern>,
+    attributes: FxHashMap<String, Pattern>,
+}
+
+type Pattern = Vec<PatternElement>;
+
+#[derive(Debug, PartialEq)]
+enum PatternElement {
+    Text(String),
+    /// `{$name}`
+    Variable(String),
+    /// `{ $name -> [key] pattern *[default] pattern }`
+    Select {
+        selector: String,
+        variants: Vec<(String, Pattern)>,
+        default: usize,
+    },
+}
+
+impl MessageCatalog {
+    /// Creates an empty catalog for the language `locale`, e.g. `fr` or `pt-BR`.
+    pub fn new(locale: &str) -> Result<Self, CatalogError> {
+        let langid: LanguageIdentifier =
+            locale.parse().map_err(|_| CatalogError::InvalidLocale(locale.to_owned()))?;
+        // Most regional variants of a language share its plural rules, and only have them under
+        // the name of the language.
+        let language = LanguageIdentifier::from_parts(langid.language, None, None, &[]);
+        let plural_rules = PluralRules::create(langid, PluralRuleType::CARDINAL)
+            .or_else(|_| PluralRules::create(language, PluralRuleType::CARDINAL))
+            .map_err(|_| CatalogError::MissingPluralRules(locale.to_owned()))?;
+        Ok(MessageCatalog { messages: Default::default(), plural_rules })
+    }
+
+    /// Loads the messages for `locale` from the `.ftl` files in `$sysroot/share/locale/$locale`,
+    /// along with the messages in `additional_ftl`, which take precedence. Returns `None` if
+    /// neither is given. Without a `locale`, the messages of `additional_ftl` use the English
+    /// plural rules.
+    pub fn load(
+        sysroot: &Path,
+        locale: Option<&str>,
+        additional_ftl: Option<&Path>,
+    ) -> Result<Option<MessageCatalog>, CatalogError> {
+        if locale.is_none() && additional_ftl.is_none() {
+            return Ok(None);
+        }
+
+        let mut catalog = MessageCatalog::new(locale.unwrap_or("en-US"))?;
+        if let Some(locale) = locale {
+            let dir = sysroot.join("share").join("locale").join(locale);
+            match fs::read_dir(&dir) {
+                Ok(entries) => {
+                    let mut paths = Vec::new();
+                    for entry in entries {
+                        let path = entry.map_err(|e| CatalogError::Io(dir.clone(), e))?.path();
+                        if path.extension().map_or(false, |ext| ext == "ftl") {
+                            paths.push(path);
+                        }
+                    }
+                    // Later resources override earlier ones, so load them in a stable order.
+                    paths.sort();
+                    for path in paths {
+                        catalog.add_file(&path)?;
+                    }
+                }
+                // Only messages from `additional_ftl` are used then.
+                Err(e) if e.kind() == io::ErrorKind::NotFound && additional_ftl.is_some() => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    return Err(CatalogError::MissingLocale(locale.to_owned(), dir));
+                }
+                Err(e) => return Err(CatalogError::Io(dir, e)),
+            }
+        }
+        if let Some(path) = additional_ftl {
+            catalog.add_file(path)?;
+        }
+        Ok(Some(catalog))
+    }
+
+    fn add_file(&mut self, path: &Path) -> Result<(), CatalogError> {
+        let source = fs::read_to_string(path).map_err(|e| CatalogError::Io(path.to_owned(), e))?;
+        self.add_resource(&source).map_err(|e| match e {
+            CatalogError::Parse(_, line, msg) => {
+                CatalogError::Parse(Some(path.to_owned()), line, msg)
+            }
+            e => e,
+        })
+    }
+
+    /// Adds the messages in the Fluent resource `source`, replacing existing messages with the
+    /// same identifier.
+    pub fn add_resource(&mut self, source: &str) -> Result<(), CatalogError> {
+        // The message or attribute whose pattern is being parsed, the line it started on, and the
+        // lines of its pattern so far.
+        struct Entry {
+            id: String,
+            attr: Option<String>,
+            line: usize,
+            lines: Vec<String>,
+        }
+
+        let mut entries: Vec<Entry> = Vec::new();
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let error = |msg: &str| CatalogError::Parse(None, line_number, msg.to_owned());
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // The closing brace of a select expression doesn't need to be indented.
+            if line.starts_with(|c: char| c.is_whitespace() || c == '}') {
+                let line = line.trim();
+                let entry = entries.last_mut().ok_or_else(|| error("expected a message"))?;
+                if let Some(attr) = line.strip_prefix('.') {
+                    let (name, value) =
+                        parse_definition(attr).ok_or_else(|| error("expected an attribute"))?;
+                    let id = entry.id.clone();
+                    entries.push(Entry {
+                        id,
+                        attr: Some(name.to_owned()),
+                        line: line_number,
+                        lines: vec![value.to_owned()],
+                    });
+                } else {
+                    entry.lines.push(line.to_owned());
+                }
+            } else {
+                let (id, value) =
+                    parse_definition(line).ok_or_else(|| error("expected a message"))?;
+                entries.push(Entry {
+                    id: id.to_owned(),
+                    attr: None,
+                    line: line_number,
+                    lines: vec![value.to_owned()],
+                });
+            }
+        }
+
+        for Entry { id, attr, line, lines } in entries {
+            let source = lines.iter().map(|l| l.as_str()).filter(|l| !l.is_empty());
+            let source = source.collect::<Vec<_>>().join("\n");
+            let pattern =
+                parse_pattern(&source).map_err(|msg| CatalogError::Parse(None, line, msg))?;
+            match attr {
+                // A new definition of a message replaces all of its attributes.
+                None => {
+                    let value = if pattern.is_empty() { None } else { Some(pattern) };
+                    self.messages.insert(id, Message { value, attributes: Default::default() });
+                }
+                Some(attr) => {
+                    self.messages.entry(id).or_default().attributes.insert(attr, pattern);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Formats `message` with `args`. Fails if the catalog doesn't have the message or the
+    /// message refers to an argument that isn't in `args`.
+    pub fn format(
+        &self,
+        message: &DiagnosticMessage,
+        args: &DiagnosticArgs,
+    ) -> Result<String, TranslationError> {
+        let missing = || TranslationError::MissingMessage(message.clone());
+        let entry = self.messages.get(&*message.id).ok_or_else(missing)?;
+        let pattern = match &message.attr {
+            Some(attr) => entry.attributes.get(&**attr),
+            None => entry.value.as_ref(),
+        };
+        let mut out = String::new();
+        self.format_pattern(pattern.ok_or_else(missing)?, args, &mut out)
+            .map_err(|arg| TranslationError::MissingArgument(message.clone(), arg))?;
+        Ok(out)
+    }
+
+    /// Formats `pattern` into `out`, or returns the name of an argument it refers to which isn't
+    /// in `args`.
+    fn format_pattern(
+        &self,
+        pattern: &Pattern,
+        args: &DiagnosticArgs,
+        out: &mut String,
+    ) -> Result<(), String> {
+        let arg = |name: &str| {
+            args.iter()
+                .find(|(arg, _)| arg == name)
+                .map(|(_, value)| value)
+                .ok_or_else(|| name.to_owned())
+        };
+        for element in pattern {
+            match element {
+                PatternElement::Text(text) => out.push_str(text),
+                PatternElement::Variable(name) => out.push_str(&arg(name)?.to_string()),
+                PatternElement::Select { selector, variants, default } => {
+                    let variant = |key: &str| variants.iter().find(|(k, _)| k == key);
+                    let selected = match arg(selector)? {
+                        DiagnosticArgValue::Str(s) => variant(&**s),
+                        DiagnosticArgValue::Number(n) => {
+                            let n = n.to_string();
+                            variant(&n).or_else(|| {
+                                let category = self.plural_rules.select(&*n).ok()?;
+                                variant(plural_category_name(category))
+                            })
+                        }
+                    };
+                    let (_, pattern) = selected.unwrap_or(&variants[*default]);
+                    self.format_pattern(pattern, args, out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn plural_category_name(category: PluralCategory) -> &'static str {
+    match category {
+        PluralCategory::ZERO => "zero",
+        PluralCategory::ONE => "one",
+        PluralCategory::TWO => "two",
+        PluralCategory::FEW => "few",
+        PluralCategory::MANY => "many",
+        PluralCategory::OTHER => "other",
+    }
+}
+
+/// Splits `name = value` into its parts, checking that `name` is a valid identifier.
+fn parse_definition(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.split_once('=')?;
+    let name = name.trim_end();
+    let mut chars = name.chars();
+    let valid = chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid { Some((name, value.trim())) } else { None }
+}
+
+fn parse_pattern(source: &str) -> Result<Pattern, String> {
+    let mut parser = PatternParser { source, pos: 0 };
+    let pattern = parser.pattern(false)?;
+    if parser.pos < source.len() {
+        return Err("unexpected `}`".to_owned());
+    }
+    Ok(pattern)
+}
+
+struct PatternParser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> PatternParser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.source[self.pos..].starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        while self.peek().map_or(false, |c| c == ' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while self.peek().map_or(false, |c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err("expected an identifier".to_owned());
+        }
+        Ok(self.source[start..self.pos].to_owned())
+    }
+
+    /// Parses text and placeables until the end of the source, a `}`, or, in the variants of a
+    /// select expression, the end of the line.
+    fn pattern(&mut self, in_variant: bool) -> Result<Pattern, String> {
+        let mut pattern = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            if c == '}' || (in_variant && c == '\n') {
+                break;
+            }
+            self.pos += c.len_utf8();
+            if c == '{' {
+                if !text.is_empty() {
+                    pattern.push(PatternElement::Text(std::mem::take(&mut text)));
+                }
+                pattern.push(self.placeable()?);
+            } else {
+                text.push(c);
+            }
+        }
+        if !text.is_empty() {
+            pattern.push(PatternElement::Text(text));
+        }
+        Ok(pattern)
+    }
+
+    /// Parses a placeable, after its opening `{`.
+    fn placeable(&mut self) -> Result<PatternElement, String> {
+        self.skip_whitespace();
+        let element = if self.eat("\"") {
+            let start = self.pos;
+            let len = self.source[start..].find('"').ok_or("unterminated string literal")?;
+            self.pos += len + 1;
+            PatternElement::Text(self.source[start..start + len].to_owned())
+        } else if self.eat("$") {
+            let selector = self.identifier()?;
+            self.skip_whitespace();
+            if self.eat("->") {
+                self.variants(selector)?
+            } else {
+                PatternElement::Variable(selector)
+            }
+        } else {
+            return Err("expected a variable or a string literal".to_owned());
+        };
+        self.skip_whitespace();
+        if !self.eat("}") {
+            return Err("expected `}`".to_owned());
+        }
+        Ok(element)
+    }
+
+    /// Parses the variants of a select expression, after its `->`.
+    fn variants(&mut self, selector: String) -> Result<PatternElement, String> {
+        let mut variants = Vec::new();
+        let mut default = None;
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') || self.peek().is_none() {
+                break;
+            }
+            if self.eat("*") {
+                if default.is_some() {
+                    return Err("more than one default variant".to_owned());
+                }
+                default = Some(variants.len());
+            }
+            if !self.eat("[") {
+                return Err("expected a variant".to_owned());
+            }
+            self.skip_blank();
+            let key = self.identifier()?;
+            self.skip_blank();
+            if !self.eat("]") {
+                return Err("expected `]`".to_owned());
+            }
+            self.skip_blank();
+            variants.push((key, self.pattern(true)?));
+        }
+        let default = default.ok_or("missing default variant")?;
+        Ok(PatternElement::Select { selector, variants, default })
+    }
+}
+
+/// Formats diagnostic messages in the language requested with `-Z translate-lang`.
+#[derive(Clone, Default)]
+pub struct Translator {
+    /// The messages of the requested language, if any.
+    catalog: Option<Lrc<MessageCatalog>>,
+}
+
+impl Translator {
+    pub fn new(catalog: Option<MessageCatalog>) -> Self {
+        Translator { catalog: catalog.map(Lrc::new) }
+    }
+
+    /// Formats `message` with `args`, using the English message if the requested language
+    /// doesn't have it or if it couldn't be formatted.
+    pub fn translate(
+        &self,
+        message: &DiagnosticMessage,
+        args: &DiagnosticArgs,
+    ) -> Result<String, TranslationError> {
+        if let Some(Ok(translated)) = self.catalog.as_ref().map(|c| c.format(message, args)) {
+            return Ok(translated);
+        }
+        match &*FALLBACK_CATALOG {
+            Ok(catalog) => catalog.format(message, args),
+            Err(e) => Err(TranslationError::InvalidFallback(e.to_string())),
+        }
+    }
+}
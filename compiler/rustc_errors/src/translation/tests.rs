@@ -0,0 +1,171 @@
+use super::*;
+
+fn catalog(source: &str) -> MessageCatalog {
+    let mut catalog = MessageCatalog::default();
+    catalog.add_resource(source).unwrap();
+    catalog
+}
+
+fn args(args: &[(&'static str, DiagnosticArgValue)]) -> DiagnosticArgs {
+    args.iter().map(|(name, value)| (Cow::Borrowed(*name), value.clone())).collect()
+}
+
+#[test]
+fn format_messages_and_attributes() {
+    let catalog = catalog(
+        "# A comment.\n\
+         simple = a simple message\n\
+         \n\
+         with-args =\n    \
+             field `{$ident}` specified {$count} times\n    \
+             .label = first use of `{$ident}`\n    \
+             .escaped = literal {\"{\"}braces{\"}\"}\n",
+    );
+    let args =
+        args(&[("ident", "x".into_diagnostic_arg()), ("count", 2usize.into_diagnostic_arg())]);
+
+    let message = DiagnosticMessage::new("simple");
+    assert_eq!(catalog.format(&message, &args).as_deref(), Ok("a simple message"));
+    let message = DiagnosticMessage::new("with-args");
+    assert_eq!(catalog.format(&message, &args).as_deref(), Ok("field `x` specified 2 times"));
+    assert_eq!(
+        catalog.format(&message.with_attr("label"), &args).as_deref(),
+        Ok("first use of `x`")
+    );
+    assert_eq!(
+        catalog.format(&message.with_attr("escaped"), &args).as_deref(),
+        Ok("literal {braces}")
+    );
+
+    // Missing messages, attributes and arguments.
+    let missing = DiagnosticMessage::new("missing");
+    assert_eq!(
+        catalog.format(&missing, &args),
+        Err(TranslationError::MissingMessage(missing.clone()))
+    );
+    assert_eq!(
+        catalog.format(&message.with_attr("missing"), &args),
+        Err(TranslationError::MissingMessage(message.with_attr("missing")))
+    );
+    assert_eq!(
+        catalog.format(&message, &Vec::new()),
+        Err(TranslationError::MissingArgument(message.clone(), "ident".to_owned()))
+    );
+}
+
+#[test]
+fn select_variants() {
+    let catalog = catalog(
+        "items = { $count ->\n    \
+             [0] no items\n    \
+             [one] one item\n   \
+            *[other] {$count} items\n\
+         }\n\
+         kind = { $kind ->\n    \
+             [struct] a struct\n   \
+            *[other] an item\n\
+         } here\n",
+    );
+    let items = DiagnosticMessage::new("items");
+    let format = |message: &DiagnosticMessage, value: DiagnosticArgValue| {
+        let name = if message.id == "items" { "count" } else { "kind" };
+        catalog.format(message, &args(&[(name, value)])).unwrap()
+    };
+    assert_eq!(format(&items, 0usize.into_diagnostic_arg()), "no items");
+    assert_eq!(format(&items, 1usize.into_diagnostic_arg()), "one item");
+    assert_eq!(format(&items, 5usize.into_diagnostic_arg()), "5 items");
+
+    let kind = DiagnosticMessage::new("kind");
+    assert_eq!(format(&kind, "struct".into_diagnostic_arg()), "a struct here");
+    assert_eq!(format(&kind, "enum".into_diagnostic_arg()), "an item here");
+}
+
+#[test]
+fn select_plural_categories_of_the_language() {
+    let mut catalog = MessageCatalog::new("pl").unwrap();
+    catalog
+        .add_resource(
+            "files = { $count ->\n    \
+                 [one] {$count} plik\n    \
+                 [few] {$count} pliki\n   \
+                *[many] {$count} plików\n\
+             }\n",
+        )
+        .unwrap();
+    let files = DiagnosticMessage::new("files");
+    let format = |count: usize| {
+        catalog.format(&files, &args(&[("count", count.into_diagnostic_arg())])).unwrap()
+    };
+    assert_eq!(format(1), "1 plik");
+    assert_eq!(format(3), "3 pliki");
+    assert_eq!(format(22), "22 pliki");
+    assert_eq!(format(5), "5 plików");
+    assert_eq!(format(12), "12 plików");
+
+    // Regional variants use the rules of their language if they don't have their own.
+    assert!(MessageCatalog::new("pl-PL").is_ok());
+    assert!(matches!(MessageCatalog::new("not a locale"), Err(CatalogError::InvalidLocale(_))));
+}
+
+#[test]
+fn later_definitions_override_earlier_ones() {
+    let mut catalog = catalog("message = first\n    .label = first label\n");
+    catalog.add_resource("message = second\n").unwrap();
+    let message = DiagnosticMessage::new("message");
+    assert_eq!(catalog.format(&message, &Vec::new()).as_deref(), Ok("second"));
+    assert!(catalog.format(&message.with_attr("label"), &Vec::new()).is_err());
+}
+
+#[test]
+fn parse_errors() {
+    let error = |source: &str| {
+        let mut catalog = MessageCatalog::default();
+        match catalog.add_resource(source) {
+            Err(CatalogError::Parse(None, line, msg)) => (line, msg),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    };
+    assert_eq!(error("    .label = no message\n"), (1, "expected a message".to_owned()));
+    assert_eq!(error("a = b\nnot a message\n"), (2, "expected a message".to_owned()));
+    assert_eq!(error("a = {$b\n"), (1, "expected `}`".to_owned()));
+    assert_eq!(error("a = b}\n"), (1, "unexpected `}`".to_owned()));
+    assert_eq!(error("a = {b}\n"), (1, "expected a variable or a string literal".to_owned()));
+    assert_eq!(
+        error("a = b\n\nc = { $d ->\n    [one] e\n}\n"),
+        (3, "missing default variant".to_owned())
+    );
+}
+
+#[test]
+fn fallback_to_english() {
+    let message = DiagnosticMessage::new("typeck-unrecognized-atomic-operation");
+    let args = args(&[("op", "fence".into_diagnostic_arg())]);
+    let english = "unrecognized atomic operation function: `fence`";
+    assert_eq!(Translator::default().translate(&message, &args).as_deref(), Ok(english));
+
+    let translator = Translator::new(Some(catalog(
+        "typeck-unrecognized-atomic-operation =\n    \
+             opération atomique inconnue : `{$op}`\n",
+    )));
+    assert_eq!(
+        translator.translate(&message, &args).as_deref(),
+        Ok("opération atomique inconnue : `fence`")
+    );
+    // The translation doesn't have the label, so it is in English.
+    assert_eq!(
+        translator.translate(&message.with_attr("label"), &args).as_deref(),
+        Ok("unrecognized atomic operation")
+    );
+    // Neither has the argument, so the message can't be formatted.
+    assert_eq!(
+        translator.translate(&message, &Vec::new()),
+        Err(TranslationError::MissingArgument(message.clone(), "op".to_owned()))
+    );
+}
+
+#[test]
+fn english_messages_are_valid() {
+    for resource in DEFAULT_LOCALE_RESOURCES {
+        MessageCatalog::default().add_resource(resource).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 use crate::expand::{self, AstFragment, Invocation};
 use crate::module::DirOwnership;
+use crate::proc_macro_sandbox::ProcMacroSandbox;
 
 use rustc_ast::attr::MarkedAttrs;
 use rustc_ast::ptr::P;
@@ -12,9 +13,10 @@
 use rustc_data_structures::sync::{self, Lrc};
 use rustc_errors::{Applicability, DiagnosticBuilder, ErrorGuaranteed, PResult};
 use rustc_lint_defs::builtin::PROC_MACRO_BACK_COMPAT;
-use rustc_lint_defs::BuiltinLintDiagnostics;
+use rustc_lint_defs::{BuiltinLintDiagnostics, LintId};
 use rustc_parse::{self, nt_to_tokenstream, parser, MACRO_ARGUMENTS};
-use rustc_session::{parse::ParseSess, Limit, Session};
+use rustc_session::parse::{ParseSess, TrackedInput};
+use rustc_session::{Limit, Session};
 use rustc_span::def_id::{CrateNum, DefId, LocalDefId};
 use rustc_span::edition::Edition;
 use rustc_span::hygiene::{AstPass, ExpnData, ExpnKind, LocalExpnId};
@@ -935,6 +937,10 @@ fn pre_expansion_lint(
         items: &[P<Item>],
         name: &str,
     );
+
+    /// Looks up the lint named `name`, for the diagnostics that proc macros emit under a lint.
+    /// Returns `None` for unknown lints and lint groups.
+    fn find_lint(&self, name: &str) -> Option<LintId>;
 }
 
 type LintStoreExpandDyn<'a> = Option<&'a (dyn LintStoreExpand + 'a)>;
@@ -993,6 +999,14 @@ pub struct ExtCtxt<'a> {
     /// in the AST, but insert it here so that we know
     /// not to expand it again.
     pub(super) expanded_inert_attrs: MarkedAttrs,
+    /// The sandbox of the proc macros, with `-Z proc-macro-sandbox`.
+    pub(super) proc_macro_sandbox: Option<ProcMacroSandbox>,
+    /// Whether the running proc macro used a part of the proc macro API whose results depend on
+    /// more than its input, or which has side effects, so that its expansion can't be cached.
+    pub(super) proc_macro_uncacheable: bool,
+    /// The inputs declared by the running proc macro, which its expansion is cached with. They
+    /// are only recorded in incremental mode.
+    pub(super) proc_macro_inputs: Vec<TrackedInput>,
 }
 
 impl<'a> ExtCtxt<'a> {
@@ -1021,6 +1035,13 @@ pub fn new(
             force_mode: false,
             expansions: FxHashMap::default(),
             expanded_inert_attrs: MarkedAttrs::new(),
+            proc_macro_sandbox: sess
+                .opts
+                .debugging_opts
+                .proc_macro_sandbox
+                .then(ProcMacroSandbox::default),
+            proc_macro_uncacheable: false,
+            proc_macro_inputs: Vec::new(),
         }
     }
 
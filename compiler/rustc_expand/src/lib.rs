@@ -28,6 +28,8 @@
 pub mod expand;
 pub mod module;
 pub mod proc_macro;
+pub mod proc_macro_cache;
+pub mod proc_macro_sandbox;
 
 crate mod mbe;
 
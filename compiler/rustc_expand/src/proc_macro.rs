@@ -1,4 +1,6 @@
 use crate::base::{self, *};
+use crate::proc_macro_cache::{self, PureProcMacro};
+use crate::proc_macro_sandbox::{self, ProcMacroLocation};
 use crate::proc_macro_server;
 
 use rustc_ast as ast;
@@ -15,6 +17,10 @@
 
 pub struct BangProcMacro {
     pub client: pm::bridge::client::Client<fn(pm::TokenStream) -> pm::TokenStream>,
+    /// Where to load the macro from in the proc macro sandbox, unless it's built into the compiler.
+    pub location: Option<ProcMacroLocation>,
+    /// Whether the macro declared itself pure, so that its expansions can be cached.
+    pub pure: Option<PureProcMacro>,
 }
 
 impl base::ProcMacro for BangProcMacro {
@@ -25,8 +31,18 @@ fn expand<'cx>(
         input: TokenStream,
     ) -> Result<TokenStream, ErrorGuaranteed> {
         let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
-        let server = proc_macro_server::Rustc::new(ecx);
-        self.client.run(&EXEC_STRATEGY, server, input, proc_macro_backtrace).map_err(|e| {
+        let sandbox_location = self.location.as_ref().filter(|_| ecx.proc_macro_sandbox.is_some());
+        let result = proc_macro_cache::expand(ecx, self.pure.as_ref(), &[&input], |ecx| {
+            if let Some(location) = sandbox_location {
+                proc_macro_sandbox::expand(ecx, span, location, &[&input], |strategy, server| {
+                    self.client.run(strategy, server, input.clone(), proc_macro_backtrace)
+                })
+            } else {
+                let server = proc_macro_server::Rustc::new(ecx);
+                self.client.run(&EXEC_STRATEGY, server, input.clone(), proc_macro_backtrace)
+            }
+        });
+        result.map_err(|e| {
             let mut err = ecx.struct_span_err(span, "proc macro panicked");
             if let Some(s) = e.as_str() {
                 err.help(&format!("message: {}", s));
@@ -39,6 +55,10 @@ fn expand<'cx>(
 
 pub struct AttrProcMacro {
     pub client: pm::bridge::client::Client<fn(pm::TokenStream, pm::TokenStream) -> pm::TokenStream>,
+    /// Where to load the macro from in the proc macro sandbox, unless it's built into the compiler.
+    pub location: Option<ProcMacroLocation>,
+    /// Whether the macro declared itself pure, so that its expansions can be cached.
+    pub pure: Option<PureProcMacro>,
 }
 
 impl base::AttrProcMacro for AttrProcMacro {
@@ -50,22 +70,36 @@ fn expand<'cx>(
         annotated: TokenStream,
     ) -> Result<TokenStream, ErrorGuaranteed> {
         let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
-        let server = proc_macro_server::Rustc::new(ecx);
-        self.client
-            .run(&EXEC_STRATEGY, server, annotation, annotated, proc_macro_backtrace)
-            .map_err(|e| {
-                let mut err = ecx.struct_span_err(span, "custom attribute panicked");
-                if let Some(s) = e.as_str() {
-                    err.help(&format!("message: {}", s));
-                }
-                err.emit();
-                ErrorGuaranteed
-            })
+        let inputs = [&annotation, &annotated];
+        let sandbox_location = self.location.as_ref().filter(|_| ecx.proc_macro_sandbox.is_some());
+        let result = proc_macro_cache::expand(ecx, self.pure.as_ref(), &inputs, |ecx| {
+            let (annotation, annotated) = (annotation.clone(), annotated.clone());
+            if let Some(location) = sandbox_location {
+                proc_macro_sandbox::expand(ecx, span, location, &inputs, |strategy, server| {
+                    self.client.run(strategy, server, annotation, annotated, proc_macro_backtrace)
+                })
+            } else {
+                let server = proc_macro_server::Rustc::new(ecx);
+                self.client.run(&EXEC_STRATEGY, server, annotation, annotated, proc_macro_backtrace)
+            }
+        });
+        result.map_err(|e| {
+            let mut err = ecx.struct_span_err(span, "custom attribute panicked");
+            if let Some(s) = e.as_str() {
+                err.help(&format!("message: {}", s));
+            }
+            err.emit();
+            ErrorGuaranteed
+        })
     }
 }
 
 pub struct ProcMacroDerive {
     pub client: pm::bridge::client::Client<fn(pm::TokenStream) -> pm::TokenStream>,
+    /// Where to load the macro from in the proc macro sandbox, unless it's built into the compiler.
+    pub location: Option<ProcMacroLocation>,
+    /// Whether the macro declared itself pure, so that its expansions can be cached.
+    pub pure: Option<PureProcMacro>,
 }
 
 impl MultiItemModifier for ProcMacroDerive {
@@ -100,8 +134,18 @@ fn expand(
         };
 
         let proc_macro_backtrace = ecx.ecfg.proc_macro_backtrace;
-        let server = proc_macro_server::Rustc::new(ecx);
-        let stream = match self.client.run(&EXEC_STRATEGY, server, input, proc_macro_backtrace) {
+        let sandbox_location = self.location.as_ref().filter(|_| ecx.proc_macro_sandbox.is_some());
+        let result = proc_macro_cache::expand(ecx, self.pure.as_ref(), &[&input], |ecx| {
+            if let Some(location) = sandbox_location {
+                proc_macro_sandbox::expand(ecx, span, location, &[&input], |strategy, server| {
+                    self.client.run(strategy, server, input.clone(), proc_macro_backtrace)
+                })
+            } else {
+                let server = proc_macro_server::Rustc::new(ecx);
+                self.client.run(&EXEC_STRATEGY, server, input.clone(), proc_macro_backtrace)
+            }
+        });
+        let stream = match result {
             Ok(stream) => stream,
             Err(e) => {
                 let mut err = ecx.struct_span_err(span, "proc-macro derive panicked");
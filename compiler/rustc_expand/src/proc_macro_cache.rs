@@ -0,0 +1,290 @@
+//! Caching the expansions of proc macros.
+//!
+//! An invocation of a proc macro can reuse the expansion of an earlier invocation of the same
+//! macro with the same input tokens, as long as the macro only depends on its input. The spans of
+//! a cached expansion are relative to its invocation, either as indices in the spans of its input
+//! or as one of the sites of its invocation, and are moved to the corresponding spans of the
+//! invocation reusing it.
+//!
+//! The proc macro sandbox caches the expansions of the macros it runs for the rest of the session
+//! (see [`crate::proc_macro_sandbox`]). In incremental mode, the expansions of the macros which
+//! declare themselves pure with `#[proc_macro_pure]` are saved in the incremental directory too,
+//! keyed by the hash of the crate of the macro and the fingerprint of the input, so that the next
+//! session can reuse them without running the macro.
+//!
+//! The files and environment variables that an invocation declares with `tracked_path::path` and
+//! `tracked_env::var` are saved with its expansion, along with the fingerprints of their contents,
+//! and the next session only reuses the expansion if none of them changed. An edit to one of them
+//! thus only expands the invocations which read it again.
+//!
+//! Invocations which emit diagnostics, or whose output depends on more than their tokens and
+//! declared inputs, like the positions of their spans, aren't cached.
+
+use crate::base::ExtCtxt;
+
+use rustc_ast::token::{DelimToken, Token, TokenKind};
+use rustc_ast::tokenstream::{DelimSpan, Spacing, TokenStream, TokenTree};
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_data_structures::svh::Svh;
+use rustc_serialize::{opaque, Decodable, Encodable};
+use rustc_session::parse::{ProcMacroExpansion, TrackedInput};
+use rustc_span::{Span, Symbol};
+
+use pm::bridge::PanicMessage;
+use std::env;
+use std::hash::Hash;
+use std::mem;
+
+/// A proc macro which declared with `#[proc_macro_pure]` that its expansions only depend on its
+/// input, so that they can be reused across incremental sessions.
+#[derive(Clone, Copy, Debug, Encodable)]
+pub struct PureProcMacro {
+    /// The hash of the crate of the macro, which changes with the code of the macro.
+    pub crate_hash: Svh,
+    /// The index of the macro in the proc macro declarations of its crate.
+    pub index: usize,
+}
+
+/// Expands an invocation of a proc macro on `inputs` with `expand`. If the macro is pure and we
+/// are in incremental mode, reuses the expansion of an invocation with the same input from this
+/// session or the previous one, or saves the expansion for the next session.
+pub(crate) fn expand<'a>(
+    ecx: &mut ExtCtxt<'a>,
+    pure: Option<&PureProcMacro>,
+    inputs: &[&TokenStream],
+    expand: impl FnOnce(&mut ExtCtxt<'a>) -> Result<TokenStream, PanicMessage>,
+) -> Result<TokenStream, PanicMessage> {
+    let pure = match pure {
+        Some(pure) if ecx.sess.opts.incremental.is_some() => pure,
+        _ => return expand(ecx),
+    };
+    let spans = InvocationSpans::new(ecx, inputs);
+    let Some(key) = spans.cache_key(pure, inputs) else {
+        return expand(ecx);
+    };
+
+    let sess = ecx.parse_sess();
+    let cached = {
+        let mut expansions = sess.proc_macro_expansions.lock();
+        if !expansions.contains_key(&key) {
+            // Moving the expansion to the ones of this session saves it for the next session. An
+            // expansion whose inputs changed is dropped, and replaced by the new one below.
+            if let Some(expansion) = sess.prev_proc_macro_expansions.lock().remove(&key) {
+                if expansion.inputs.iter().all(TrackedInput::is_unchanged) {
+                    expansions.insert(key, expansion);
+                }
+            }
+        }
+        expansions.get(&key).map(|expansion| {
+            let trees = Vec::<CachedTree>::decode(&mut opaque::Decoder::new(&expansion.tokens, 0));
+            (expansion.inputs.clone(), trees)
+        })
+    };
+    if let Some((tracked, trees)) = cached {
+        replay_inputs(ecx, tracked);
+        return Ok(spans.instantiate(&trees));
+    }
+
+    let (result, tracked) = run_cacheable(ecx, expand);
+    if let (Ok(output), Some(tracked)) = (&result, tracked) {
+        if let Some(trees) = spans.cache_stream(output) {
+            let mut encoder = opaque::Encoder::new(Vec::new());
+            trees.encode(&mut encoder).unwrap();
+            let expansion = ProcMacroExpansion { inputs: tracked, tokens: encoder.into_inner() };
+            sess.proc_macro_expansions.lock().insert(key, expansion);
+        }
+    }
+    result
+}
+
+/// Runs proc macros with `run`, and returns the inputs they declared if their expansions can be
+/// cached, i.e. if they only used the parts of the proc macro API whose results only depend on
+/// their input and declared inputs, and didn't report errors.
+pub(crate) fn run_cacheable<'a, R>(
+    ecx: &mut ExtCtxt<'a>,
+    run: impl FnOnce(&mut ExtCtxt<'a>) -> R,
+) -> (R, Option<Vec<TrackedInput>>) {
+    let err_count = ecx.sess.err_count();
+    // The macros can expand other macros with `expand_expr`, so this may be a nested expansion.
+    let outer_uncacheable = mem::take(&mut ecx.proc_macro_uncacheable);
+    let outer_inputs = mem::take(&mut ecx.proc_macro_inputs);
+    let result = run(ecx);
+    let uncacheable = ecx.proc_macro_uncacheable;
+    ecx.proc_macro_uncacheable |= outer_uncacheable;
+    let inputs = mem::replace(&mut ecx.proc_macro_inputs, outer_inputs);
+    add_inputs(ecx, &inputs);
+    let cacheable = !uncacheable && ecx.sess.err_count() == err_count;
+    (result, cacheable.then(|| inputs))
+}
+
+/// Declares the inputs of a reused expansion again, as if the macro had run, so that they are
+/// still in the dep-info, and are inputs of the expansion of an outer macro.
+pub(crate) fn replay_inputs(ecx: &mut ExtCtxt<'_>, inputs: Vec<TrackedInput>) {
+    let sess = ecx.parse_sess();
+    for input in &inputs {
+        match input {
+            TrackedInput::File(path, _) => {
+                sess.file_depinfo.borrow_mut().insert(Symbol::intern(path));
+            }
+            TrackedInput::EnvVar(var, _) => {
+                let value = env::var(var).ok();
+                let value = value.as_deref().map(Symbol::intern);
+                sess.env_depinfo.borrow_mut().insert((Symbol::intern(var), value));
+            }
+        }
+    }
+    add_inputs(ecx, &inputs);
+}
+
+fn add_inputs(ecx: &mut ExtCtxt<'_>, inputs: &[TrackedInput]) {
+    for input in inputs {
+        if !ecx.proc_macro_inputs.contains(input) {
+            ecx.proc_macro_inputs.push(input.clone());
+        }
+    }
+}
+
+/// A span of a cached expansion, relative to its invocation.
+#[derive(Clone, Copy, Encodable, Decodable)]
+pub(crate) enum CachedSpan {
+    /// The span with this index in the input of the invocation.
+    Input(u32),
+    CallSite,
+    DefSite,
+    MixedSite,
+}
+
+/// A token tree of a cached expansion, or of the input of an invocation.
+#[derive(Encodable, Decodable)]
+pub(crate) enum CachedTree {
+    Token(TokenKind, CachedSpan, Spacing),
+    Delimited(DelimToken, CachedSpan, CachedSpan, Vec<CachedTree>, Spacing),
+}
+
+/// The spans of an invocation that the spans of cached expansions are relative to.
+pub(crate) struct InvocationSpans {
+    /// The spans of the input, in the order of its tokens. Delimited groups have the spans of their
+    /// opening and closing delimiters, followed by their entire span.
+    input: Vec<Span>,
+    /// The index of the first occurrence of each span of the input.
+    indices: FxHashMap<Span, u32>,
+    call_site: Span,
+    def_site: Span,
+    mixed_site: Span,
+}
+
+impl InvocationSpans {
+    pub(crate) fn new(ecx: &ExtCtxt<'_>, inputs: &[&TokenStream]) -> InvocationSpans {
+        // The same spans as the ones of the proc macro server.
+        let expn_data = ecx.current_expansion.id.expn_data();
+        let mut spans = InvocationSpans {
+            input: Vec::new(),
+            indices: FxHashMap::default(),
+            call_site: ecx.with_call_site_ctxt(expn_data.call_site),
+            def_site: ecx.with_def_site_ctxt(expn_data.def_site),
+            mixed_site: ecx.with_mixed_site_ctxt(expn_data.call_site),
+        };
+        for input in inputs {
+            spans.add_input(input);
+        }
+        spans
+    }
+
+    fn add_input(&mut self, stream: &TokenStream) {
+        for tree in stream.trees() {
+            match tree {
+                TokenTree::Token(token) => self.add_span(token.span),
+                TokenTree::Delimited(span, _, stream) => {
+                    self.add_span(span.open);
+                    self.add_span(span.close);
+                    self.add_span(span.entire());
+                    self.add_input(&stream);
+                }
+            }
+        }
+    }
+
+    fn add_span(&mut self, span: Span) {
+        let index = self.input.len() as u32;
+        self.indices.entry(span).or_insert(index);
+        self.input.push(span);
+    }
+
+    /// Returns the fingerprint of the macro `id` and the input of the invocation, or `None` if the
+    /// input can't be cached. The input is hashed with its spans relative to each other, so that
+    /// the key says which of its tokens have the same span.
+    pub(crate) fn cache_key(
+        &self,
+        id: &impl Encodable<opaque::Encoder>,
+        inputs: &[&TokenStream],
+    ) -> Option<Fingerprint> {
+        let inputs =
+            inputs.iter().map(|input| self.cache_stream(input)).collect::<Option<Vec<_>>>()?;
+        let mut encoder = opaque::Encoder::new(Vec::new());
+        id.encode(&mut encoder).unwrap();
+        inputs.encode(&mut encoder).unwrap();
+        let mut hasher = StableHasher::new();
+        encoder.into_inner().hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    /// Converts a token stream to a cached one, or returns `None` if it has interpolated tokens or
+    /// spans which aren't relative to the invocation.
+    pub(crate) fn cache_stream(&self, stream: &TokenStream) -> Option<Vec<CachedTree>> {
+        let mut trees = Vec::new();
+        let mut cursor = stream.trees();
+        while let Some((tree, spacing)) = cursor.next_with_spacing() {
+            trees.push(match tree {
+                TokenTree::Token(Token { kind: TokenKind::Interpolated(_), .. }) => return None,
+                TokenTree::Token(token) => {
+                    CachedTree::Token(token.kind, self.cache_span(token.span)?, spacing)
+                }
+                TokenTree::Delimited(span, delim, stream) => CachedTree::Delimited(
+                    delim,
+                    self.cache_span(span.open)?,
+                    self.cache_span(span.close)?,
+                    self.cache_stream(&stream)?,
+                    spacing,
+                ),
+            });
+        }
+        Some(trees)
+    }
+
+    fn cache_span(&self, span: Span) -> Option<CachedSpan> {
+        Some(if span == self.call_site {
+            CachedSpan::CallSite
+        } else if span == self.def_site {
+            CachedSpan::DefSite
+        } else if span == self.mixed_site {
+            CachedSpan::MixedSite
+        } else {
+            CachedSpan::Input(*self.indices.get(&span)?)
+        })
+    }
+
+    /// Converts a cached token stream back, with the spans of this invocation.
+    pub(crate) fn instantiate(&self, trees: &[CachedTree]) -> TokenStream {
+        let trees = trees.iter().map(|tree| match *tree {
+            CachedTree::Token(ref kind, span, spacing) => {
+                (TokenTree::token(kind.clone(), self.span(span)), spacing)
+            }
+            CachedTree::Delimited(delim, open, close, ref trees, spacing) => {
+                let span = DelimSpan::from_pair(self.span(open), self.span(close));
+                (TokenTree::Delimited(span, delim, self.instantiate(trees)), spacing)
+            }
+        });
+        TokenStream::new(trees.collect())
+    }
+
+    fn span(&self, span: CachedSpan) -> Span {
+        match span {
+            CachedSpan::Input(index) => self.input[index as usize],
+            CachedSpan::CallSite => self.call_site,
+            CachedSpan::DefSite => self.def_site,
+            CachedSpan::MixedSite => self.mixed_site,
+        }
+    }
+}
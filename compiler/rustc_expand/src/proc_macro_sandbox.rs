@@ -0,0 +1,334 @@
+//! Running proc macros in a sandbox, with `-Z proc-macro-sandbox`.
+//!
+//! Proc macros normally run in the process of the compiler, where they can do anything the
+//! compiler can. In the sandbox, they run in a worker process instead: the compiler starts itself
+//! again with [`WORKER_ENV_VAR`] set and with an empty environment, and the worker loads the
+//! library of the macro and runs it, forwarding the requests of the macro to the proc macro server
+//! in the compiler (see `proc_macro::bridge::server::Remote`). A macro which crashes or exits the
+//! worker is reported as an error instead of taking the compiler down.
+//!
+//! The messages between the compiler and the worker go through the stdin and stdout the worker was
+//! started with. On Unix, the worker replaces them with `/dev/null` and stderr before running any
+//! macro, so that macros which use them directly, or start processes which do, can't corrupt the
+//! messages.
+//!
+//! Macros can only read the environment of the compiler with `proc_macro::tracked_env::var`, which
+//! declares the variables as inputs of the expansion. On Linux, the worker also asks the compiler
+//! whether the macro may access each file it opens, inspects or executes, from any of its threads
+//! or from the processes it starts (see `rustc_metadata::proc_macro_worker`). Only the files the
+//! invocation declared with `proc_macro::tracked_path::path` and the files of the system are
+//! allowed, and the other accesses fail and are reported as errors. The worker isn't restricted
+//! otherwise, so a macro can still use the network.
+//!
+//! The expansions of sandboxed macros are cached for the rest of the session, keyed by the macro
+//! and its input token stream, so the expansion of an invocation with the same tokens as an earlier
+//! one is reused (see [`crate::proc_macro_cache`]).
+
+use crate::base::ExtCtxt;
+use crate::proc_macro_cache::{self, CachedTree, InvocationSpans};
+use crate::proc_macro_server;
+
+use rustc_ast::tokenstream::TokenStream;
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::sync::Lrc;
+use rustc_serialize::{opaque, Decodable, Encodable};
+use rustc_session::parse::TrackedInput;
+use rustc_session::Session;
+use rustc_span::Span;
+
+use pm::bridge::server::Remote;
+use pm::bridge::PanicMessage;
+use std::cell::RefCell;
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{env, fs, mem};
+
+/// The environment variable which makes the compiler run as a proc macro worker.
+pub const WORKER_ENV_VAR: &str = "RUSTC_PROC_MACRO_WORKER";
+
+/// The environment variable of the library search path, which the worker inherits.
+const DYLIB_PATH_VAR: &str = if cfg!(windows) {
+    "PATH"
+} else if cfg!(target_os = "macos") {
+    "DYLD_LIBRARY_PATH"
+} else {
+    "LD_LIBRARY_PATH"
+};
+
+/// Whether the driver of the compiler runs the worker when it's started with [`WORKER_ENV_VAR`],
+/// which other drivers than the ones of rustc and rustdoc may not do.
+static CAN_RUN_WORKER: AtomicBool = AtomicBool::new(false);
+
+/// Notes that the driver of the compiler runs the worker when it's started with
+/// [`WORKER_ENV_VAR`], so that it can be started again as the worker of the sandbox.
+pub fn set_can_run_worker() {
+    CAN_RUN_WORKER.store(true, Ordering::Relaxed);
+}
+
+/// Where to find a proc macro, to load it in a worker.
+#[derive(Clone, Debug, Encodable, Decodable)]
+pub struct ProcMacroLocation {
+    /// The absolute path of the library of the macro.
+    pub dylib: PathBuf,
+    /// The symbol of the proc macro declarations of the library.
+    pub decls_symbol: String,
+    /// The index of the macro in the declarations.
+    pub index: usize,
+}
+
+/// A request of the compiler to a worker, to run a macro.
+#[derive(Encodable, Decodable)]
+pub struct WorkerRequest {
+    pub location: ProcMacroLocation,
+    /// The input of the client of the macro.
+    pub input: Vec<u8>,
+    pub force_show_panics: bool,
+}
+
+/// A message of a worker to the compiler.
+#[derive(Encodable, Decodable)]
+pub enum WorkerMessage {
+    /// A request of the macro to the server, whose response is sent back to the worker.
+    Dispatch(Vec<u8>),
+    /// The output of the client of the macro, which is done.
+    Output(Vec<u8>),
+    /// The macro couldn't be loaded.
+    Error(String),
+    /// The macro is accessing the file at the absolute path, which the compiler answers with
+    /// whether it may.
+    Access(PathBuf),
+}
+
+/// Writes a message to a worker or to the compiler, prefixed with its length.
+pub fn write_message<T: Encodable<opaque::Encoder>>(
+    w: &mut impl Write,
+    message: &T,
+) -> io::Result<()> {
+    let mut encoder = opaque::Encoder::new(Vec::new());
+    message.encode(&mut encoder).unwrap();
+    let bytes = encoder.into_inner();
+    w.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    w.write_all(&bytes)?;
+    w.flush()
+}
+
+/// The length of the longest message, past which the stream is assumed to be corrupted.
+const MAX_MESSAGE_LEN: u64 = 1 << 30;
+
+/// Reads a message written with [`write_message`].
+pub fn read_message<T: for<'a> Decodable<opaque::Decoder<'a>>>(r: &mut impl Read) -> io::Result<T> {
+    let mut len = [0; 8];
+    r.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes is too long", len),
+        ));
+    }
+    let mut bytes = vec![0; len as usize];
+    r.read_exact(&mut bytes)?;
+    Ok(T::decode(&mut opaque::Decoder::new(&bytes, 0)))
+}
+
+/// A worker process, which runs the macros of a crate one after the other.
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Worker {
+    fn spawn() -> io::Result<Worker> {
+        if !CAN_RUN_WORKER.load(Ordering::Relaxed) {
+            // Starting the compiler again would run the whole driver instead of the worker.
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "this compiler driver can't run proc macro workers, since it doesn't call \
+                 `rustc_metadata::proc_macro_worker::run_if_requested`",
+            ));
+        }
+        let mut command = Command::new(env::current_exe()?);
+        command.env_clear().env(WORKER_ENV_VAR, "1");
+        // The worker may need the library search path to find the libraries of the compiler.
+        if let Some(path) = env::var_os(DYLIB_PATH_VAR) {
+            command.env(DYLIB_PATH_VAR, path);
+        }
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Ok(Worker { child, stdin, stdout })
+    }
+
+    /// Runs a macro in the worker, with `dispatch` handling its requests, and `access` deciding
+    /// whether it may access files. Returns its output, or why the worker failed to run it.
+    fn run(
+        &mut self,
+        request: &WorkerRequest,
+        dispatch: &mut dyn FnMut(Vec<u8>) -> Vec<u8>,
+        access: &dyn Fn(&Path) -> bool,
+    ) -> Result<Vec<u8>, String> {
+        self.exchange(request, dispatch, access).unwrap_or_else(|err| {
+            if err.kind() == io::ErrorKind::InvalidData {
+                // The worker may still be running, but the messages can't be told apart anymore.
+                let _ = self.child.kill();
+                return Err(format!("the worker process sent an invalid message: {}", err));
+            }
+            // The worker most likely exited, e.g. because the macro aborted.
+            Err(match self.child.wait() {
+                Ok(status) => format!("the worker process exited unexpectedly ({})", status),
+                Err(_) => format!("couldn't communicate with the worker process: {}", err),
+            })
+        })
+    }
+
+    fn exchange(
+        &mut self,
+        request: &WorkerRequest,
+        dispatch: &mut dyn FnMut(Vec<u8>) -> Vec<u8>,
+        access: &dyn Fn(&Path) -> bool,
+    ) -> io::Result<Result<Vec<u8>, String>> {
+        write_message(&mut self.stdin, request)?;
+        loop {
+            match read_message(&mut self.stdout)? {
+                WorkerMessage::Dispatch(b) => write_message(&mut self.stdin, &dispatch(b))?,
+                WorkerMessage::Output(b) => return Ok(Ok(b)),
+                WorkerMessage::Error(err) => return Ok(Err(err)),
+                WorkerMessage::Access(path) => write_message(&mut self.stdin, &access(&path))?,
+            }
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// The sandbox of the proc macros of a crate.
+#[derive(Default)]
+pub struct ProcMacroSandbox {
+    /// The worker process, started by the first sandboxed invocation.
+    worker: Option<Worker>,
+    /// The expansions of the invocations, with the inputs they declared, by the fingerprint of
+    /// their macro and input.
+    cache: FxHashMap<Fingerprint, (Vec<TrackedInput>, Vec<CachedTree>)>,
+    /// The files the running invocation declared with `tracked_path::path`, which it may access,
+    /// resolved with [`resolve_path`].
+    declared_paths: Lrc<RefCell<Vec<PathBuf>>>,
+}
+
+impl ProcMacroSandbox {
+    /// Notes that the running invocation declared the file at `path`.
+    pub(crate) fn declare_path(&self, path: &Path) {
+        self.declared_paths.borrow_mut().push(resolve_path(path));
+    }
+}
+
+/// The directories of the system, whose files, like shared libraries and configuration, macros and
+/// the processes they start may access without declaring them.
+const SYSTEM_DIRS: &[&str] =
+    &["/bin", "/dev", "/etc", "/lib", "/lib32", "/lib64", "/proc", "/sbin", "/sys", "/usr"];
+
+/// Returns the absolute path of the file at `path`, without symlinks if it exists, so that the
+/// different paths of a file compare equal.
+fn resolve_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| match env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => path.to_owned(),
+    })
+}
+
+/// Whether a macro may access the file at `path` without declaring it, because it's part of the
+/// system, of the sysroot, or of the libraries the worker and its processes may load.
+fn is_system_path(sess: &Session, path: &Path) -> bool {
+    let dylib_path = env::var_os(DYLIB_PATH_VAR).unwrap_or_default();
+    path.parent().is_none()
+        || SYSTEM_DIRS.iter().any(|dir| path.starts_with(dir))
+        || path.starts_with(&sess.sysroot)
+        || env::split_paths(&dylib_path)
+            .any(|dir| !dir.as_os_str().is_empty() && path.starts_with(dir))
+}
+
+/// The strategy running the clients of sandboxed macros in the worker.
+pub(crate) type Strategy<'a> =
+    Remote<&'a dyn Fn(Vec<u8>, bool, &mut dyn FnMut(Vec<u8>) -> Vec<u8>) -> Vec<u8>>;
+
+/// Expands an invocation of the macro at `location` on `inputs` in the sandbox, or reuses the
+/// expansion of an earlier invocation with the same input. `run` runs the client of the macro
+/// with the given strategy and server.
+pub(crate) fn expand(
+    ecx: &mut ExtCtxt<'_>,
+    span: Span,
+    location: &ProcMacroLocation,
+    inputs: &[&TokenStream],
+    run: impl FnOnce(
+        &Strategy<'_>,
+        proc_macro_server::Rustc<'_, '_>,
+    ) -> Result<TokenStream, PanicMessage>,
+) -> Result<TokenStream, PanicMessage> {
+    let spans = InvocationSpans::new(ecx, inputs);
+    let key = spans.cache_key(location, inputs);
+    let sandbox = ecx.proc_macro_sandbox.as_mut().unwrap();
+    if let Some((tracked, trees)) = key.and_then(|key| sandbox.cache.get(&key)) {
+        let (tracked, expansion) = (tracked.clone(), spans.instantiate(trees));
+        proc_macro_cache::replay_inputs(ecx, tracked);
+        return Ok(expansion);
+    }
+
+    let sess = ecx.sess;
+    let worker = RefCell::new(sandbox.worker.take());
+    // The macros can expand other macros with `expand_expr`, so this may be a nested expansion.
+    let declared_paths = Lrc::clone(&sandbox.declared_paths);
+    let outer_declared_paths = mem::take(&mut *declared_paths.borrow_mut());
+    let expn_data = ecx.current_expansion.id.expn_data();
+    let denied_paths = RefCell::new(FxHashSet::default());
+    let access = |path: &Path| {
+        let path = resolve_path(path);
+        let allowed = is_system_path(sess, &path) || declared_paths.borrow().contains(&path);
+        if !allowed && denied_paths.borrow_mut().insert(path.clone()) {
+            let msg = format!(
+                "the proc macro sandbox denied `{}` access to `{}`",
+                expn_data.kind.descr(),
+                path.display()
+            );
+            sess.struct_span_err(expn_data.call_site, &msg)
+                .help(
+                    "macros can declare the files they read with `proc_macro::tracked_path::path`",
+                )
+                .emit();
+        }
+        allowed
+    };
+    let remote = |input, force_show_panics, dispatch: &mut dyn FnMut(Vec<u8>) -> Vec<u8>| {
+        let mut worker = worker.borrow_mut();
+        if worker.is_none() {
+            *worker = Some(Worker::spawn().unwrap_or_else(|err| {
+                sess.span_fatal(span, &format!("couldn't start the proc macro sandbox: {}", err))
+            }));
+        }
+        let request = WorkerRequest { location: location.clone(), input, force_show_panics };
+        let output = worker.as_mut().unwrap().run(&request, dispatch, &access);
+        output.unwrap_or_else(|err| {
+            sess.span_fatal(span, &format!("proc macro failed in the sandbox: {}", err))
+        })
+    };
+    let (result, tracked) = proc_macro_cache::run_cacheable(ecx, |ecx| {
+        run(&Remote(&remote), proc_macro_server::Rustc::new(ecx))
+    });
+
+    *declared_paths.borrow_mut() = outer_declared_paths;
+    let sandbox = ecx.proc_macro_sandbox.as_mut().unwrap();
+    sandbox.worker = worker.into_inner();
+    if let (Ok(output), Some(key), Some(tracked)) = (&result, key, tracked) {
+        if let Some(trees) = spans.cache_stream(output) {
+            sandbox.cache.insert(key, (tracked, trees));
+        }
+    }
+    result
+}
@@ -7,20 +7,21 @@
 use rustc_ast_pretty::pprust;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::sync::Lrc;
-use rustc_errors::{Diagnostic, PResult};
+use rustc_errors::PResult;
 use rustc_lint_defs::builtin::PROC_MACRO_BACK_COMPAT;
 use rustc_lint_defs::BuiltinLintDiagnostics;
 use rustc_parse::lexer::nfc_normalize;
 use rustc_parse::{nt_to_tokenstream, parse_stream_from_source_str};
-use rustc_session::parse::ParseSess;
+use rustc_session::parse::{ParseSess, TrackedInput};
 use rustc_span::def_id::CrateNum;
 use rustc_span::hygiene::ExpnKind;
 use rustc_span::symbol::{self, kw, sym, Symbol};
 use rustc_span::{BytePos, FileName, MultiSpan, Pos, RealFileName, SourceFile, Span};
 
 use pm::bridge::{server, TokenTree};
-use pm::{Delimiter, Level, LineColumn, Spacing};
+use pm::{Applicability, Delimiter, Level, LineColumn, LiteralKind, Spacing};
 use std::ops::Bound;
+use std::path::Path;
 use std::{ascii, panic};
 
 trait FromInternal<T> {
@@ -282,6 +283,18 @@ fn to_internal(self) -> rustc_errors::Level {
     }
 }
 
+impl ToInternal<rustc_errors::Applicability> for Applicability {
+    fn to_internal(self) -> rustc_errors::Applicability {
+        match self {
+            Applicability::MachineApplicable => rustc_errors::Applicability::MachineApplicable,
+            Applicability::MaybeIncorrect => rustc_errors::Applicability::MaybeIncorrect,
+            Applicability::HasPlaceholders => rustc_errors::Applicability::HasPlaceholders,
+            Applicability::Unspecified => rustc_errors::Applicability::Unspecified,
+            _ => unreachable!("unknown proc_macro::Applicability variant: {:?}", self),
+        }
+    }
+}
+
 pub struct FreeFunctions;
 
 #[derive(Clone)]
@@ -355,6 +368,15 @@ pub struct Literal {
     span: Span,
 }
 
+pub struct Diagnostic {
+    diag: rustc_errors::Diagnostic,
+    /// The suggestions, which are only added to `diag` when it is emitted, since their parts are
+    /// sent one by one.
+    suggestions: Vec<(String, Vec<(Span, String)>, rustc_errors::Applicability)>,
+    /// The name of the lint the diagnostic is emitted under, if any.
+    lint: Option<String>,
+}
+
 pub(crate) struct Rustc<'a, 'b> {
     ecx: &'a mut ExtCtxt<'b>,
     def_site: Span,
@@ -381,6 +403,24 @@ fn sess(&self) -> &ParseSess {
         self.ecx.parse_sess()
     }
 
+    /// Records an input of the current invocation, so that a cached expansion of the invocation
+    /// is only reused while the input doesn't change.
+    fn track_input(&mut self, input: impl FnOnce() -> TrackedInput) {
+        if self.ecx.sess.opts.incremental.is_none() {
+            return;
+        }
+        let input = input();
+        if !self.ecx.proc_macro_inputs.contains(&input) {
+            self.ecx.proc_macro_inputs.push(input);
+        }
+    }
+
+    /// Notes that the current invocation used a part of the API whose results depend on more than
+    /// its input, or which has side effects, so that its expansion isn't cached.
+    fn uncacheable(&mut self) {
+        self.ecx.proc_macro_uncacheable = true;
+    }
+
     fn lit(&mut self, kind: token::LitKind, symbol: Symbol, suffix: Option<Symbol>) -> Literal {
         Literal { lit: token::Lit::new(kind, symbol, suffix), span: server::Span::call_site(self) }
     }
@@ -407,10 +447,20 @@ fn track_env_var(&mut self, var: &str, value: Option<&str>) {
             .env_depinfo
             .borrow_mut()
             .insert((Symbol::intern(var), value.map(Symbol::intern)));
+        self.track_input(|| TrackedInput::env_var(var, value));
     }
 
     fn track_path(&mut self, path: &str) {
         self.sess().file_depinfo.borrow_mut().insert(Symbol::intern(path));
+        self.track_input(|| TrackedInput::file(path));
+        if let Some(sandbox) = &self.ecx.proc_macro_sandbox {
+            sandbox.declare_path(Path::new(path));
+        }
+    }
+
+    fn env_var(&mut self, var: &str) -> Option<String> {
+        // Only used by `tracked_env::var`, which then tracks the variable.
+        std::env::var(var).ok()
     }
 }
 
@@ -433,6 +483,7 @@ fn to_string(&mut self, stream: &Self::TokenStream) -> String {
         pprust::tts_to_string(stream)
     }
     fn expand_expr(&mut self, stream: &Self::TokenStream) -> Result<Self::TokenStream, ()> {
+        self.uncacheable();
         // Parse the expression from our tokenstream.
         let expr: PResult<'_, _> = try {
             let mut p = rustc_parse::stream_to_parser(
@@ -641,12 +692,55 @@ fn to_string(&mut self, literal: &Self::Literal) -> String {
     fn debug_kind(&mut self, literal: &Self::Literal) -> String {
         format!("{:?}", literal.lit.kind)
     }
+    fn kind(&mut self, literal: &Self::Literal) -> LiteralKind {
+        match literal.lit.kind {
+            token::Byte => LiteralKind::Byte,
+            token::Char => LiteralKind::Char,
+            // The parser makes integers with a float suffix, like `1f32`, floats.
+            token::Integer if matches!(literal.lit.suffix, Some(sym::f32 | sym::f64)) => {
+                LiteralKind::Float
+            }
+            token::Integer => LiteralKind::Integer,
+            token::Float => LiteralKind::Float,
+            token::Str => LiteralKind::Str,
+            token::StrRaw(n) => LiteralKind::StrRaw(n),
+            token::ByteStr => LiteralKind::ByteStr,
+            token::ByteStrRaw(n) => LiteralKind::ByteStrRaw(n),
+            // `true` and `false` are identifiers in proc macros, so they aren't expected here.
+            token::Bool | token::Err => LiteralKind::Err,
+        }
+    }
     fn symbol(&mut self, literal: &Self::Literal) -> String {
         literal.lit.symbol.to_string()
     }
     fn suffix(&mut self, literal: &Self::Literal) -> Option<String> {
         literal.lit.suffix.as_ref().map(Symbol::to_string)
     }
+    fn value(&mut self, literal: &Self::Literal) -> Result<Vec<u8>, ()> {
+        // The suffix doesn't change the value, and may be one that only the proc macro knows.
+        // Float suffixes are kept, since they make integers floats.
+        let suffix = literal.lit.suffix.filter(|suffix| matches!(*suffix, sym::f32 | sym::f64));
+        let mut lit = token::Lit { suffix, ..literal.lit };
+        let mut sign = "";
+        if let token::Integer | token::Float = lit.kind {
+            // Negative numbers created by proc macros have their sign in the symbol.
+            if let Some(abs) = lit.symbol.as_str().strip_prefix('-') {
+                sign = "-";
+                lit.symbol = Symbol::intern(abs);
+            }
+        }
+        // This unescapes strings and characters like the parser does.
+        let lit = ast::Lit::from_lit_token(lit, literal.span).map_err(|_| ())?;
+        Ok(match lit.kind {
+            ast::LitKind::Str(symbol, _) => symbol.to_string().into_bytes(),
+            ast::LitKind::ByteStr(bytes) => bytes.to_vec(),
+            ast::LitKind::Byte(byte) => vec![byte],
+            ast::LitKind::Char(ch) => ch.to_string().into_bytes(),
+            ast::LitKind::Int(n, _) => format!("{}{}", sign, n).into_bytes(),
+            ast::LitKind::Float(symbol, _) => format!("{}{}", sign, symbol).into_bytes(),
+            ast::LitKind::Bool(_) | ast::LitKind::Err(_) => return Err(()),
+        })
+    }
     fn integer(&mut self, n: &str) -> Self::Literal {
         self.lit(token::Integer, Symbol::intern(n), None)
     }
@@ -758,9 +852,9 @@ fn push(&mut self, spans: &mut Self::MultiSpan, span: Self::Span) {
 
 impl server::Diagnostic for Rustc<'_, '_> {
     fn new(&mut self, level: Level, msg: &str, spans: Self::MultiSpan) -> Self::Diagnostic {
-        let mut diag = Diagnostic::new(level.to_internal(), msg);
+        let mut diag = rustc_errors::Diagnostic::new(level.to_internal(), msg);
         diag.set_span(MultiSpan::from_spans(spans));
-        diag
+        Diagnostic { diag, suggestions: Vec::new(), lint: None }
     }
     fn sub(
         &mut self,
@@ -769,15 +863,76 @@ fn sub(
         msg: &str,
         spans: Self::MultiSpan,
     ) {
-        diag.sub(level.to_internal(), msg, MultiSpan::from_spans(spans), None);
+        diag.diag.sub(level.to_internal(), msg, MultiSpan::from_spans(spans), None);
+    }
+    fn suggestion(&mut self, diag: &mut Self::Diagnostic, msg: &str, applicability: Applicability) {
+        diag.suggestions.push((msg.to_owned(), Vec::new(), applicability.to_internal()));
+    }
+    fn suggestion_part(
+        &mut self,
+        diag: &mut Self::Diagnostic,
+        span: Self::Span,
+        replacement: &str,
+    ) {
+        if let Some((_, parts, _)) = diag.suggestions.last_mut() {
+            parts.push((span, replacement.to_owned()));
+        }
+    }
+    fn set_lint(&mut self, diag: &mut Self::Diagnostic, lint: &str) {
+        diag.lint = Some(lint.to_owned());
     }
     fn emit(&mut self, diag: Self::Diagnostic) {
+        self.uncacheable();
+        let Diagnostic { mut diag, suggestions, lint } = diag;
+        // Suggestions without any part have nothing to show.
+        let suggestions = suggestions.into_iter().filter(|(_, parts, _)| !parts.is_empty());
+
+        if let Some(lint) = lint {
+            match self.ecx.lint_store.and_then(|lint_store| lint_store.find_lint(&lint)) {
+                Some(lint_id) => {
+                    // The level of the lint is only known once the lint levels are computed, after
+                    // expansion, so the lint is buffered like the ones of the compiler. That level
+                    // alone decides whether this is an error, so error and warning children are
+                    // downgraded to notes, as documented on `proc_macro::Diagnostic::set_lint`.
+                    let children = diag
+                        .children
+                        .iter()
+                        .map(|child| {
+                            let is_help = matches!(child.level, rustc_errors::Level::Help);
+                            (is_help, child.message(), child.span.clone())
+                        })
+                        .collect();
+                    self.sess().buffer_lint_with_diagnostic(
+                        lint_id.lint,
+                        diag.span.clone(),
+                        self.ecx.current_expansion.lint_node_id,
+                        &diag.message(),
+                        BuiltinLintDiagnostics::ProcMacroLint {
+                            children,
+                            suggestions: suggestions.collect(),
+                        },
+                    );
+                    return;
+                }
+                None => {
+                    diag.note(&format!(
+                        "this diagnostic was emitted under `{}`, which is not the name of a lint",
+                        lint
+                    ));
+                }
+            }
+        }
+
+        for (msg, parts, applicability) in suggestions {
+            diag.multipart_suggestion(&msg, parts, applicability);
+        }
         self.sess().span_diagnostic.emit_diagnostic(&diag);
     }
 }
 
 impl server::Span for Rustc<'_, '_> {
     fn debug(&mut self, span: Self::Span) -> String {
+        self.uncacheable();
         if self.ecx.ecfg.span_debug {
             format!("{:?}", span)
         } else {
@@ -794,19 +949,24 @@ fn mixed_site(&mut self) -> Self::Span {
         self.mixed_site
     }
     fn source_file(&mut self, span: Self::Span) -> Self::SourceFile {
+        self.uncacheable();
         self.sess().source_map().lookup_char_pos(span.lo()).file
     }
     fn parent(&mut self, span: Self::Span) -> Option<Self::Span> {
+        self.uncacheable();
         span.parent_callsite()
     }
     fn source(&mut self, span: Self::Span) -> Self::Span {
+        self.uncacheable();
         span.source_callsite()
     }
     fn start(&mut self, span: Self::Span) -> LineColumn {
+        self.uncacheable();
         let loc = self.sess().source_map().lookup_char_pos(span.lo());
         LineColumn { line: loc.line, column: loc.col.to_usize() }
     }
     fn end(&mut self, span: Self::Span) -> LineColumn {
+        self.uncacheable();
         let loc = self.sess().source_map().lookup_char_pos(span.hi());
         LineColumn { line: loc.line, column: loc.col.to_usize() }
     }
@@ -817,6 +977,7 @@ fn after(&mut self, span: Self::Span) -> Self::Span {
         span.shrink_to_hi()
     }
     fn join(&mut self, first: Self::Span, second: Self::Span) -> Option<Self::Span> {
+        self.uncacheable();
         let self_loc = self.sess().source_map().lookup_char_pos(first.lo());
         let other_loc = self.sess().source_map().lookup_char_pos(second.lo());
 
@@ -827,9 +988,11 @@ fn join(&mut self, first: Self::Span, second: Self::Span) -> Option<Self::Span>
         Some(first.to(second))
     }
     fn resolved_at(&mut self, span: Self::Span, at: Self::Span) -> Self::Span {
+        self.uncacheable();
         span.with_ctxt(at.ctxt())
     }
     fn source_text(&mut self, span: Self::Span) -> Option<String> {
+        self.uncacheable();
         self.sess().source_map().span_to_snippet(span).ok()
     }
     /// Saves the provided span into the metadata of
@@ -857,6 +1020,7 @@ fn source_text(&mut self, span: Self::Span) -> Option<String> {
     /// since we've loaded `my_proc_macro` from disk in order to execute it).
     /// In this way, we have obtained a span pointing into `my_proc_macro`
     fn save_span(&mut self, span: Self::Span) -> usize {
+        self.uncacheable();
         self.sess().save_proc_macro_span(span)
     }
     fn recover_proc_macro_span(&mut self, id: usize) -> Self::Span {
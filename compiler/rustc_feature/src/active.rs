@@ -477,6 +477,9 @@ pub fn set(&self, features: &mut Features, span: Span) {
     (active, precise_pointer_size_matching, "1.32.0", Some(56354), None),
     /// Allows macro attributes on expressions, statements and non-inline modules.
     (active, proc_macro_hygiene, "1.30.0", Some(54727), None),
+    /// Allows proc macros to declare that their expansions only depend on their input with
+    /// `#[proc_macro_pure]`.
+    (active, proc_macro_pure, "1.61.0", None, None),
     /// Allows the use of raw-dylibs (RFC 2627).
     (incomplete, raw_dylib, "1.40.0", Some(58713), None),
     /// Allows `&raw const $place_expr` and `&raw mut $place_expr` expressions.
@@ -499,6 +502,8 @@ pub fn set(&self, features: &mut Features, span: Span) {
     (active, stmt_expr_attributes, "1.6.0", Some(15701), None),
     /// Allows the use of `#[target_feature]` on safe functions.
     (active, target_feature_11, "1.45.0", Some(69098), None),
+    /// Allows re-running a failing `#[test]` with `#[retries = "N"]`.
+    (active, test_retries, "1.61.0", None, None),
     /// Allows using `#[thread_local]` on `static` items.
     (active, thread_local, "1.0.0", Some(29594), None),
     /// Allows defining `trait X = A + B;` alias items.
@@ -404,6 +404,10 @@ pub struct BuiltinAttribute {
         test_runner, CrateLevel, template!(List: "path"), ErrorFollowing, custom_test_frameworks,
         "custom test frameworks are an unstable feature",
     ),
+    gated!(
+        retries, Normal, template!(NameValueStr: "N"), ErrorFollowing, test_retries,
+        experimental!(retries),
+    ),
     // RFC #1268
     gated!(
         marker, Normal, template!(Word), WarnFollowing, marker_trait_attr, experimental!(marker)
@@ -448,6 +452,9 @@ pub struct BuiltinAttribute {
         "`default_method_body_is_const` is a temporary placeholder for declaring default bodies \
         as `const`, which may be removed or renamed in the future."
     ),
+    gated!(
+        proc_macro_pure, Normal, template!(Word), WarnFollowing, experimental!(proc_macro_pure)
+    ),
 
     // ==========================================================================
     // Internal attributes: Stability, deprecation, and unsafe:
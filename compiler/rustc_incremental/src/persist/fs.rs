@@ -126,6 +126,7 @@
 const STAGING_DEP_GRAPH_FILENAME: &str = "dep-graph.part.bin";
 const WORK_PRODUCTS_FILENAME: &str = "work-products.bin";
 const QUERY_CACHE_FILENAME: &str = "query-cache.bin";
+const PROC_MACRO_EXPANSIONS_FILENAME: &str = "proc-macro-expansions.bin";
 
 // We encode integers using the following base, so they are shorter than decimal
 // or hexadecimal numbers (we want short file and directory names). Since these
@@ -151,6 +152,10 @@ pub fn work_products_path(sess: &Session) -> PathBuf {
 pub fn query_cache_path(sess: &Session) -> PathBuf {
     in_incr_comp_dir_sess(sess, QUERY_CACHE_FILENAME)
 }
+/// Returns the path to the expansions of a session's pure proc macros.
+pub fn proc_macro_expansions_path(sess: &Session) -> PathBuf {
+    in_incr_comp_dir_sess(sess, PROC_MACRO_EXPANSIONS_FILENAME)
+}
 
 /// Locks a given session directory.
 pub fn lock_file_path(session_dir: &Path) -> PathBuf {
@@ -7,6 +7,7 @@
 use rustc_serialize::opaque::Decoder;
 use rustc_serialize::Decodable;
 use rustc_session::config::IncrementalStateAssertion;
+use rustc_session::parse::{ProcMacroExpansions, TrackedInput};
 use rustc_session::Session;
 use std::path::Path;
 
@@ -241,3 +242,62 @@ pub fn load_query_result_cache<'a, C: OnDiskCache<'a>>(sess: &'a Session) -> Opt
         _ => Some(C::new_empty(sess.source_map())),
     }
 }
+
+/// Loads the expansions of the pure proc macros of the previous session.
+///
+/// If we are not in incremental compilation mode, or if the expansions could not be loaded,
+/// returns an empty map. With `-Zincremental-info`, reports the inputs tracked by the expansions
+/// that changed since then.
+pub fn load_proc_macro_expansions(sess: &Session) -> ProcMacroExpansions {
+    if sess.opts.incremental.is_none() {
+        return Default::default();
+    }
+
+    let _prof_timer = sess.prof.generic_activity("incr_comp_load_proc_macro_expansions");
+
+    let expansions: ProcMacroExpansions =
+        load_proc_macro_data(sess, &proc_macro_expansions_path(sess));
+
+    if sess.opts.debugging_opts.incremental_info {
+        eprintln!("[incremental] loaded {} cached proc macro expansions", expansions.len());
+        let changed = |input: &TrackedInput| !input.is_unchanged();
+        let outdated =
+            expansions.values().filter(|expansion| expansion.inputs.iter().any(changed)).count();
+        eprintln!(
+            "[incremental] the tracked inputs of {} of {} cached proc macro expansions changed",
+            outdated,
+            expansions.len()
+        );
+        let mut changed_inputs: Vec<_> = expansions
+            .values()
+            .flat_map(|expansion| &expansion.inputs)
+            .filter(|input| changed(input))
+            .map(|input| input.to_string())
+            .collect();
+        changed_inputs.sort();
+        changed_inputs.dedup();
+        for input in changed_inputs {
+            eprintln!("[incremental] proc macro input changed: {}", input);
+        }
+    }
+
+    expansions
+}
+
+/// Loads data about proc macros saved by the previous session, or returns the default value if
+/// it couldn't be loaded or was saved with different command line arguments.
+fn load_proc_macro_data<T: Default + for<'a> Decodable<Decoder<'a>>>(
+    sess: &Session,
+    path: &Path,
+) -> T {
+    let LoadResult::Ok { data: (bytes, start_pos) } =
+        load_data(sess.opts.debugging_opts.incremental_info, path, sess.is_nightly_build())
+    else {
+        return T::default();
+    };
+    let mut decoder = Decoder::new(&bytes, start_pos);
+    if u64::decode(&mut decoder) != sess.opts.dep_tracking_hash(false) {
+        return T::default();
+    }
+    T::decode(&mut decoder)
+}
@@ -43,6 +43,13 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
             tcx.dep_graph.print_incremental_info()
         }
 
+        sess.time("incr_comp_persist_proc_macro_expansions", || {
+            let path = proc_macro_expansions_path(sess);
+            file_format::save_in(sess, path, "proc macro expansions", |e| {
+                encode_proc_macro_expansions(sess, e)
+            });
+        });
+
         join(
             move || {
                 sess.time("incr_comp_persist_result_cache", || {
@@ -140,6 +147,12 @@ fn encode_work_product_index(
     serialized_products.encode(encoder)
 }
 
+fn encode_proc_macro_expansions(sess: &Session, encoder: &mut FileEncoder) -> FileEncodeResult {
+    // Only the expansions used by this session are kept, so that the file doesn't keep growing.
+    sess.opts.dep_tracking_hash(false).encode(encoder)?;
+    sess.parse_sess.proc_macro_expansions.lock().encode(encoder)
+}
+
 fn encode_query_cache(tcx: TyCtxt<'_>, encoder: &mut FileEncoder) -> FileEncodeResult {
     tcx.sess.time("incr_comp_serialize_result_cache", || tcx.serialize_query_result_cache(encoder))
 }
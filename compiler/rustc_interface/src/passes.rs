@@ -216,6 +216,11 @@ pub fn register_plugins<'a>(
         });
     }
 
+    let prev_proc_macro_expansions = sess.time("incr_comp_load_proc_macro_expansions", || {
+        rustc_incremental::load_proc_macro_expansions(sess)
+    });
+    *sess.parse_sess.prev_proc_macro_expansions.lock() = prev_proc_macro_expansions;
+
     let mut lint_store = rustc_lint::new_lint_store(
         sess.opts.debugging_opts.no_interleave_lints,
         sess.unstable_options(),
@@ -269,6 +274,13 @@ fn pre_expansion_lint(
     ) {
         pre_expansion_lint(sess, self.0, registered_tools, (node_id, attrs, items), name);
     }
+
+    fn find_lint(&self, name: &str) -> Option<lint::LintId> {
+        if self.0.is_lint_group(Symbol::intern(name)) {
+            return None;
+        }
+        self.0.find_lints(name).ok()?.into_iter().next()
+    }
 }
 
 /// Runs the "early phases" of the compiler: initial `cfg` processing, loading compiler plugins,
@@ -15,7 +15,7 @@
 use rustc_session::config::{CFGuard, ExternEntry, LinkerPluginLto, LtoCli, SwitchWithOptPath};
 use rustc_session::lint::Level;
 use rustc_session::search_paths::SearchPath;
-use rustc_session::utils::{CanonicalizedPath, NativeLib, NativeLibKind};
+use rustc_session::utils::{CanonicalizedPath, NativeLib, NativeLibKind, TrackedFile};
 use rustc_session::{build_session, getopts, DiagnosticOutput, Session};
 use rustc_span::edition::{Edition, DEFAULT_EDITION};
 use rustc_span::symbol::sym;
@@ -655,7 +655,9 @@ macro_rules! untracked {
     untracked!(dump_mir_dir, String::from("abc"));
     untracked!(dump_mir_exclude_pass_number, true);
     untracked!(dump_mir_graphviz, true);
+    untracked!(emit_lint_baseline, Some(PathBuf::from("lint-baseline.json")));
     untracked!(emit_stack_sizes, true);
+    untracked!(emit_type_sizes, Some(PathBuf::from("type-sizes")));
     untracked!(future_incompat_test, true);
     untracked!(hir_stats, true);
     untracked!(identify_regions, true);
@@ -766,6 +768,7 @@ macro_rules! tracked {
     tracked!(polonius, true);
     tracked!(precise_enum_drop_elaboration, false);
     tracked!(print_fuel, Some("abc".to_string()));
+    tracked!(proc_macro_sandbox, true);
     tracked!(profile, true);
     tracked!(profile_emit, Some(PathBuf::from("abc")));
     tracked!(profiler_runtime, "abc".to_string());
@@ -805,7 +808,32 @@ macro_rules! tracked_no_crate_hash {
             assert_non_crate_hash_different(&reference, &opts);
         };
     }
+    tracked_no_crate_hash!(lint_baseline, Some(TrackedFile::new(Path::new("lint-baseline.json"))));
     tracked_no_crate_hash!(no_codegen, true);
+    tracked_no_crate_hash!(
+        translate_additional_ftl,
+        Some(TrackedFile::new(Path::new("messages.ftl")))
+    );
+    tracked_no_crate_hash!(translate_lang, Some(String::from("de")));
+}
+
+#[test]
+fn test_tracked_file_contents() {
+    let path = std::env::temp_dir().join(format!("tracked-file-{}.txt", std::process::id()));
+    let mut reference = Options::default();
+    let mut opts = Options::default();
+
+    std::fs::write(&path, "before").unwrap();
+    reference.debugging_opts.lint_baseline = Some(TrackedFile::new(&path));
+    opts.debugging_opts.lint_baseline = Some(TrackedFile::new(&path));
+    assert_same_hash(&reference, &opts);
+
+    // Changing the contents of the file, without changing its path, changes the hash.
+    std::fs::write(&path, "after").unwrap();
+    opts.debugging_opts.lint_baseline = Some(TrackedFile::new(&path));
+    assert_non_crate_hash_different(&reference, &opts);
+
+    std::fs::remove_file(&path).unwrap();
 }
 
 #[test]
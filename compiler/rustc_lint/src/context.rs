@@ -808,6 +808,18 @@ fn lookup_with_diagnostics(
                         db.span_suggestion(span, "did you mean", format!("{punctuation}{best_match}{punctuation}"), Applicability::MaybeIncorrect);
                     }
                 },
+                BuiltinLintDiagnostics::ProcMacroLint { children, suggestions } => {
+                    for (is_help, msg, span) in children {
+                        if is_help {
+                            db.span_help(span, &msg);
+                        } else {
+                            db.span_note(span, &msg);
+                        }
+                    }
+                    for (msg, parts, applicability) in suggestions {
+                        db.multipart_suggestion(&msg, parts, applicability);
+                    }
+                }
             }
             // Rewrap `db`, and pass control to the user.
             decorate(LintDiagnosticBuilder::new(db));
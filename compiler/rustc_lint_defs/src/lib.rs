@@ -311,6 +311,12 @@ pub enum BuiltinLintDiagnostics {
     NamedAsmLabel(String),
     UnicodeTextFlow(Span, String),
     UnexpectedCfg(Span, Symbol, Option<Symbol>),
+    /// A diagnostic that a proc macro emitted under a lint. `children` are its notes, and its
+    /// help messages, which are marked with `true`. Children of any other level are notes too.
+    ProcMacroLint {
+        children: Vec<(bool, String, MultiSpan)>,
+        suggestions: Vec<(String, Vec<(Span, String)>, Applicability)>,
+    },
 }
 
 /// Lints that are buffered up early on in the `Session` before the
@@ -66,6 +66,7 @@ pub fn newtype_index(input: TokenStream) -> TokenStream {
         message,
         lint,
         error,
+        slug,
         label,
         suggestion,
         suggestion_short,
@@ -39,6 +39,26 @@
 ///     opt_sugg: Some(suggestion, Applicability::MachineApplicable),
 /// });
 /// ```
+///
+/// To make a diagnostic translatable, give it a `#[slug = "..."]` identifying its message in the
+/// message catalogs (see `rustc_errors::translation`). The message of the diagnostic is then the
+/// value of that catalog message, the strings in `#[label = "..."]`, `#[message = "..."]` and the
+/// `message` of suggestions name attributes of it, and all fields without attributes are passed
+/// to it as arguments:
+/// ```ignore (pseudo-rust)
+/// #[derive(SessionDiagnostic)]
+/// #[error = "E0062"]
+/// #[slug = "typeck-field-multiply-specified-in-initializer"]
+/// pub struct FieldMultiplySpecifiedInInitializer {
+///     // The span of the message, with a label from its `.label` attribute.
+///     #[message]
+///     #[label]
+///     pub span: Span,
+///     #[label = "previous-use-label"]
+///     pub prev_span: Span,
+///     pub ident: Ident,
+/// }
+/// ```
 pub fn session_diagnostic_derive(s: synstructure::Structure<'_>) -> proc_macro2::TokenStream {
     // Names for the diagnostic we build and the session we build it from.
     let diag = format_ident!("diag");
@@ -149,7 +169,13 @@ fn new(diag: syn::Ident, sess: syn::Ident, structure: synstructure::Structure<'a
         }
 
         Self {
-            builder: SessionDiagnosticDeriveBuilder { diag, sess, fields: fields_map, kind: None },
+            builder: SessionDiagnosticDeriveBuilder {
+                diag,
+                sess,
+                fields: fields_map,
+                kind: None,
+                slug: None,
+            },
             structure,
         }
     }
@@ -191,6 +217,29 @@ fn into_tokens(self) -> proc_macro2::TokenStream {
                         #(#result);*
                     };
                 });
+                // The fields without attributes are the arguments of translated messages.
+                let translation = builder.slug.as_ref().map(|slug| {
+                    let args = structure.variants()[0].bindings().iter().filter_map(|binding| {
+                        let field = binding.ast();
+                        let ident = field.ident.as_ref()?;
+                        if field.attrs.iter().any(is_field_attr) {
+                            return None;
+                        }
+                        let name = ident.to_string();
+                        Some(quote! {
+                            (
+                                std::borrow::Cow::Borrowed(#name),
+                                rustc_errors::IntoDiagnosticArg::into_diagnostic_arg(
+                                    Clone::clone(&self.#ident)
+                                ),
+                            )
+                        })
+                    });
+                    quote! {
+                        let __session_diagnostic_message = rustc_errors::DiagnosticMessage::new(#slug);
+                        let __session_diagnostic_args: rustc_errors::DiagnosticArgs = vec![#(#args),*];
+                    }
+                });
                 // Finally, putting it altogether.
                 match builder.kind {
                     None => {
@@ -205,6 +254,7 @@ fn into_tokens(self) -> proc_macro2::TokenStream {
                             let (diag, sess) = (&builder.diag, &builder.sess);
                             quote! {
                                 let mut #diag = #sess.struct_err_with_code("", rustc_errors::DiagnosticId::Error(#code));
+                                #translation
                                 #preamble
                                 match self {
                                     #body
@@ -240,6 +290,22 @@ fn into_diagnostic(
     }
 }
 
+/// Checks whether `attr` is one of the attributes of `#[derive(SessionDiagnostic)]` that can be
+/// applied to fields.
+fn is_field_attr(attr: &syn::Attribute) -> bool {
+    attr.path.segments.last().map_or(false, |segment| {
+        matches!(
+            segment.ident.to_string().as_str(),
+            "message"
+                | "label"
+                | "suggestion"
+                | "suggestion_short"
+                | "suggestion_hidden"
+                | "suggestion_verbose"
+        )
+    })
+}
+
 /// Field information passed to the builder. Deliberately omits attrs to discourage the generate_*
 /// methods from walking the attributes themselves.
 struct FieldInfo<'a> {
@@ -268,6 +334,10 @@ struct SessionDiagnosticDeriveBuilder<'a> {
     /// stores at what Span the kind was first set at (for error reporting purposes, if the kind
     /// was multiply specified).
     kind: Option<(DiagnosticId, proc_macro2::Span)>,
+
+    /// Identifier of the message of the diagnostic in the message catalogs, if it is
+    /// translatable.
+    slug: Option<String>,
 }
 
 impl<'a> SessionDiagnosticDeriveBuilder<'a> {
@@ -287,6 +357,19 @@ fn generate_structure_code(
                             #diag.set_primary_message(#formatted_str);
                         }
                     }
+                    "slug" => {
+                        if self.slug.is_some() {
+                            throw_span_err!(s.span().unwrap(), "`slug` specified multiple times");
+                        }
+                        self.slug = Some(s.value());
+                        let (diag, sess) = (&self.diag, &self.sess);
+                        quote! {
+                            #diag.set_primary_message(#sess.translate(
+                                &__session_diagnostic_message,
+                                &__session_diagnostic_args,
+                            ));
+                        }
+                    }
                     attr @ "error" | attr @ "lint" => {
                         self.set_kind_once(
                             if attr == "error" {
@@ -385,7 +468,11 @@ fn generate_non_option_field_code(
         let meta = attr.parse_meta()?;
         Ok(match meta {
             syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(s), .. }) => {
-                let formatted_str = self.build_format(&s.value(), attr.span());
+                let formatted_str = if self.slug.is_some() {
+                    self.translate_attr(&s.value())
+                } else {
+                    self.build_format(&s.value(), attr.span())
+                };
                 match name {
                     "message" => {
                         if type_matches_path(&info.ty, &["rustc_span", "Span"]) {
@@ -421,6 +508,44 @@ fn generate_non_option_field_code(
                     ),
                 }
             }
+            syn::Meta::Path(_) if self.slug.is_some() => match name {
+                "message" => {
+                    if type_matches_path(&info.ty, &["rustc_span", "Span"]) {
+                        quote! {
+                            #diag.set_span(*#field_binding);
+                        }
+                    } else {
+                        throw_span_err!(
+                            attr.span().unwrap(),
+                            "the `#[message]` attribute can only be applied to fields of type Span"
+                        );
+                    }
+                }
+                "label" => {
+                    if type_matches_path(&info.ty, &["rustc_span", "Span"]) {
+                        let label = self.translate_attr("label");
+                        quote! {
+                            #diag.span_label(*#field_binding, #label);
+                        }
+                    } else {
+                        throw_span_err!(
+                            attr.span().unwrap(),
+                            "The `#[label]` attribute can only be applied to fields of type Span"
+                        );
+                    }
+                }
+                other => throw_span_err!(
+                    attr.span().unwrap(),
+                    &format!("`#[{}]` is not a valid SessionDiagnostic field attribute", other)
+                ),
+            },
+            syn::Meta::Path(_) => throw_span_err!(
+                attr.span().unwrap(),
+                &format!("`#[{}]` can only be used on translatable diagnostics", name),
+                |diag| diag.help(
+                    "use the [slug = \"...\"] attribute to make this diagnostic translatable"
+                )
+            ),
             syn::Meta::List(list) => {
                 match list.path.segments.iter().last().unwrap().ident.to_string().as_str() {
                     suggestion_kind @ "suggestion"
@@ -512,13 +637,15 @@ fn generate_non_option_field_code(
                                         .ident
                                         .to_string();
                                     let name = name.as_str();
-                                    let formatted_str = self.build_format(&s.value(), arg.span());
                                     match name {
+                                        "message" if self.slug.is_some() => {
+                                            msg = Some(self.translate_attr(&s.value()));
+                                        }
                                         "message" => {
-                                            msg = Some(formatted_str);
+                                            msg = Some(self.build_format(&s.value(), arg.span()));
                                         }
                                         "code" => {
-                                            code = Some(formatted_str);
+                                            code = Some(self.build_format(&s.value(), arg.span()));
                                         }
                                         other => throw_span_err!(
                                             arg.span().unwrap(),
@@ -533,6 +660,9 @@ fn generate_non_option_field_code(
                         }
                         let msg = if let Some(msg) = msg {
                             quote!(#msg.as_str())
+                        } else if self.slug.is_some() {
+                            let msg = self.translate_attr("suggestion");
+                            quote!(#msg.as_str())
                         } else {
                             throw_span_err!(
                                 list.span().unwrap(),
@@ -559,6 +689,18 @@ fn generate_non_option_field_code(
         })
     }
 
+    /// Builds the translation of the attribute `attr` of the message of a translatable
+    /// diagnostic.
+    fn translate_attr(&self, attr: &str) -> proc_macro2::TokenStream {
+        let sess = &self.sess;
+        quote! {
+            #sess.translate(
+                &__session_diagnostic_message.with_attr(#attr),
+                &__session_diagnostic_args,
+            )
+        }
+    }
+
     /// In the strings in the attributes supplied to this macro, we want callers to be able to
     /// reference fields in the format string. Take this, for example:
     /// ```ignore (not-usage-example)
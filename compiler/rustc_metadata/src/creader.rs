@@ -392,7 +392,7 @@ fn register_crate(
 
         let cnum_map = self.resolve_crate_deps(root, &crate_root, &metadata, cnum, dep_kind)?;
 
-        let raw_proc_macros = if crate_root.is_proc_macro_crate() {
+        let (raw_proc_macros, proc_macro_dylib) = if crate_root.is_proc_macro_crate() {
             let temp_root;
             let (dlsym_source, dlsym_root) = match &host_lib {
                 Some(host_lib) => (&host_lib.source, {
@@ -402,9 +402,12 @@ fn register_crate(
                 None => (&source, &crate_root),
             };
             let dlsym_dylib = dlsym_source.dylib.as_ref().expect("no dylib for a proc-macro crate");
-            Some(self.dlsym_proc_macros(&dlsym_dylib.0, dlsym_root.stable_crate_id())?)
+            // Make sure the path contains a / or the linker will search for it.
+            let path = env::current_dir().unwrap().join(&dlsym_dylib.0);
+            let sym_name = self.sess.generate_proc_macro_decls_symbol(dlsym_root.stable_crate_id());
+            (Some(dlsym_proc_macros(&path, &sym_name)?), Some((path, sym_name)))
         } else {
-            None
+            (None, None)
         };
 
         // Perform some verification *after* resolve_crate_deps() above is
@@ -420,6 +423,7 @@ fn register_crate(
             metadata,
             crate_root,
             raw_proc_macros,
+            proc_macro_dylib,
             cnum,
             cnum_map,
             dep_kind,
@@ -654,28 +658,6 @@ fn resolve_crate_deps(
         Ok(crate_num_map)
     }
 
-    fn dlsym_proc_macros(
-        &self,
-        path: &Path,
-        stable_crate_id: StableCrateId,
-    ) -> Result<&'static [ProcMacro], CrateError> {
-        // Make sure the path contains a / or the linker will search for it.
-        let path = env::current_dir().unwrap().join(path);
-        let lib = unsafe { libloading::Library::new(path) }
-            .map_err(|err| CrateError::DlOpen(err.to_string()))?;
-
-        let sym_name = self.sess.generate_proc_macro_decls_symbol(stable_crate_id);
-        let sym = unsafe { lib.get::<*const &[ProcMacro]>(sym_name.as_bytes()) }
-            .map_err(|err| CrateError::DlSym(err.to_string()))?;
-
-        // Intentionally leak the dynamic library. We can't ever unload it
-        // since the library can make things that will live arbitrarily long.
-        let sym = unsafe { sym.into_raw() };
-        std::mem::forget(lib);
-
-        Ok(unsafe { **sym })
-    }
-
     fn inject_panic_runtime(&mut self, krate: &ast::Crate) {
         // If we're only compiling an rlib, then there's no need to select a
         // panic runtime, so we just skip this section entirely.
@@ -1041,3 +1023,23 @@ fn visit_item(&mut self, item: &'ast ast::Item) {
     visit::walk_crate(&mut f, krate);
     f.spans
 }
+
+/// Loads the proc macros declared with the symbol `sym_name` in the library at `path`, which must
+/// contain a `/`.
+crate fn dlsym_proc_macros(
+    path: &Path,
+    sym_name: &str,
+) -> Result<&'static [ProcMacro], CrateError> {
+    let lib = unsafe { libloading::Library::new(path) }
+        .map_err(|err| CrateError::DlOpen(err.to_string()))?;
+
+    let sym = unsafe { lib.get::<*const &[ProcMacro]>(sym_name.as_bytes()) }
+        .map_err(|err| CrateError::DlSym(err.to_string()))?;
+
+    // Intentionally leak the dynamic library. We can't ever unload it
+    // since the library can make things that will live arbitrarily long.
+    let sym = unsafe { sym.into_raw() };
+    std::mem::forget(lib);
+
+    Ok(unsafe { **sym })
+}
@@ -1,6 +1,7 @@
 #![doc(html_root_url = "https://doc.rust-lang.org/nightly/nightly-rustc/")]
 #![feature(crate_visibility_modifier)]
 #![feature(drain_filter)]
+#![feature(internal_output_capture)]
 #![feature(let_else)]
 #![feature(nll)]
 #![feature(once_cell)]
@@ -29,5 +30,6 @@
 
 pub mod creader;
 pub mod locator;
+pub mod proc_macro_worker;
 
 pub use rmeta::{encode_metadata, EncodedMetadata, METADATA_HEADER};
@@ -0,0 +1,167 @@
+//! The worker process of the proc macro sandbox, see `rustc_expand::proc_macro_sandbox`.
+
+use crate::creader::dlsym_proc_macros;
+use crate::locator::CrateError;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_expand::proc_macro_sandbox::{self, read_message, write_message};
+use rustc_expand::proc_macro_sandbox::{WorkerMessage, WorkerRequest, WORKER_ENV_VAR};
+
+use proc_macro::bridge::client::ProcMacro;
+use std::env;
+#[cfg(unix)]
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod access_checks;
+
+/// Runs the worker instead of the compiler if the compiler was started as a proc macro worker.
+///
+/// Drivers must call this before anything else to support `-Z proc-macro-sandbox`, which starts
+/// the executable of the compiler again as the worker.
+pub fn run_if_requested() {
+    if env::var_os(WORKER_ENV_VAR).is_some() {
+        main()
+    }
+    proc_macro_sandbox::set_can_run_worker();
+}
+
+/// Runs the proc macros the compiler requests on stdin, until it closes it.
+fn main() -> ! {
+    let Ok((input, output)) = take_protocol_streams() else { process::exit(1) };
+    let channel = Arc::new(Mutex::new(Channel { input, output }));
+    #[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        let channel = Arc::clone(&channel);
+        // Without the checks, the macros can access any file, as with other operating systems.
+        let _ = access_checks::install(move |path| check_access(&channel, path));
+    }
+
+    let mut libs: FxHashMap<(PathBuf, String), &'static [ProcMacro]> = FxHashMap::default();
+    let result: io::Result<()> = try {
+        loop {
+            let request = read_message::<WorkerRequest>(&mut channel.lock().unwrap().input);
+            let Ok(request) = request else { break };
+            let location = request.location;
+            let key = (location.dylib, location.decls_symbol);
+            let macros = match libs.get(&key) {
+                Some(macros) => *macros,
+                None => match dlsym_proc_macros(&key.0, &key.1) {
+                    Ok(macros) => *libs.entry(key).or_insert(macros),
+                    Err(CrateError::DlOpen(err) | CrateError::DlSym(err)) => {
+                        let message = WorkerMessage::Error(err);
+                        write_message(&mut channel.lock().unwrap().output, &message)?;
+                        continue;
+                    }
+                    Err(_) => unreachable!(),
+                },
+            };
+
+            let mut dispatch = |b| {
+                let response: io::Result<Vec<u8>> = try {
+                    let mut streams = channel.lock().unwrap();
+                    write_message(&mut streams.output, &WorkerMessage::Dispatch(b))?;
+                    read_message(&mut streams.input)?
+                };
+                // The macro can't go on without the response, and the compiler is gone anyway.
+                response.unwrap_or_else(|_| process::exit(1))
+            };
+            MACRO_RUNNING.store(true, Ordering::SeqCst);
+            let result = macros[location.index].run_remote(
+                request.input,
+                &mut dispatch,
+                request.force_show_panics,
+            );
+
+            let mut streams = channel.lock().unwrap();
+            MACRO_RUNNING.store(false, Ordering::SeqCst);
+            flush_printed()?;
+            write_message(&mut streams.output, &WorkerMessage::Output(result))?;
+        }
+    };
+    // The compiler closes stdin once it's done with the macros.
+    process::exit(if result.is_ok() { 0 } else { 1 })
+}
+
+/// The streams of the messages exchanged with the compiler, which the threads checking the file
+/// accesses of macros send messages to as well.
+struct Channel<R, W> {
+    input: R,
+    output: W,
+}
+
+/// Whether a macro is running, whose file accesses the compiler checks. The other accesses, e.g.
+/// loading the libraries of macros, are always allowed.
+static MACRO_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Asks the compiler whether the macro running may access the file at `path`.
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn check_access<R: io::Read, W: Write>(channel: &Mutex<Channel<R, W>>, path: PathBuf) -> bool {
+    if !MACRO_RUNNING.load(Ordering::SeqCst) {
+        return true;
+    }
+    let mut streams = channel.lock().unwrap();
+    // The macro may have returned while the streams were in use.
+    if !MACRO_RUNNING.load(Ordering::SeqCst) {
+        return true;
+    }
+    let allowed: io::Result<bool> = try {
+        write_message(&mut streams.output, &WorkerMessage::Access(path))?;
+        read_message(&mut streams.input)?
+    };
+    allowed.unwrap_or_else(|_| process::exit(1))
+}
+
+/// Returns the streams the messages of the compiler are read from and written to, which are the
+/// stdin and stdout the worker was started with.
+///
+/// Macros may read stdin or write to stdout directly, or start processes which do, so the messages
+/// go through copies of the original streams, and stdin and stdout are then replaced with
+/// `/dev/null` and stderr.
+#[cfg(unix)]
+fn take_protocol_streams() -> io::Result<(io::BufReader<File>, File)> {
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    fn cvt(ret: libc::c_int) -> io::Result<libc::c_int> {
+        if ret == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(ret)
+        }
+    }
+
+    let null = File::open("/dev/null")?;
+    // SAFETY: the copies are closed on `exec`, so that the processes started by macros don't
+    // inherit them, and are only owned by the returned files.
+    unsafe {
+        let input = File::from_raw_fd(cvt(libc::fcntl(0, libc::F_DUPFD_CLOEXEC, 3))?);
+        let output = File::from_raw_fd(cvt(libc::fcntl(1, libc::F_DUPFD_CLOEXEC, 3))?);
+        cvt(libc::dup2(null.as_raw_fd(), 0))?;
+        cvt(libc::dup2(2, 1))?;
+        Ok((io::BufReader::new(input), output))
+    }
+}
+
+/// Returns the streams the messages of the compiler are read from and written to, which are the
+/// stdin and stdout of the worker.
+///
+/// What the macros print goes to stderr instead, but unlike on Unix, a macro which writes to
+/// stdout directly corrupts the messages.
+#[cfg(not(unix))]
+fn take_protocol_streams() -> io::Result<(io::Stdin, io::Stdout)> {
+    io::set_output_capture(Some(Default::default()));
+    Ok((io::stdin(), io::stdout()))
+}
+
+/// Writes what the last macro printed to stderr, if it was captured.
+fn flush_printed() -> io::Result<()> {
+    let Some(printed) = io::set_output_capture(None) else { return Ok(()) };
+    let bytes = std::mem::take(&mut *printed.lock().unwrap());
+    io::set_output_capture(Some(printed));
+    io::stderr().write_all(&bytes)
+}
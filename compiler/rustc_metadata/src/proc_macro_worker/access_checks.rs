@@ -0,0 +1,244 @@
+//! Checking the files the macros running in the worker access, on Linux.
+//!
+//! The worker installs a seccomp filter which makes the system calls that access files by path
+//! wait for a supervisor thread, in the threads of the macros and in the processes they start.
+//! The supervisor reads the path from the memory of the calling thread and asks the compiler
+//! whether the file may be accessed, then lets the system call go on, or makes it fail with
+//! `EACCES`.
+//!
+//! The checks help find the inputs macros forget to declare, but they aren't a security boundary:
+//! a macro could still change the path after it's read, or use the network.
+
+use std::ffi::{CStr, OsStr};
+use std::fs;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+
+// The definitions of `linux/seccomp.h` which the `libc` crate doesn't have.
+#[allow(dead_code)]
+#[repr(C)]
+struct SeccompData {
+    nr: libc::c_int,
+    arch: u32,
+    instruction_pointer: u64,
+    args: [u64; 6],
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct SeccompNotif {
+    id: u64,
+    pid: u32,
+    flags: u32,
+    data: SeccompData,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct SeccompNotifResp {
+    id: u64,
+    val: i64,
+    error: i32,
+    flags: u32,
+}
+
+const SECCOMP_SET_MODE_FILTER: libc::c_ulong = 1;
+const SECCOMP_FILTER_FLAG_NEW_LISTENER: libc::c_ulong = 1 << 3;
+const SECCOMP_RET_USER_NOTIF: u32 = 0x7fc0_0000;
+const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+const SECCOMP_USER_NOTIF_FLAG_CONTINUE: u32 = 1;
+const SECCOMP_IOCTL_NOTIF_RECV: libc::c_ulong = 0xc050_2100;
+const SECCOMP_IOCTL_NOTIF_SEND: libc::c_ulong = 0xc018_2101;
+const SECCOMP_IOCTL_NOTIF_ID_VALID: libc::c_ulong = 0x4008_2102;
+
+#[cfg(target_arch = "x86_64")]
+const AUDIT_ARCH: u32 = 0xc000_003e;
+#[cfg(target_arch = "aarch64")]
+const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+/// The system calls which access files by path, with the index of their path argument, and of
+/// their directory argument if the path may be relative to a directory other than the working
+/// directory.
+const SYSCALLS: &[(libc::c_long, usize, Option<usize>)] = &[
+    (libc::SYS_openat, 1, Some(0)),
+    (libc::SYS_openat2, 1, Some(0)),
+    (libc::SYS_newfstatat, 1, Some(0)),
+    (libc::SYS_statx, 1, Some(0)),
+    (libc::SYS_faccessat, 1, Some(0)),
+    (libc::SYS_faccessat2, 1, Some(0)),
+    (libc::SYS_readlinkat, 1, Some(0)),
+    (libc::SYS_execve, 0, None),
+    (libc::SYS_execveat, 1, Some(0)),
+];
+
+/// The older system calls which only some architectures have.
+#[cfg(target_arch = "x86_64")]
+const LEGACY_SYSCALLS: &[(libc::c_long, usize, Option<usize>)] = &[
+    (libc::SYS_open, 0, None),
+    (libc::SYS_creat, 0, None),
+    (libc::SYS_stat, 0, None),
+    (libc::SYS_lstat, 0, None),
+    (libc::SYS_access, 0, None),
+    (libc::SYS_readlink, 0, None),
+];
+#[cfg(not(target_arch = "x86_64"))]
+const LEGACY_SYSCALLS: &[(libc::c_long, usize, Option<usize>)] = &[];
+
+fn syscalls() -> impl Iterator<Item = &'static (libc::c_long, usize, Option<usize>)> {
+    SYSCALLS.iter().chain(LEGACY_SYSCALLS)
+}
+
+fn cvt<T: Default + PartialOrd>(ret: T) -> io::Result<T> {
+    if ret < T::default() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Whether the kernel can let the system calls it notified the supervisor of go on, which it can
+/// since Linux 5.5.
+fn kernel_supported() -> bool {
+    // SAFETY: `uname` fills the structure in, with a nul-terminated release.
+    let mut uts: libc::utsname = unsafe { mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return false;
+    }
+    let release = unsafe { CStr::from_ptr(uts.release.as_ptr()) }.to_string_lossy();
+    let mut version = release.split(|c: char| !c.is_ascii_digit()).map(|n| n.parse().unwrap_or(0));
+    (version.next().unwrap_or(0), version.next().unwrap_or(0)) >= (5u32, 5u32)
+}
+
+/// Makes the current thread, and the threads and processes it starts from then on, ask `allowed`
+/// whether they may access files, which is called on another thread. Returns an error if the
+/// system doesn't support it.
+pub fn install(allowed: impl Fn(PathBuf) -> bool + Send + 'static) -> io::Result<()> {
+    if !kernel_supported() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "Linux 5.5 or newer is needed"));
+    }
+    // The supervisor must not be restricted by the filter itself, so it's started first.
+    let (sender, receiver) = mpsc::channel::<libc::c_int>();
+    thread::Builder::new().name("access checks".into()).spawn(move || {
+        if let Ok(listener) = receiver.recv() {
+            supervise(listener, allowed);
+        }
+    })?;
+
+    let mut filter = vec![
+        bpf_stmt(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, 4),
+        bpf_jump(libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K, AUDIT_ARCH, 1, 0),
+        bpf_stmt(libc::BPF_RET | libc::BPF_K, SECCOMP_RET_ALLOW),
+        bpf_stmt(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS, 0),
+    ];
+    let count = syscalls().count();
+    for (i, &(nr, ..)) in syscalls().enumerate() {
+        let to_notify = (count - i) as u8;
+        filter.push(bpf_jump(libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K, nr as u32, to_notify, 0));
+    }
+    filter.push(bpf_stmt(libc::BPF_RET | libc::BPF_K, SECCOMP_RET_ALLOW));
+    filter.push(bpf_stmt(libc::BPF_RET | libc::BPF_K, SECCOMP_RET_USER_NOTIF));
+    let program = libc::sock_fprog { len: filter.len() as u16, filter: filter.as_mut_ptr() };
+
+    // SAFETY: the filter only makes the system calls wait for the supervisor.
+    let listener = unsafe {
+        cvt(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+        cvt(libc::syscall(
+            libc::SYS_seccomp,
+            SECCOMP_SET_MODE_FILTER,
+            SECCOMP_FILTER_FLAG_NEW_LISTENER,
+            &program as *const libc::sock_fprog,
+        ))?
+    };
+    // The supervisor is waiting for it.
+    sender.send(listener as libc::c_int).unwrap();
+    Ok(())
+}
+
+fn bpf_stmt(code: u32, k: u32) -> libc::sock_filter {
+    libc::sock_filter { code: code as u16, jt: 0, jf: 0, k }
+}
+
+fn bpf_jump(code: u32, k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+    libc::sock_filter { code: code as u16, jt, jf, k }
+}
+
+/// Answers the notifications of the filter on `listener`, until it fails.
+fn supervise(listener: libc::c_int, allowed: impl Fn(PathBuf) -> bool) {
+    loop {
+        // SAFETY: the notifications and responses have the layout the kernel expects.
+        let mut notif: SeccompNotif = unsafe { mem::zeroed() };
+        if let Err(err) =
+            cvt(unsafe { libc::ioctl(listener, SECCOMP_IOCTL_NOTIF_RECV as _, &mut notif) })
+        {
+            match err.raw_os_error() {
+                // The system call was interrupted, or its thread is gone.
+                Some(libc::EINTR | libc::ENOENT) => continue,
+                _ => return,
+            }
+        }
+        let path = accessed_path(&notif);
+        // The thread may have been interrupted while its memory was read, so that it may now
+        // hold anything else.
+        if unsafe { libc::ioctl(listener, SECCOMP_IOCTL_NOTIF_ID_VALID as _, &notif.id) } != 0 {
+            continue;
+        }
+        let mut response = SeccompNotifResp { id: notif.id, val: 0, error: 0, flags: 0 };
+        if path.map_or(true, &allowed) {
+            response.flags = SECCOMP_USER_NOTIF_FLAG_CONTINUE;
+        } else {
+            response.error = -libc::EACCES;
+        }
+        unsafe { libc::ioctl(listener, SECCOMP_IOCTL_NOTIF_SEND as _, &mut response) };
+    }
+}
+
+/// Returns the absolute path of the file accessed by the system call of `notif`, if it has one.
+fn accessed_path(notif: &SeccompNotif) -> Option<PathBuf> {
+    let &(_, path_arg, dir_arg) =
+        syscalls().find(|&&(nr, ..)| nr == libc::c_long::from(notif.data.nr))?;
+    let path = read_c_str(notif.pid, notif.data.args[path_arg])?;
+    if path.is_empty() {
+        // The system call operates on the directory argument, which is a file the thread opened.
+        return None;
+    }
+    let path = PathBuf::from(OsStr::from_bytes(&path));
+    if path.is_absolute() {
+        return Some(path);
+    }
+    let dir = match dir_arg.map(|arg| notif.data.args[arg] as libc::c_int) {
+        Some(fd) if fd != libc::AT_FDCWD => format!("/proc/{}/fd/{}", notif.pid, fd),
+        _ => format!("/proc/{}/cwd", notif.pid),
+    };
+    Some(fs::read_link(dir).ok()?.join(path))
+}
+
+/// Reads the nul-terminated string at `addr` in the memory of the process of the thread `tid`.
+fn read_c_str(tid: u32, mut addr: u64) -> Option<Vec<u8>> {
+    // Pages are aligned to this, so reading a chunk which doesn't cross it can't fail because the
+    // string ends right before an unmapped page.
+    const CHUNK_ALIGN: u64 = 4096;
+    let mut bytes = Vec::new();
+    while bytes.len() < libc::PATH_MAX as usize {
+        let mut chunk = [0u8; CHUNK_ALIGN as usize];
+        let len = (CHUNK_ALIGN - addr % CHUNK_ALIGN) as usize;
+        let local = libc::iovec { iov_base: chunk.as_mut_ptr().cast(), iov_len: len };
+        let remote = libc::iovec { iov_base: addr as *mut libc::c_void, iov_len: len };
+        // SAFETY: the local buffer is `len` bytes long.
+        let read = unsafe { libc::process_vm_readv(tid as libc::pid_t, &local, 1, &remote, 1, 0) };
+        if read <= 0 {
+            return None;
+        }
+        let chunk = &chunk[..read as usize];
+        if let Some(end) = chunk.iter().position(|&b| b == 0) {
+            bytes.extend_from_slice(&chunk[..end]);
+            return Some(bytes);
+        }
+        bytes.extend_from_slice(chunk);
+        addr += read as u64;
+    }
+    None
+}
@@ -14,6 +14,8 @@
 use rustc_data_structures::unhash::UnhashMap;
 use rustc_expand::base::{SyntaxExtension, SyntaxExtensionKind};
 use rustc_expand::proc_macro::{AttrProcMacro, BangProcMacro, ProcMacroDerive};
+use rustc_expand::proc_macro_cache::PureProcMacro;
+use rustc_expand::proc_macro_sandbox::ProcMacroLocation;
 use rustc_hir as hir;
 use rustc_hir::def::{CtorKind, CtorOf, DefKind, Res};
 use rustc_hir::def_id::{CrateNum, DefId, DefIndex, CRATE_DEF_INDEX, LOCAL_CRATE};
@@ -44,7 +46,7 @@
 use std::io;
 use std::mem;
 use std::num::NonZeroUsize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
 pub(super) use cstore_impl::provide;
@@ -97,6 +99,9 @@ fn deref(&self) -> &[u8] {
     trait_impls: FxHashMap<(u32, DefIndex), Lazy<[(DefIndex, Option<SimplifiedType>)]>>,
     /// Proc macro descriptions for this crate, if it's a proc macro crate.
     raw_proc_macros: Option<&'static [ProcMacro]>,
+    /// The library the proc macros were loaded from, and the symbol of their descriptions in it,
+    /// to load them in the proc macro sandbox.
+    proc_macro_dylib: Option<(PathBuf, String)>,
     /// Source maps for code from the crate.
     source_map_import_info: OnceCell<Vec<ImportedSourceFile>>,
     /// For every definition in this crate, maps its `DefPathHash` to its `DefIndex`.
@@ -793,19 +798,21 @@ impl CrateRoot<'_> {
 }
 
 impl<'a, 'tcx> CrateMetadataRef<'a> {
-    fn raw_proc_macro(self, id: DefIndex) -> &'a ProcMacro {
+    fn raw_proc_macro_index(self, id: DefIndex) -> usize {
         // DefIndex's in root.proc_macro_data have a one-to-one correspondence
         // with items in 'raw_proc_macros'.
-        let pos = self
-            .root
+        self.root
             .proc_macro_data
             .as_ref()
             .unwrap()
             .macros
             .decode(self)
             .position(|i| i == id)
-            .unwrap();
-        &self.raw_proc_macros.unwrap()[pos]
+            .unwrap()
+    }
+
+    fn raw_proc_macro(self, id: DefIndex) -> &'a ProcMacro {
+        &self.raw_proc_macros.unwrap()[self.raw_proc_macro_index(id)]
     }
 
     fn opt_item_name(self, item_index: DefIndex) -> Option<Symbol> {
@@ -874,25 +881,40 @@ fn get_span(self, index: DefIndex, sess: &Session) -> Span {
     }
 
     fn load_proc_macro(self, id: DefIndex, sess: &Session) -> SyntaxExtension {
-        let (name, kind, helper_attrs) = match *self.raw_proc_macro(id) {
+        let index = self.raw_proc_macro_index(id);
+        let location = self.proc_macro_dylib.clone().map(|(dylib, decls_symbol)| {
+            ProcMacroLocation { dylib, decls_symbol, index }
+        });
+        let attrs: Vec<_> = self.get_item_attrs(id, sess).collect();
+        let pure = sess
+            .contains_name(&attrs, sym::proc_macro_pure)
+            .then(|| PureProcMacro { crate_hash: self.root.hash, index });
+        let (name, kind, helper_attrs) = match self.raw_proc_macros.unwrap()[index] {
             ProcMacro::CustomDerive { trait_name, attributes, client } => {
                 let helper_attrs =
                     attributes.iter().cloned().map(Symbol::intern).collect::<Vec<_>>();
                 (
                     trait_name,
-                    SyntaxExtensionKind::Derive(Box::new(ProcMacroDerive { client })),
+                    SyntaxExtensionKind::Derive(Box::new(ProcMacroDerive {
+                        client,
+                        location,
+                        pure,
+                    })),
                     helper_attrs,
                 )
             }
-            ProcMacro::Attr { name, client } => {
-                (name, SyntaxExtensionKind::Attr(Box::new(AttrProcMacro { client })), Vec::new())
-            }
-            ProcMacro::Bang { name, client } => {
-                (name, SyntaxExtensionKind::Bang(Box::new(BangProcMacro { client })), Vec::new())
-            }
+            ProcMacro::Attr { name, client } => (
+                name,
+                SyntaxExtensionKind::Attr(Box::new(AttrProcMacro { client, location, pure })),
+                Vec::new(),
+            ),
+            ProcMacro::Bang { name, client } => (
+                name,
+                SyntaxExtensionKind::Bang(Box::new(BangProcMacro { client, location, pure })),
+                Vec::new(),
+            ),
         };
 
-        let attrs: Vec<_> = self.get_item_attrs(id, sess).collect();
         SyntaxExtension::new(
             sess,
             kind,
@@ -1742,6 +1764,7 @@ impl CrateMetadata {
         blob: MetadataBlob,
         root: CrateRoot<'static>,
         raw_proc_macros: Option<&'static [ProcMacro]>,
+        proc_macro_dylib: Option<(PathBuf, String)>,
         cnum: CrateNum,
         cnum_map: CrateNumMap,
         dep_kind: CrateDepKind,
@@ -1767,6 +1790,7 @@ impl CrateMetadata {
             root,
             trait_impls,
             raw_proc_macros,
+            proc_macro_dylib,
             source_map_import_info: OnceCell::new(),
             def_path_hash_map,
             expn_hash_map: Default::default(),
@@ -291,6 +291,23 @@ fn struct_lint_level_impl<'s, 'd>(
         span: Option<MultiSpan>,
         decorate: Box<dyn for<'b> FnOnce(LintDiagnosticBuilder<'b>) + 'd>,
     ) {
+        // Occurrences of warnings and denied lints that are listed in the
+        // `-Z lint-baseline` file are allowed. Lints in external macros that
+        // are dropped below are skipped here, as they can't be fixed anyway.
+        let level = match (level, &span) {
+            (Level::Warn | Level::Deny, Some(span)) => match span.primary_span() {
+                Some(primary_span)
+                    if (lint.report_in_external_macro
+                        || !span.primary_spans().iter().any(|s| in_external_macro(sess, *s)))
+                        && sess.check_lint_baseline(&lint.name_lower(), primary_span) =>
+                {
+                    Level::Allow
+                }
+                _ => level,
+            },
+            _ => level,
+        };
+
         // Check for future incompatibility lints and issue a stronger warning.
         let future_incompatible = lint.future_incompatible;
 
@@ -9,7 +9,7 @@
 use rustc_hir::lang_items::LangItem;
 use rustc_index::bit_set::BitSet;
 use rustc_index::vec::{Idx, IndexVec};
-use rustc_session::{config::OptLevel, DataTypeKind, FieldInfo, SizeKind, VariantInfo};
+use rustc_session::{config::OptLevel, DataTypeKind, FieldInfo, NicheInfo, SizeKind, VariantInfo};
 use rustc_span::symbol::Symbol;
 use rustc_span::{Span, DUMMY_SP};
 use rustc_target::abi::call::{
@@ -1756,9 +1756,11 @@ fn generator_layout(
     /// layout of each type.
     #[inline(always)]
     fn record_layout_for_printing(&self, layout: TyAndLayout<'tcx>) {
-        // If we are running with `-Zprint-type-sizes`, maybe record layouts
-        // for dumping later.
-        if self.tcx.sess.opts.debugging_opts.print_type_sizes {
+        // If we are running with `-Zprint-type-sizes` or `-Zemit-type-sizes`, maybe record
+        // layouts for dumping later.
+        if self.tcx.sess.opts.debugging_opts.print_type_sizes
+            || self.tcx.sess.opts.debugging_opts.emit_type_sizes.is_some()
+        {
             self.record_layout_for_printing_outlined(layout)
         }
     }
@@ -1771,6 +1773,14 @@ fn record_layout_for_printing_outlined(&self, layout: TyAndLayout<'tcx>) {
             return;
         }
 
+        let niche = layout.largest_niche.map(|niche| NicheInfo {
+            offset: niche.offset.bytes(),
+            size: niche.scalar.value.size(self).bytes(),
+            valid_range_start: niche.scalar.valid_range.start,
+            valid_range_end: niche.scalar.valid_range.end,
+            available: niche.available(self),
+        });
+
         // (delay format until we actually need it)
         let record = |kind, packed, opt_discr_size, variants| {
             let type_desc = format!("{:?}", layout.ty);
@@ -1781,6 +1791,7 @@ fn record_layout_for_printing_outlined(&self, layout: TyAndLayout<'tcx>) {
                 layout.size,
                 packed,
                 opt_discr_size,
+                niche,
                 variants,
             );
         };
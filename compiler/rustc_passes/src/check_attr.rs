@@ -118,8 +118,10 @@ fn check_attributes(
                     self.check_default_method_body_is_const(attr, span, target)
                 }
                 sym::must_not_suspend => self.check_must_not_suspend(&attr, span, target),
+                sym::retries => self.check_retries(&attr, span),
                 sym::must_use => self.check_must_use(hir_id, &attr, span, target),
                 sym::rustc_pass_by_value => self.check_pass_by_value(&attr, span, target),
+                sym::proc_macro_pure => self.check_proc_macro_pure(attr, span, target, attrs),
                 sym::rustc_const_unstable
                 | sym::rustc_const_stable
                 | sym::unstable
@@ -1915,6 +1917,46 @@ fn check_default_method_body_is_const(
         }
     }
 
+    /// `#[retries]` is removed from `#[test]` functions when they are expanded, so any other use
+    /// of it is an error, unless the feature gate already reported it.
+    fn check_retries(&self, attr: &Attribute, span: Span) -> bool {
+        if self.tcx.features().test_retries {
+            self.tcx
+                .sess
+                .struct_span_err(attr.span, "`#[retries]` can only be used on `#[test]` functions")
+                .span_label(span, "not a `#[test]` function")
+                .emit();
+        }
+        false
+    }
+
+    /// Checks that `#[proc_macro_pure]` is only applied to the functions defining proc macros.
+    fn check_proc_macro_pure(
+        &self,
+        attr: &Attribute,
+        span: Span,
+        target: Target,
+        attrs: &[Attribute],
+    ) -> bool {
+        if target == Target::Fn && attrs.iter().any(|attr| self.tcx.sess.is_proc_macro_attr(attr)) {
+            true
+        } else {
+            self.tcx
+                .sess
+                .struct_span_err(
+                    attr.span,
+                    "`#[proc_macro_pure]` should be applied to a function defining a proc macro",
+                )
+                .span_label(span, "not a proc macro")
+                .help(
+                    "proc macros are defined with `#[proc_macro]`, `#[proc_macro_derive]` or \
+                     `#[proc_macro_attribute]`",
+                )
+                .emit();
+            false
+        }
+    }
+
     fn check_stability_promotable(&self, attr: &Attribute, _span: Span, target: Target) -> bool {
         match target {
             Target::Expression => {
@@ -1,7 +1,12 @@
 use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::sync::Lock;
+use rustc_serialize::json::{Json, ToJson};
 use rustc_target::abi::{Align, Size};
 use std::cmp::{self, Ordering};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct VariantInfo {
@@ -26,6 +31,19 @@ pub struct FieldInfo {
     pub align: u64,
 }
 
+/// The largest niche of a type, i.e. the invalid values of one of its scalars that enclosing
+/// enums can use to store their discriminant.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NicheInfo {
+    pub offset: u64,
+    pub size: u64,
+    /// The range of valid values, which wraps around if `valid_range_start > valid_range_end`.
+    pub valid_range_start: u128,
+    pub valid_range_end: u128,
+    /// The number of invalid values.
+    pub available: u128,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum DataTypeKind {
     Struct,
@@ -42,6 +60,7 @@ pub struct TypeSizeInfo {
     pub overall_size: u64,
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
+    pub niche: Option<NicheInfo>,
     pub variants: Vec<VariantInfo>,
 }
 
@@ -59,6 +78,7 @@ pub fn record_type_size<S: ToString>(
         overall_size: Size,
         packed: bool,
         opt_discr_size: Option<Size>,
+        niche: Option<NicheInfo>,
         mut variants: Vec<VariantInfo>,
     ) {
         // Sort variants so the largest ones are shown first. A stable sort is
@@ -72,6 +92,7 @@ pub fn record_type_size<S: ToString>(
             overall_size: overall_size.bytes(),
             packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
+            niche,
             variants,
         };
         self.type_sizes.borrow_mut().insert(info);
@@ -190,4 +211,131 @@ pub fn print_type_sizes(&self) {
             }
         }
     }
+
+    /// Writes the recorded type sizes to `path` as JSON, for tools that compare layouts between
+    /// builds. Types are sorted by their description, and then by their layout, so that the
+    /// output is the same for the same types and diffs well. Unlike the output of
+    /// `print_type_sizes`, the padding is listed separately from the fields.
+    ///
+    /// Values that don't fit into 64 bits, which only occur in 128-bit niches, are written as
+    /// strings.
+    pub fn write_type_sizes_json(&self, path: &Path) -> io::Result<()> {
+        let type_sizes = self.type_sizes.borrow();
+        // Descriptions aren't necessarily unique, so the layout breaks ties.
+        let mut sorted: Vec<_> = type_sizes.iter().collect();
+        sorted
+            .sort_by_cached_key(|info| (info.type_description.clone(), info.to_json().to_string()));
+
+        let types = sorted.iter().map(|info| info.to_json()).collect();
+        let mut output = Json::Object(BTreeMap::from([("types".to_owned(), Json::Array(types))]))
+            .pretty()
+            .to_string();
+        output.push('\n');
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, output)
+    }
+}
+
+impl DataTypeKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            DataTypeKind::Struct => "struct",
+            DataTypeKind::Union => "union",
+            DataTypeKind::Enum => "enum",
+            DataTypeKind::Closure => "closure",
+        }
+    }
+}
+
+fn object<const N: usize>(fields: [(&str, Json); N]) -> Json {
+    Json::Object(fields.into_iter().map(|(name, value)| (name.to_owned(), value)).collect())
+}
+
+fn u128_to_json(value: u128) -> Json {
+    match u64::try_from(value) {
+        Ok(value) => value.to_json(),
+        Err(_) => value.to_string().to_json(),
+    }
+}
+
+impl ToJson for TypeSizeInfo {
+    fn to_json(&self) -> Json {
+        let discr_size = self.opt_discr_size.unwrap_or(0);
+        let max_variant_size = self.variants.iter().map(|v| v.size).fold(discr_size, cmp::max);
+        let variants = self.variants.iter().map(|v| v.to_json_with_discr(discr_size)).collect();
+        object([
+            ("type", self.type_description.to_json()),
+            ("kind", self.kind.as_str().to_json()),
+            ("size", self.overall_size.to_json()),
+            ("align", self.align.to_json()),
+            ("packed", self.packed.to_json()),
+            ("discriminant_size", self.opt_discr_size.to_json()),
+            ("niche", self.niche.as_ref().map(NicheInfo::to_json).to_json()),
+            ("variants", Json::Array(variants)),
+            ("end_padding", self.overall_size.saturating_sub(max_variant_size).to_json()),
+        ])
+    }
+}
+
+impl ToJson for NicheInfo {
+    fn to_json(&self) -> Json {
+        object([
+            ("offset", self.offset.to_json()),
+            ("size", self.size.to_json()),
+            ("valid_range_start", u128_to_json(self.valid_range_start)),
+            ("valid_range_end", u128_to_json(self.valid_range_end)),
+            ("available", u128_to_json(self.available)),
+        ])
+    }
+}
+
+impl VariantInfo {
+    /// Lists the fields by increasing offset, along with the padding before them. The layout
+    /// of the variant starts after the discriminant, if any.
+    fn to_json_with_discr(&self, discr_size: u64) -> Json {
+        let mut fields = self.fields.clone();
+        fields.sort_by_key(|f| (f.offset, f.size));
+
+        let mut padding = vec![];
+        let mut min_offset = discr_size;
+        for field in &fields {
+            if field.offset > min_offset {
+                padding.push(object([
+                    ("offset", min_offset.to_json()),
+                    ("size", (field.offset - min_offset).to_json()),
+                ]));
+            }
+            // Fields of unions overlap.
+            min_offset = cmp::max(min_offset, field.offset + field.size);
+        }
+
+        let fields = fields
+            .iter()
+            .map(|field| {
+                object([
+                    ("name", field.name.to_json()),
+                    ("offset", field.offset.to_json()),
+                    ("size", field.size.to_json()),
+                    ("align", field.align.to_json()),
+                ])
+            })
+            .collect();
+        object([
+            ("name", self.name.to_json()),
+            (
+                "size_kind",
+                match self.kind {
+                    SizeKind::Exact => "exact",
+                    SizeKind::Min => "min",
+                }
+                .to_json(),
+            ),
+            ("size", self.size.to_json()),
+            ("align", self.align.to_json()),
+            ("fields", Json::Array(fields)),
+            ("padding", Json::Array(padding)),
+        ])
+    }
 }
@@ -373,6 +373,11 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF log covering all diagnostics, consumed by code-scanning tools.
+    Sarif,
+    /// Parameters of LSP `textDocument/publishDiagnostics` notifications, one per file,
+    /// consumed by editor integrations.
+    Lsp,
 }
 
 impl Default for ErrorOutputType {
@@ -1505,6 +1510,8 @@ pub fn parse_error_format(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif,
+            Some("lsp") => ErrorOutputType::Lsp,
 
             Some(arg) => early_error(
                 ErrorOutputType::HumanReadable(HumanReadableErrorType::Default(color)),
@@ -1589,6 +1596,18 @@ fn check_debug_option_stability(
                 "`--error-format=human-annotate-rs` is unstable",
             );
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_error(
+                ErrorOutputType::Json { pretty: false, json_rendered },
+                "`--error-format=sarif` is unstable",
+            );
+        }
+        if let ErrorOutputType::Lsp = error_format {
+            early_error(
+                ErrorOutputType::Json { pretty: false, json_rendered },
+                "`--error-format=lsp` is unstable",
+            );
+        }
     }
 }
 
@@ -2244,6 +2263,9 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
             "can't instrument with gcov profiling when compiling incrementally",
         );
     }
+    if debugging_opts.emit_lint_baseline.is_some() && incremental.is_some() {
+        early_error(error_format, "can't emit a lint baseline when compiling incrementally");
+    }
     if debugging_opts.profile {
         match codegen_units {
             Some(1) => {}
@@ -2743,7 +2765,7 @@ pub fn needs_analysis(&self) -> bool {
     };
     use crate::lint;
     use crate::options::WasiExecModel;
-    use crate::utils::{NativeLib, NativeLibKind};
+    use crate::utils::{NativeLib, NativeLibKind, TrackedFile};
     use rustc_feature::UnstableFeatures;
     use rustc_span::edition::Edition;
     use rustc_span::RealFileName;
@@ -2835,6 +2857,7 @@ fn hash(
         RealFileName,
         LocationDetail,
         BranchProtection,
+        TrackedFile,
     );
 
     impl<T1, T2> DepTrackingHash for (T1, T2)
@@ -17,6 +17,7 @@
 pub mod parse;
 
 mod code_stats;
+mod lint_baseline;
 #[macro_use]
 pub mod config;
 pub mod cstore;
@@ -0,0 +1,186 @@
+//! Lint baselines, as read by `-Z lint-baseline` and written by `-Z emit-lint-baseline`.
+//!
+//! A baseline lists the lint occurrences a project has accepted for now, so that
+//! a lint can be made to deny new occurrences without fixing all existing ones
+//! first. Occurrences are identified by the name of the lint, the path of the
+//! file they are in and a fingerprint of the source code they point to, which
+//! stays the same when unrelated code around them changes.
+
+use rustc_data_structures::flock;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::stable_hasher::StableHasher;
+use rustc_data_structures::sync::Lock;
+use rustc_serialize::json::{self, Json, ToJson};
+use rustc_span::source_map::SourceMap;
+use rustc_span::{SourceFile, Span};
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The version of the baseline file format.
+const VERSION: u64 = 1;
+
+/// A lint emitted at a particular place in the source code.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct LintOccurrence {
+    pub lint: String,
+    pub path: String,
+    pub fingerprint: String,
+}
+
+impl LintOccurrence {
+    /// Identifies the occurrence of `lint` at `span`. Returns `None` if the
+    /// span doesn't point into a source file of the current crate.
+    pub fn new(sm: &SourceMap, lint: &str, span: Span) -> Option<LintOccurrence> {
+        let span = span.source_callsite();
+        if span.is_dummy() {
+            return None;
+        }
+        let lines = sm.span_to_lines(span).ok()?;
+        if !lines.file.is_real_file() || lines.file.is_imported() {
+            return None;
+        }
+        let path = file_path(sm, &lines.file);
+
+        // Line numbers change whenever code is added above the lint, so the
+        // fingerprint only covers the text of the span and of the lines it is
+        // on, ignoring changes in indentation and other whitespace.
+        let mut hasher = StableHasher::new();
+        normalize_whitespace(&sm.span_to_snippet(span).ok()?).hash(&mut hasher);
+        for line in &lines.lines {
+            normalize_whitespace(&lines.file.get_line(line.line_index)?).hash(&mut hasher);
+        }
+        let fingerprint: u64 = hasher.finish();
+
+        Some(LintOccurrence {
+            lint: lint.to_owned(),
+            path,
+            fingerprint: format!("{:016x}", fingerprint),
+        })
+    }
+}
+
+/// The path of `file` as written to the baseline, which uses forward slashes
+/// on all platforms so that the same baseline can be used on all of them.
+fn file_path(sm: &SourceMap, file: &SourceFile) -> String {
+    sm.filename_for_diagnostics(&file.name).to_string().replace('\\', "/")
+}
+
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A set of lint occurrences, along with how often each of them occurs.
+#[derive(Default)]
+pub struct LintBaseline {
+    occurrences: Lock<FxHashMap<LintOccurrence, usize>>,
+}
+
+impl LintBaseline {
+    pub fn load(path: &Path) -> Result<LintBaseline, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read lint baseline `{}`: {}", path.display(), e))?;
+        let occurrences = parse(&contents)
+            .map_err(|e| format!("invalid lint baseline `{}`: {}", path.display(), e))?;
+        Ok(LintBaseline { occurrences: Lock::new(occurrences) })
+    }
+
+    /// Checks whether `occurrence` is covered by the baseline. Every occurrence
+    /// in the baseline covers a single occurrence of the lint, so that
+    /// duplicating code that triggers a lint still triggers it.
+    pub fn covers(&self, occurrence: &LintOccurrence) -> bool {
+        match self.occurrences.lock().get_mut(occurrence) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn record(&self, occurrence: LintOccurrence) {
+        *self.occurrences.lock().entry(occurrence).or_insert(0) += 1;
+    }
+
+    /// Writes the recorded occurrences to `path`. Occurrences in files that
+    /// aren't part of the current crate, e.g. ones recorded while compiling
+    /// other crates of the same project, are kept.
+    pub fn write(&self, path: &Path, sm: &SourceMap) -> io::Result<()> {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        // The lock keeps crates that are compiled in parallel from overwriting
+        // each other's occurrences, but isn't needed otherwise.
+        let _lock = match flock::Lock::new(&PathBuf::from(lock_path), true, true, true) {
+            Ok(lock) => Some(lock),
+            Err(e) if flock::Lock::error_unsupported(&e) => None,
+            Err(e) => return Err(e),
+        };
+
+        let mut occurrences = match fs::read_to_string(path) {
+            Ok(contents) => parse(&contents).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("invalid lint baseline: {}", e))
+            })?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => FxHashMap::default(),
+            Err(e) => return Err(e),
+        };
+        let files: FxHashSet<_> = sm
+            .files()
+            .iter()
+            .filter(|file| file.is_real_file() && !file.is_imported())
+            .map(|file| file_path(sm, file))
+            .collect();
+        occurrences.retain(|occurrence, _| !files.contains(&occurrence.path));
+        occurrences.extend(self.occurrences.lock().iter().map(|(o, &count)| (o.clone(), count)));
+
+        let mut occurrences: Vec<_> = occurrences.into_iter().collect();
+        occurrences.sort();
+        let lints = occurrences
+            .into_iter()
+            .map(|(occurrence, count)| {
+                let mut obj = BTreeMap::new();
+                obj.insert("lint".to_owned(), occurrence.lint.to_json());
+                obj.insert("path".to_owned(), occurrence.path.to_json());
+                obj.insert("fingerprint".to_owned(), occurrence.fingerprint.to_json());
+                obj.insert("count".to_owned(), count.to_json());
+                Json::Object(obj)
+            })
+            .collect();
+        let mut obj = BTreeMap::new();
+        obj.insert("version".to_owned(), VERSION.to_json());
+        obj.insert("lints".to_owned(), Json::Array(lints));
+
+        fs::write(path, format!("{}\n", Json::Object(obj).pretty()))
+    }
+}
+
+fn parse(contents: &str) -> Result<FxHashMap<LintOccurrence, usize>, String> {
+    let json = json::from_str(contents).map_err(|e| e.to_string())?;
+    let version = json.find("version").and_then(Json::as_u64);
+    if version != Some(VERSION) {
+        return Err(format!("expected version {}", VERSION));
+    }
+    let lints = json.find("lints").and_then(Json::as_array).ok_or("expected a `lints` array")?;
+
+    let mut occurrences = FxHashMap::default();
+    for lint in lints {
+        let field = |name: &str| -> Result<&Json, String> {
+            lint.find(name).ok_or_else(|| format!("missing `{}` in lint occurrence", name))
+        };
+        let string = |name: &str| -> Result<String, String> {
+            field(name)?
+                .as_string()
+                .map(str::to_owned)
+                .ok_or_else(|| format!("expected `{}` to be a string", name))
+        };
+        let occurrence = LintOccurrence {
+            lint: string("lint")?,
+            path: string("path")?,
+            fingerprint: string("fingerprint")?,
+        };
+        let count = field("count")?.as_u64().ok_or("expected `count` to be a number")?;
+        *occurrences.entry(occurrence).or_insert(0) += count as usize;
+    }
+    Ok(occurrences)
+}
@@ -3,7 +3,7 @@
 use crate::early_error;
 use crate::lint;
 use crate::search_paths::SearchPath;
-use crate::utils::NativeLib;
+use crate::utils::{NativeLib, TrackedFile};
 use rustc_target::spec::{CodeModel, LinkerFlavor, MergeFunctions, PanicStrategy, SanitizerSet};
 use rustc_target::spec::{
     RelocModel, RelroLevel, SplitDebuginfo, StackProtector, TargetTriple, TlsModel,
@@ -19,7 +19,7 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::Hasher;
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str;
 
 macro_rules! insert {
@@ -367,6 +367,7 @@ mod desc {
     pub const parse_opt_string: &str = parse_string;
     pub const parse_string_push: &str = parse_string;
     pub const parse_opt_pathbuf: &str = "a path";
+    pub const parse_opt_tracked_file: &str = parse_opt_pathbuf;
     pub const parse_list: &str = "a space-separated list of strings";
     pub const parse_opt_comma_list: &str = "a comma-separated list of strings";
     pub const parse_number: &str = "a number";
@@ -497,6 +498,16 @@ mod parse {
         }
     }
 
+    crate fn parse_opt_tracked_file(slot: &mut Option<TrackedFile>, v: Option<&str>) -> bool {
+        match v {
+            Some(s) => {
+                *slot = Some(TrackedFile::new(Path::new(s)));
+                true
+            }
+            None => false,
+        }
+    }
+
     crate fn parse_string_push(slot: &mut Vec<String>, v: Option<&str>) -> bool {
         match v {
             Some(s) => {
@@ -1218,8 +1229,14 @@ mod parse {
         computed `block` spans (one span encompassing a block's terminator and \
         all statements). If `-Z instrument-coverage` is also enabled, create \
         an additional `.html` file showing the computed coverage spans."),
+    emit_lint_baseline: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the lints emitted while compiling the crate to this file, for use with \
+        `-Z lint-baseline`"),
     emit_stack_sizes: bool = (false, parse_bool, [UNTRACKED],
         "emit a section containing stack size metadata (default: no)"),
+    emit_type_sizes: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the layout information of `-Z print-type-sizes` as JSON to a file named after \
+        the crate in this directory"),
     fewer_names: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "reduce memory use by retaining fewer names within compilation artifacts (LLVM-IR) \
         (default: no)"),
@@ -1278,6 +1295,9 @@ mod parse {
         "link native libraries in the linker invocation (default: yes)"),
     link_only: bool = (false, parse_bool, [TRACKED],
         "link the `.rlink` file generated by `-Z no-link` (default: no)"),
+    lint_baseline: Option<TrackedFile> = (None, parse_opt_tracked_file, [TRACKED_NO_CRATE_HASH],
+        "allow the occurrences of lints listed in this file, as written by \
+        `-Z emit-lint-baseline`"),
     llvm_plugins: Vec<String> = (Vec::new(), parse_list, [TRACKED],
         "a list LLVM plugins to enable (space separated)"),
     llvm_time_trace: bool = (false, parse_bool, [UNTRACKED],
@@ -1367,6 +1387,9 @@ mod parse {
         "print layout information for each type encountered (default: no)"),
     proc_macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
          "show backtraces for panics during proc-macro execution (default: no)"),
+    proc_macro_sandbox: bool = (false, parse_bool, [TRACKED],
+        "run proc macros in a separate process, without access to the environment of the \
+        compiler (default: no)"),
     profile: bool = (false, parse_bool, [TRACKED],
         "insert profiling code (default: no)"),
     profile_closures: bool = (false, parse_no_flag, [UNTRACKED],
@@ -1473,6 +1496,13 @@ mod parse {
         "choose the TLS model to use (`rustc --print tls-models` for details)"),
     trace_macros: bool = (false, parse_bool, [UNTRACKED],
         "for every macro invocation, print its name and arguments (default: no)"),
+    translate_additional_ftl: Option<TrackedFile> = (None,
+        parse_opt_tracked_file, [TRACKED_NO_CRATE_HASH],
+        "additional Fluent file with diagnostic messages, which take precedence over the ones \
+        of the language selected by `-Z translate-lang`"),
+    translate_lang: Option<String> = (None, parse_opt_string, [TRACKED_NO_CRATE_HASH],
+        "language to emit diagnostics in, falling back to English for untranslated messages \
+        (e.g. `de`)"),
     trap_unreachable: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "generate trap instructions for unreachable intrinsics (default: use target setting, usually yes)"),
     treat_err_as_bug: Option<NonZeroUsize> = (None, parse_treat_err_as_bug, [TRACKED],
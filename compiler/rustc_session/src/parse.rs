@@ -4,7 +4,9 @@
 use crate::config::CheckCfg;
 use crate::lint::{BufferedEarlyLint, BuiltinLintDiagnostics, Lint, LintId};
 use rustc_ast::node_id::NodeId;
+use rustc_data_structures::fingerprint::Fingerprint;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::stable_hasher::StableHasher;
 use rustc_data_structures::sync::{Lock, Lrc};
 use rustc_errors::{emitter::SilentEmitter, ColorConfig, Handler};
 use rustc_errors::{error_code, Applicability, Diagnostic, DiagnosticBuilder, ErrorGuaranteed};
@@ -14,13 +16,78 @@
 use rustc_span::source_map::{FilePathMapping, SourceMap};
 use rustc_span::{MultiSpan, Span, Symbol};
 
-use std::str;
+use std::hash::Hash;
+use std::{env, fmt, fs, str};
 
 /// The set of keys (and, optionally, values) that define the compilation
 /// environment of the crate, used to drive conditional compilation.
 pub type CrateConfig = FxHashSet<(Symbol, Option<Symbol>)>;
 pub type CrateCheckConfig = CheckCfg<Symbol>;
 
+/// A file or environment variable that a proc macro read, and declared with
+/// `proc_macro::tracked_path::path` or `proc_macro::tracked_env::var`, with the fingerprint of
+/// its contents at the time.
+#[derive(Clone, Debug, PartialEq, Eq, Encodable, Decodable)]
+pub enum TrackedInput {
+    /// A file, with the fingerprint of its contents, or `None` if it couldn't be read.
+    File(String, Option<Fingerprint>),
+    /// An environment variable, with the fingerprint of its value, or `None` if it wasn't set.
+    EnvVar(String, Option<Fingerprint>),
+}
+
+impl TrackedInput {
+    pub fn file(path: &str) -> TrackedInput {
+        TrackedInput::File(path.to_owned(), Self::read_file(path))
+    }
+
+    pub fn env_var(var: &str, value: Option<&str>) -> TrackedInput {
+        TrackedInput::EnvVar(var.to_owned(), value.map(Self::fingerprint))
+    }
+
+    /// Whether the input still has the contents it had when it was tracked.
+    pub fn is_unchanged(&self) -> bool {
+        match self {
+            TrackedInput::File(path, fingerprint) => Self::read_file(path) == *fingerprint,
+            TrackedInput::EnvVar(var, fingerprint) => {
+                env::var(var).ok().as_deref().map(Self::fingerprint) == *fingerprint
+            }
+        }
+    }
+
+    fn read_file(path: &str) -> Option<Fingerprint> {
+        fs::read(path).ok().map(|contents| Self::fingerprint(&contents[..]))
+    }
+
+    fn fingerprint<T: Hash + ?Sized>(contents: &T) -> Fingerprint {
+        let mut hasher = StableHasher::new();
+        contents.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl fmt::Display for TrackedInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackedInput::File(path, _) => write!(f, "file `{}`", path),
+            TrackedInput::EnvVar(var, _) => write!(f, "environment variable `{}`", var),
+        }
+    }
+}
+
+/// The expansion of an invocation of a pure proc macro, with the inputs the invocation tracked.
+#[derive(Clone, Debug, Encodable, Decodable)]
+pub struct ProcMacroExpansion {
+    /// The expansion is only reused while these inputs have the same contents.
+    pub inputs: Vec<TrackedInput>,
+    /// The tokens of the expansion, encoded by `rustc_expand`, which maps their spans to the ones
+    /// of the invocations reusing them.
+    pub tokens: Vec<u8>,
+}
+
+/// The expansions of the invocations of pure proc macros, by the fingerprint of the macro and its
+/// input.
+pub type ProcMacroExpansions = FxHashMap<Fingerprint, ProcMacroExpansion>;
+
 /// Collected spans during parsing for places where a certain feature was
 /// used and should be feature gated accordingly in `check_crate`.
 #[derive(Default)]
@@ -150,6 +217,12 @@ pub struct ParseSess {
     /// Spans passed to `proc_macro::quote_span`. Each span has a numerical
     /// identifier represented by its position in the vector.
     pub proc_macro_quoted_spans: Lock<Vec<Span>>,
+    /// The expansions of pure proc macros used by this session, which are only recorded in
+    /// incremental mode.
+    pub proc_macro_expansions: Lock<ProcMacroExpansions>,
+    /// The expansions of pure proc macros recorded by the previous incremental session, which
+    /// haven't been used by this session yet.
+    pub prev_proc_macro_expansions: Lock<ProcMacroExpansions>,
 }
 
 impl ParseSess {
@@ -181,6 +254,8 @@ pub fn with_span_handler(handler: Handler, source_map: Lrc<SourceMap>) -> Self {
             type_ascription_path_suggestions: Default::default(),
             assume_incomplete_release: false,
             proc_macro_quoted_spans: Default::default(),
+            proc_macro_expansions: Default::default(),
+            prev_proc_macro_expansions: Default::default(),
         }
     }
 
@@ -1,7 +1,8 @@
 use crate::cgu_reuse_tracker::CguReuseTracker;
 use crate::code_stats::CodeStats;
-pub use crate::code_stats::{DataTypeKind, FieldInfo, SizeKind, VariantInfo};
+pub use crate::code_stats::{DataTypeKind, FieldInfo, NicheInfo, SizeKind, VariantInfo};
 use crate::config::{self, CrateType, OutputType, SwitchWithOptPath};
+use crate::lint_baseline::{LintBaseline, LintOccurrence};
 use crate::parse::ParseSess;
 use crate::search_paths::{PathKind, SearchPath};
 use crate::{filesearch, lint};
@@ -17,9 +18,15 @@
 };
 use rustc_errors::annotate_snippet_emitter_writer::AnnotateSnippetEmitterWriter;
 use rustc_errors::emitter::{Emitter, EmitterWriter, HumanReadableErrorType};
+use rustc_errors::json::lsp::LspEmitter;
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
-use rustc_errors::{Diagnostic, DiagnosticBuilder, DiagnosticId, ErrorGuaranteed};
+use rustc_errors::sarif::SarifEmitter;
+use rustc_errors::translation::MessageCatalog;
+use rustc_errors::{
+    Diagnostic, DiagnosticArgs, DiagnosticBuilder, DiagnosticId, DiagnosticMessage,
+    ErrorGuaranteed, Translator,
+};
 use rustc_macros::HashStable_Generic;
 pub use rustc_span::def_id::StableCrateId;
 use rustc_span::edition::Edition;
@@ -164,6 +171,12 @@ pub struct Session {
     /// Data about code being compiled, gathered during compilation.
     pub code_stats: CodeStats,
 
+    /// Lint occurrences allowed by `-Z lint-baseline`.
+    lint_baseline: Option<LintBaseline>,
+
+    /// Lint occurrences to write to the file given by `-Z emit-lint-baseline`.
+    emitted_lints: Option<LintBaseline>,
+
     /// Tracks fuel info if `-zfuel=crate=n` is specified.
     optimization_fuel: Lock<OptimizationFuel>,
 
@@ -275,10 +288,49 @@ fn check_miri_unleashed_features(&self) {
     /// Invoked all the way at the end to finish off diagnostics printing.
     pub fn finish_diagnostics(&self, registry: &Registry) {
         self.check_miri_unleashed_features();
+        self.emit_lint_baseline();
         self.diagnostic().print_error_count(registry);
         self.emit_future_breakage();
     }
 
+    /// Records an occurrence of `lint` at `span` for `-Z emit-lint-baseline`,
+    /// and returns whether it is allowed by `-Z lint-baseline`.
+    pub fn check_lint_baseline(&self, lint: &str, span: Span) -> bool {
+        if self.lint_baseline.is_none() && self.emitted_lints.is_none() {
+            return false;
+        }
+        let occurrence = match LintOccurrence::new(self.source_map(), lint, span) {
+            Some(occurrence) => occurrence,
+            None => return false,
+        };
+        let allowed =
+            self.lint_baseline.as_ref().map_or(false, |baseline| baseline.covers(&occurrence));
+        if let Some(emitted_lints) = &self.emitted_lints {
+            emitted_lints.record(occurrence);
+        }
+        allowed
+    }
+
+    fn emit_lint_baseline(&self) {
+        let path = match &self.opts.debugging_opts.emit_lint_baseline {
+            Some(path) => path,
+            None => return,
+        };
+        // Lints that run after the error was found are missing, and writing
+        // the baseline would remove them from it. Errors of denied lints don't
+        // count, as they don't stop the compilation early and are recorded in
+        // the baseline themselves.
+        if self.has_errors() {
+            self.warn("not writing the lint baseline because compilation failed");
+            return;
+        }
+        if let Some(emitted_lints) = &self.emitted_lints {
+            if let Err(e) = emitted_lints.write(path, self.source_map()) {
+                self.err(&format!("failed to write lint baseline `{}`: {}", path.display(), e));
+            }
+        }
+    }
+
     fn emit_future_breakage(&self) {
         if !self.opts.json_future_incompat {
             return;
@@ -409,6 +461,10 @@ pub fn err(&self, msg: &str) {
     pub fn emit_err<'a>(&'a self, err: impl SessionDiagnostic<'a>) -> ErrorGuaranteed {
         err.into_diagnostic(self).emit()
     }
+    /// Formats `message` with `args` in the language requested with `-Z translate-lang`.
+    pub fn translate(&self, message: &DiagnosticMessage, args: &DiagnosticArgs) -> String {
+        self.diagnostic().translate(message, args)
+    }
     #[inline]
     pub fn err_count(&self) -> usize {
         self.diagnostic().err_count()
@@ -1150,6 +1206,14 @@ fn default_emitter(
             )
             .ui_testing(sopts.debugging_opts.ui_testing),
         ),
+        (config::ErrorOutputType::Sarif, None) => {
+            Box::new(SarifEmitter::stderr(Some(registry), source_map))
+        }
+        (config::ErrorOutputType::Sarif, Some(dst)) => {
+            Box::new(SarifEmitter::new(dst, Some(registry), source_map))
+        }
+        (config::ErrorOutputType::Lsp, None) => Box::new(LspEmitter::stderr(source_map)),
+        (config::ErrorOutputType::Lsp, Some(dst)) => Box::new(LspEmitter::new(dst, source_map)),
     }
 }
 
@@ -1214,10 +1278,18 @@ pub fn build_session(
     ));
     let emitter = default_emitter(&sopts, registry, source_map.clone(), write_dest);
 
+    let catalog = MessageCatalog::load(
+        &sysroot,
+        sopts.debugging_opts.translate_lang.as_deref(),
+        sopts.debugging_opts.translate_additional_ftl.as_ref().map(|ftl| ftl.path()),
+    )
+    .unwrap_or_else(|e| early_error(sopts.error_format, &e.to_string()));
+
     let span_diagnostic = rustc_errors::Handler::with_emitter_and_flags(
         emitter,
         sopts.debugging_opts.diagnostic_handler_flags(can_emit_warnings),
-    );
+    )
+    .with_translator(Translator::new(catalog));
 
     let self_profiler = if let SwitchWithOptPath::Enabled(ref d) = sopts.debugging_opts.self_profile
     {
@@ -1242,6 +1314,10 @@ pub fn build_session(
 
     let mut parse_sess = ParseSess::with_span_handler(span_diagnostic, source_map);
     parse_sess.assume_incomplete_release = sopts.debugging_opts.assume_incomplete_release;
+    if let Some(ftl) = &sopts.debugging_opts.translate_additional_ftl {
+        let path = Symbol::intern(&ftl.path().to_string_lossy());
+        parse_sess.file_depinfo.borrow_mut().insert(path);
+    }
 
     let host_triple = config::host_triple();
     let target_triple = sopts.target_triple.triple();
@@ -1259,6 +1335,17 @@ pub fn build_session(
     let local_crate_source_file =
         local_crate_source_file.map(|path| file_path_mapping.map_prefix(path).0);
 
+    let lint_baseline = sopts.debugging_opts.lint_baseline.as_ref().map(|baseline| {
+        let path = baseline.path();
+        // A baseline that's also written by this session would always be newer than the output.
+        if sopts.debugging_opts.emit_lint_baseline.as_deref() != Some(path) {
+            parse_sess.file_depinfo.borrow_mut().insert(Symbol::intern(&path.to_string_lossy()));
+        }
+        LintBaseline::load(path).unwrap_or_else(|e| early_error(sopts.error_format, &e))
+    });
+    let emitted_lints =
+        sopts.debugging_opts.emit_lint_baseline.as_ref().map(|_| LintBaseline::default());
+
     let optimization_fuel = Lock::new(OptimizationFuel {
         remaining: sopts.debugging_opts.fuel.as_ref().map_or(0, |i| i.1),
         out_of_fuel: false,
@@ -1309,6 +1396,8 @@ pub fn build_session(
             normalize_projection_ty: AtomicUsize::new(0),
         },
         code_stats: Default::default(),
+        lint_baseline,
+        emitted_lints,
         optimization_fuel,
         print_fuel,
         jobserver: jobserver::client(),
@@ -1448,6 +1537,8 @@ pub fn early_error_no_abort(output: config::ErrorOutputType, msg: &str) {
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, None, false))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
+        config::ErrorOutputType::Lsp => Box::new(LspEmitter::basic()),
     };
     let handler = rustc_errors::Handler::with_emitter(true, None, emitter);
     handler.struct_fatal(msg).emit();
@@ -1467,6 +1558,8 @@ pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
         config::ErrorOutputType::Json { pretty, json_rendered } => {
             Box::new(JsonEmitter::basic(pretty, json_rendered, None, false))
         }
+        config::ErrorOutputType::Sarif => Box::new(SarifEmitter::basic()),
+        config::ErrorOutputType::Lsp => Box::new(LspEmitter::basic()),
     };
     let handler = rustc_errors::Handler::with_emitter(true, None, emitter);
     handler.struct_warn(msg).emit();
@@ -4,6 +4,8 @@
 use rustc_ast::tokenstream::CanSynthesizeMissingTokens;
 use rustc_ast::tokenstream::{DelimSpan, TokenStream, TokenTree};
 use rustc_data_structures::profiling::VerboseTimingGuard;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 
 pub type NtToTokenstream = fn(&Nonterminal, &ParseSess, CanSynthesizeMissingTokens) -> TokenStream;
@@ -77,6 +79,30 @@ pub fn original(&self) -> &PathBuf {
     }
 }
 
+/// A file read by the compiler whose contents are part of the dep-tracking hash, so that changing
+/// them invalidates the incremental cache, like changing the path does.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TrackedFile {
+    path: PathBuf,
+    // Optional since the file may not exist, which is reported when it's used
+    contents_hash: Option<u64>,
+}
+
+impl TrackedFile {
+    pub fn new(path: &Path) -> Self {
+        let contents_hash = std::fs::read(path).ok().map(|contents| {
+            let mut hasher = DefaultHasher::new();
+            contents.hash(&mut hasher);
+            hasher.finish()
+        });
+        Self { path: path.to_owned(), contents_hash }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
 // FIXME: Find a better spot for this - it needs to be accessible from `rustc_ast_lowering`,
 // and needs to access `ParseSess
 pub struct FlattenNonterminals<'a> {
@@ -1060,6 +1060,7 @@
         proc_macro_mod,
         proc_macro_non_items,
         proc_macro_path_invoc,
+        proc_macro_pure,
         profiler_builtins,
         profiler_runtime,
         ptr_guaranteed_eq,
@@ -1124,6 +1125,7 @@
         repr_transparent,
         residual,
         result,
+        retries,
         rhs,
         rintf32,
         rintf64,
@@ -1380,6 +1382,7 @@
         test_accepted_feature,
         test_case,
         test_removed_feature,
+        test_retries,
         test_runner,
         then_with,
         thread,
@@ -4,34 +4,35 @@
 
 #[derive(SessionDiagnostic)]
 #[error = "E0062"]
+#[slug = "typeck-field-multiply-specified-in-initializer"]
 pub struct FieldMultiplySpecifiedInInitializer {
-    #[message = "field `{ident}` specified more than once"]
-    #[label = "used more than once"]
+    #[message]
+    #[label]
     pub span: Span,
-    #[label = "first use of `{ident}`"]
+    #[label = "previous-use-label"]
     pub prev_span: Span,
     pub ident: Ident,
 }
 
 #[derive(SessionDiagnostic)]
 #[error = "E0092"]
+#[slug = "typeck-unrecognized-atomic-operation"]
 pub struct UnrecognizedAtomicOperation<'a> {
-    #[message = "unrecognized atomic operation function: `{op}`"]
-    #[label = "unrecognized atomic operation"]
+    #[message]
+    #[label]
     pub span: Span,
     pub op: &'a str,
 }
 
 #[derive(SessionDiagnostic)]
 #[error = "E0094"]
+#[slug = "typeck-wrong-number-of-generic-arguments-to-intrinsic"]
 pub struct WrongNumberOfGenericArgumentsToIntrinsic<'a> {
-    #[message = "intrinsic has wrong number of {descr} \
-                         parameters: found {found}, expected {expected}"]
-    #[label = "expected {expected} {descr} parameter{expected_pluralize}"]
+    #[message]
+    #[label]
     pub span: Span,
     pub found: usize,
     pub expected: usize,
-    pub expected_pluralize: &'a str,
     pub descr: &'a str,
 }
 
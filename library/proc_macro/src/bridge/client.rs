@@ -482,4 +482,40 @@ pub const fn bang(
     ) -> Self {
         ProcMacro::Bang { name, client: Client::expand1(expand) }
     }
+
+    /// Runs the macro for a server in another process, which sent `input` to it,
+    /// with `dispatch` forwarding its requests to the server and returning the
+    /// responses. Returns the output to send back to the server.
+    ///
+    /// This is the other end of `server::Remote`.
+    pub fn run_remote(
+        &self,
+        input: Vec<u8>,
+        dispatch: &mut dyn FnMut(Vec<u8>) -> Vec<u8>,
+        force_show_panics: bool,
+    ) -> Vec<u8> {
+        match *self {
+            ProcMacro::CustomDerive { client, .. } | ProcMacro::Bang { client, .. } => {
+                client.run_remote(input, dispatch, force_show_panics)
+            }
+            ProcMacro::Attr { client, .. } => client.run_remote(input, dispatch, force_show_panics),
+        }
+    }
+}
+
+impl<F: Copy> Client<F> {
+    fn run_remote(
+        self,
+        input: Vec<u8>,
+        dispatch: &mut dyn FnMut(Vec<u8>) -> Vec<u8>,
+        force_show_panics: bool,
+    ) -> Vec<u8> {
+        let mut dispatch = |b: Buffer<u8>| Buffer::from(dispatch(b.to_vec()));
+        let bridge = Bridge {
+            cached_buffer: Buffer::from(input),
+            dispatch: (&mut dispatch).into(),
+            force_show_panics,
+        };
+        (self.run)(bridge, self.f).to_vec()
+    }
 }
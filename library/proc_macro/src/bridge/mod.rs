@@ -8,7 +8,7 @@
 
 #![deny(unsafe_code)]
 
-use crate::{Delimiter, Level, LineColumn, Spacing};
+use crate::{Applicability, Delimiter, Level, LineColumn, LiteralKind, Spacing};
 use std::fmt;
 use std::hash::Hash;
 use std::marker;
@@ -56,6 +56,7 @@ macro_rules! with_api {
                 fn drop($self: $S::FreeFunctions);
                 fn track_env_var(var: &str, value: Option<&str>);
                 fn track_path(path: &str);
+                fn env_var(var: &str) -> Option<String>;
             },
             TokenStream {
                 fn drop($self: $S::TokenStream);
@@ -112,8 +113,10 @@ fn next(
                 fn from_str(s: &str) -> Result<$S::Literal, ()>;
                 fn to_string($self: &$S::Literal) -> String;
                 fn debug_kind($self: &$S::Literal) -> String;
+                fn kind($self: &$S::Literal) -> LiteralKind;
                 fn symbol($self: &$S::Literal) -> String;
                 fn suffix($self: &$S::Literal) -> Option<String>;
+                fn value($self: &$S::Literal) -> Result<Vec<u8>, ()>;
                 fn integer(n: &str) -> $S::Literal;
                 fn typed_integer(n: &str, kind: &str) -> $S::Literal;
                 fn float(n: &str) -> $S::Literal;
@@ -151,6 +154,17 @@ fn sub(
                     msg: &str,
                     span: $S::MultiSpan,
                 );
+                fn suggestion(
+                    $self: &mut $S::Diagnostic,
+                    msg: &str,
+                    applicability: Applicability,
+                );
+                fn suggestion_part(
+                    $self: &mut $S::Diagnostic,
+                    span: $S::Span,
+                    replacement: &str,
+                );
+                fn set_lint($self: &mut $S::Diagnostic, lint: &str);
                 fn emit($self: $S::Diagnostic);
             },
             Span {
@@ -359,14 +373,25 @@ fn unmark(self) -> Self::Unmarked {
     &'_ [u8],
     &'_ str,
     String,
+    Vec<u8>,
     usize,
+    Applicability,
     Delimiter,
     Level,
     LineColumn,
+    LiteralKind,
     Spacing,
     Bound<usize>,
 }
 
+rpc_encode_decode!(
+    enum Applicability {
+        MachineApplicable,
+        MaybeIncorrect,
+        HasPlaceholders,
+        Unspecified,
+    }
+);
 rpc_encode_decode!(
     enum Delimiter {
         Parenthesis,
@@ -384,6 +409,19 @@ enum Level {
     }
 );
 rpc_encode_decode!(struct LineColumn { line, column });
+rpc_encode_decode!(
+    enum LiteralKind {
+        Byte,
+        Char,
+        Integer,
+        Float,
+        Str,
+        StrRaw(n),
+        ByteStr,
+        ByteStrRaw(n),
+        Err,
+    }
+);
 rpc_encode_decode!(
     enum Spacing {
         Alone,
@@ -126,6 +126,7 @@ fn decode(r: &mut Reader<'_>, _: &mut S) -> Self {
     }
 }
 
+rpc_encode_decode!(le u16);
 rpc_encode_decode!(le u32);
 rpc_encode_decode!(le usize);
 
@@ -246,6 +247,18 @@ fn decode(r: &mut Reader<'_>, s: &mut S) -> Self {
     }
 }
 
+impl<S> Encode<S> for Vec<u8> {
+    fn encode(self, w: &mut Writer, s: &mut S) {
+        self[..].encode(w, s);
+    }
+}
+
+impl<S> DecodeMut<'_, '_, S> for Vec<u8> {
+    fn decode(r: &mut Reader<'_>, s: &mut S) -> Self {
+        <&[u8]>::decode(r, s).to_vec()
+    }
+}
+
 /// Simplified version of panic payloads, ignoring
 /// types other than `&'static str` and `String`.
 pub enum PanicMessage {
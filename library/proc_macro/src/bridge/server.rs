@@ -268,6 +268,30 @@ enum State<T> {
     }
 }
 
+/// Runs the client in another process, e.g. to sandbox it.
+///
+/// The function is given the input of the client and a function dispatching
+/// its requests, and returns its output. The other process must run the same
+/// proc macro, loaded from the same library, with `ProcMacro::run_remote`.
+pub struct Remote<F>(pub F);
+
+impl<F> ExecutionStrategy for Remote<F>
+where
+    F: Fn(Vec<u8>, bool, &mut dyn FnMut(Vec<u8>) -> Vec<u8>) -> Vec<u8>,
+{
+    fn run_bridge_and_client<D: Copy + Send + 'static>(
+        &self,
+        dispatcher: &mut impl DispatcherTrait,
+        input: Buffer<u8>,
+        _run_client: extern "C" fn(Bridge<'_>, D) -> Buffer<u8>,
+        _client_data: D,
+        force_show_panics: bool,
+    ) -> Buffer<u8> {
+        let mut dispatch = |b: Vec<u8>| dispatcher.dispatch(Buffer::from(b)).to_vec();
+        Buffer::from((self.0)(input.to_vec(), force_show_panics, &mut dispatch))
+    }
+}
+
 fn run_server<
     S: Server,
     I: Encode<HandleStore<MarkedTypes<S>>>,
@@ -15,6 +15,24 @@ pub enum Level {
     Help,
 }
 
+/// How confident a diagnostic is that its suggestion is what the user wants, which tells tools
+/// like `rustfix` whether they may apply it automatically.
+#[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended, and can be applied automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain, so it should only be
+    /// applied after the user reviewed it.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `(...)` or `{ /* fields */ }`, which the user
+    /// has to fill in.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    Unspecified,
+}
+
 /// Trait implemented by types that can be converted into a set of `Span`s.
 #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
 pub trait MultiSpan {
@@ -52,6 +70,16 @@ pub struct Diagnostic {
     message: String,
     spans: Vec<Span>,
     children: Vec<Diagnostic>,
+    suggestions: Vec<Suggestion>,
+    lint: Option<String>,
+}
+
+/// A suggestion to replace the code at some spans.
+#[derive(Clone, Debug)]
+struct Suggestion {
+    message: String,
+    parts: Vec<(Span, String)>,
+    applicability: Applicability,
 }
 
 macro_rules! diagnostic_child_methods {
@@ -97,7 +125,14 @@ impl Diagnostic {
     /// Creates a new diagnostic with the given `level` and `message`.
     #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
     pub fn new<T: Into<String>>(level: Level, message: T) -> Diagnostic {
-        Diagnostic { level, message: message.into(), spans: vec![], children: vec![] }
+        Diagnostic {
+            level,
+            message: message.into(),
+            spans: vec![],
+            children: vec![],
+            suggestions: vec![],
+            lint: None,
+        }
     }
 
     /// Creates a new diagnostic with the given `level` and `message` pointing to
@@ -108,7 +143,14 @@ pub fn spanned<S, T>(spans: S, level: Level, message: T) -> Diagnostic
         S: MultiSpan,
         T: Into<String>,
     {
-        Diagnostic { level, message: message.into(), spans: spans.into_spans(), children: vec![] }
+        Diagnostic {
+            level,
+            message: message.into(),
+            spans: spans.into_spans(),
+            children: vec![],
+            suggestions: vec![],
+            lint: None,
+        }
     }
 
     diagnostic_child_methods!(span_error, error, Level::Error);
@@ -116,6 +158,60 @@ pub fn spanned<S, T>(spans: S, level: Level, message: T) -> Diagnostic
     diagnostic_child_methods!(span_note, note, Level::Note);
     diagnostic_child_methods!(span_help, help, Level::Help);
 
+    /// Adds a suggestion to replace the code at `span` with `replacement`, described by
+    /// `message`.
+    ///
+    /// Suggestions are shown with the diagnostic, and the ones that are
+    /// [`MachineApplicable`](Applicability::MachineApplicable) can be applied by tools like
+    /// `rustfix`, which read them from the JSON output of the compiler.
+    #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+    pub fn span_suggestion<T, U>(
+        self,
+        span: Span,
+        message: T,
+        replacement: U,
+        applicability: Applicability,
+    ) -> Diagnostic
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        self.multipart_suggestion(message, vec![(span, replacement.into())], applicability)
+    }
+
+    /// Adds a suggestion to replace the code at each span of `parts` with the string that goes
+    /// with it, described by `message`. All the parts are applied together.
+    #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+    pub fn multipart_suggestion<T: Into<String>>(
+        mut self,
+        message: T,
+        parts: Vec<(Span, String)>,
+        applicability: Applicability,
+    ) -> Diagnostic {
+        self.suggestions.push(Suggestion { message: message.into(), parts, applicability });
+        self
+    }
+
+    /// Returns the name of the lint `self` is emitted under, if any.
+    #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+    pub fn lint(&self) -> Option<&str> {
+        self.lint.as_deref()
+    }
+
+    /// Emits `self` under the lint named `lint`, like `unused_variables` or `dead_code`.
+    ///
+    /// The lint must be known to the compiler, and can then be allowed, warned about or denied
+    /// like any other lint, with attributes around the macro call or with command line flags. The
+    /// level of `self` is replaced by the level of the lint at the macro call. Since the level of
+    /// the lint decides whether the diagnostic is an error, its children can't be errors or
+    /// warnings themselves: the help messages stay help messages, and all the other children are
+    /// emitted as notes. If `lint` isn't the name of a lint, for instance because it names a lint
+    /// group like `unused`, `self` is emitted with its own level and a note saying so.
+    #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
+    pub fn set_lint<T: Into<String>>(&mut self, lint: T) {
+        self.lint = Some(lint.into());
+    }
+
     /// Returns the diagnostic `level` for `self`.
     #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
     pub fn level(&self) -> Level {
@@ -177,6 +273,15 @@ fn to_internal(spans: Vec<Span>) -> crate::bridge::client::MultiSpan {
         for c in self.children {
             diag.sub(c.level, &c.message[..], to_internal(c.spans));
         }
+        for s in self.suggestions {
+            diag.suggestion(&s.message[..], s.applicability);
+            for (span, replacement) in s.parts {
+                diag.suggestion_part(span.0, &replacement[..]);
+            }
+        }
+        if let Some(lint) = self.lint {
+            diag.set_lint(&lint[..]);
+        }
         diag.emit();
     }
 }
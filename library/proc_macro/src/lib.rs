@@ -40,13 +40,13 @@
 mod diagnostic;
 
 #[unstable(feature = "proc_macro_diagnostic", issue = "54140")]
-pub use diagnostic::{Diagnostic, Level, MultiSpan};
+pub use diagnostic::{Applicability, Diagnostic, Level, MultiSpan};
 
 use std::cmp::Ordering;
 use std::ops::RangeBounds;
 use std::path::PathBuf;
 use std::str::FromStr;
-use std::{error, fmt, iter, mem};
+use std::{error, fmt, iter, mem, str};
 
 /// Determines whether proc_macro has been made accessible to the currently
 /// running program.
@@ -1018,6 +1018,32 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 #[stable(feature = "proc_macro_lib2", since = "1.29.0")]
 pub struct Literal(bridge::client::Literal);
 
+/// The kind of a [`Literal`], as returned by [`Literal::kind`].
+#[unstable(feature = "proc_macro_literal_value", issue = "none")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LiteralKind {
+    /// A byte literal, like `b'a'`.
+    Byte,
+    /// A character literal, like `'a'`.
+    Char,
+    /// An integer literal, like `1` or `0xffu8`.
+    Integer,
+    /// A floating point literal, like `2.3` or `1e10f32`, or an integer literal with a floating
+    /// point suffix, like `1f32`.
+    Float,
+    /// A string literal, like `"hello"`.
+    Str,
+    /// A raw string literal, like `r#"hello"#`, with the number of `#`s around it.
+    StrRaw(u16),
+    /// A byte string literal, like `b"hello"`.
+    ByteStr,
+    /// A raw byte string literal, like `br#"hello"#`, with the number of `#`s around it.
+    ByteStrRaw(u16),
+    /// A literal that could not be lexed, for which the compiler has reported an error.
+    Err,
+}
+
 macro_rules! suffixed_int_literals {
     ($($name:ident => $kind:ident,)*) => ($(
         /// Creates a new suffixed integer literal with the specified value.
@@ -1226,6 +1252,103 @@ pub fn set_span(&mut self, span: Span) {
     pub fn subspan<R: RangeBounds<usize>>(&self, range: R) -> Option<Span> {
         self.0.subspan(range.start_bound().cloned(), range.end_bound().cloned()).map(Span)
     }
+
+    /// Returns the kind of this literal.
+    #[unstable(feature = "proc_macro_literal_value", issue = "none")]
+    pub fn kind(&self) -> LiteralKind {
+        self.0.kind()
+    }
+
+    /// Returns the suffix of this literal, like `u8` in `1u8`, if it has one.
+    #[unstable(feature = "proc_macro_literal_value", issue = "none")]
+    pub fn suffix(&self) -> Option<String> {
+        self.0.suffix()
+    }
+
+    /// Returns the value of a string or raw string literal, with its escapes processed.
+    ///
+    /// Returns `None` if this is another kind of literal.
+    #[unstable(feature = "proc_macro_literal_value", issue = "none")]
+    pub fn str_value(&self) -> Option<String> {
+        match self.kind() {
+            LiteralKind::Str | LiteralKind::StrRaw(_) => String::from_utf8(self.value()?).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a byte string or raw byte string literal, with its escapes
+    /// processed.
+    ///
+    /// Returns `None` if this is another kind of literal.
+    #[unstable(feature = "proc_macro_literal_value", issue = "none")]
+    pub fn byte_str_value(&self) -> Option<Vec<u8>> {
+        match self.kind() {
+            LiteralKind::ByteStr | LiteralKind::ByteStrRaw(_) => self.value(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a character literal, with its escapes processed.
+    ///
+    /// Returns `None` if this is another kind of literal.
+    #[unstable(feature = "proc_macro_literal_value", issue = "none")]
+    pub fn char_value(&self) -> Option<char> {
+        match self.kind() {
+            LiteralKind::Char => String::from_utf8(self.value()?).ok()?.chars().next(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a byte literal, with its escapes processed.
+    ///
+    /// Returns `None` if this is another kind of literal.
+    #[unstable(feature = "proc_macro_literal_value", issue = "none")]
+    pub fn byte_value(&self) -> Option<u8> {
+        match self.kind() {
+            LiteralKind::Byte => self.value()?.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an integer literal in any base, like `0x_ff` or `-1`, as a `T`.
+    /// The suffix of the literal is ignored.
+    ///
+    /// Returns `None` if this is another kind of literal, or if its value doesn't fit in `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #![feature(proc_macro_literal_value)]
+    /// # extern crate proc_macro;
+    /// # use proc_macro::Literal;
+    /// fn array_len(lit: &Literal) -> Option<usize> {
+    ///     lit.integer_value::<usize>()
+    /// }
+    /// ```
+    #[unstable(feature = "proc_macro_literal_value", issue = "none")]
+    pub fn integer_value<T: FromStr>(&self) -> Option<T> {
+        match self.kind() {
+            LiteralKind::Integer => str::from_utf8(&self.value()?).ok()?.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a floating point literal. The suffix of the literal is ignored.
+    ///
+    /// Returns `None` if this is another kind of literal.
+    #[unstable(feature = "proc_macro_literal_value", issue = "none")]
+    pub fn float_value(&self) -> Option<f64> {
+        match self.kind() {
+            LiteralKind::Float => str::from_utf8(&self.value()?).ok()?.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The value of the literal as decoded by the compiler, as UTF-8 for the literals that aren't
+    /// byte literals, or `None` if it isn't valid.
+    fn value(&self) -> Option<Vec<u8>> {
+        self.0.value().ok()
+    }
 }
 
 /// Parse a single literal from its stringified representation.
@@ -1286,10 +1409,18 @@ pub mod tracked_env {
     /// compilation, and will be able to rerun the build when the value of that variable changes.
     /// Besides the dependency tracking this function should be equivalent to `env::var` from the
     /// standard library, except that the argument must be UTF-8.
+    ///
+    /// In incremental mode, the value of the variable is also recorded with the macro invocation,
+    /// so that the cached expansion of a `#[proc_macro_pure]` macro is only reused while the
+    /// variable keeps its value.
     #[unstable(feature = "proc_macro_tracked_env", issue = "74690")]
     pub fn var<K: AsRef<OsStr> + AsRef<str>>(key: K) -> Result<String, VarError> {
         let key: &str = key.as_ref();
-        let value = env::var(key);
+        let mut value = env::var(key);
+        if let Err(VarError::NotPresent) = value {
+            // The macro may run in a sandbox which doesn't have the environment of the compiler.
+            value = crate::bridge::client::FreeFunctions::env_var(key).ok_or(VarError::NotPresent);
+        }
         crate::bridge::client::FreeFunctions::track_env_var(key, value.as_deref().ok());
         value
     }
@@ -1301,7 +1432,9 @@ pub mod tracked_path {
 
     /// Track a file explicitly.
     ///
-    /// Commonly used for tracking asset preprocessing.
+    /// Commonly used for tracking asset preprocessing. In incremental mode, the contents of the
+    /// file are also recorded with the macro invocation, so that the cached expansion of a
+    /// `#[proc_macro_pure]` macro is only reused while the file keeps its contents.
     #[unstable(feature = "track_path", issue = "73921")]
     pub fn path<P: AsRef<str>>(path: P) {
         let path: &str = path.as_ref();
@@ -0,0 +1,512 @@
+//! Module `baseline` contains everything related to saving the results of benchmarks and
+//! comparing later runs against them, which backs the `--bench-save` and `--bench-baseline`
+//! options.
+//!
+//! Baselines are saved as `<name>.json` in the directory given by the
+//! `RUST_TEST_BENCH_BASELINE_DIR` environment variable, or in `bench-baselines` in the target
+//! directory otherwise. That's the directory given by `CARGO_TARGET_DIR` if it's set, and else the
+//! `target` directory the benchmark executable was built in, or the directory of the executable if
+//! it isn't in one. The current directory isn't used as a fallback, since cargo runs the
+//! benchmarks of each package of a workspace in a different one.
+//! Saving a baseline keeps the results of benchmarks which weren't run this time, so that
+//! several benchmark binaries can share a baseline.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsString;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::bench::{BenchSamples, SAMPLE_COUNT};
+use super::formatters::EscapedString;
+use super::stats::{self, Summary};
+
+/// Environment variable overriding the directory baselines are saved in.
+pub const BASELINE_DIR_ENV: &str = "RUST_TEST_BENCH_BASELINE_DIR";
+
+/// Changes with a p-value below this are considered significant.
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// The saved result of a single benchmark.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    samples: usize,
+    summary: Summary,
+    mb_s: usize,
+}
+
+/// Results of benchmarks saved by an earlier run.
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    name: String,
+    path: PathBuf,
+    benchmarks: BTreeMap<String, Entry>,
+}
+
+/// Change of a benchmark compared to its result in the baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchChange {
+    /// Mean ns/iter of the benchmark in the baseline.
+    pub baseline_mean: f64,
+    /// Change of the mean ns/iter, in percent of the baseline.
+    pub change_pct: f64,
+    /// p-value of Welch's t-test between the baseline and the new samples.
+    pub p_value: f64,
+}
+
+impl BenchChange {
+    pub fn is_significant(&self) -> bool {
+        self.p_value < SIGNIFICANCE_LEVEL
+    }
+}
+
+/// Formats the change of a benchmark, e.g. `[+3.12%, p = 0.004: regressed]`.
+pub fn fmt_bench_change(change: Option<&BenchChange>) -> String {
+    let change = match change {
+        Some(change) => change,
+        None => return "[not in baseline]".to_owned(),
+    };
+    let verdict = if !change.is_significant() {
+        "no change"
+    } else if change.change_pct > 0.0 {
+        "regressed"
+    } else {
+        "improved"
+    };
+    format!("[{:+.2}%, p = {:.3}: {}]", change.change_pct, change.p_value, verdict)
+}
+
+fn baseline_path(name: &str) -> io::Result<PathBuf> {
+    let dir = baseline_dir(env::var_os(BASELINE_DIR_ENV), env::var_os("CARGO_TARGET_DIR"), || {
+        env::current_exe()
+    })?;
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Finds the directory baselines are saved in, given the values of `RUST_TEST_BENCH_BASELINE_DIR`
+/// and `CARGO_TARGET_DIR`.
+fn baseline_dir(
+    dir_override: Option<OsString>,
+    target_dir: Option<OsString>,
+    current_exe: impl FnOnce() -> io::Result<PathBuf>,
+) -> io::Result<PathBuf> {
+    if let Some(dir) = dir_override {
+        return Ok(PathBuf::from(dir));
+    }
+    let target_dir = match target_dir {
+        Some(target_dir) => PathBuf::from(target_dir),
+        // Cargo doesn't set `CARGO_TARGET_DIR` for the executables it runs, which it builds in
+        // e.g. `target/release/deps`.
+        None => {
+            let exe = current_exe().map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "no directory for benchmark baselines, set {}: {}",
+                        BASELINE_DIR_ENV, e
+                    ),
+                )
+            })?;
+            let exe_dir = exe.parent().unwrap_or(Path::new("."));
+            let mut ancestors = exe_dir.ancestors();
+            let target_dir = ancestors.find(|dir| dir.file_name() == Some("target".as_ref()));
+            target_dir.unwrap_or(exe_dir).to_owned()
+        }
+    };
+    Ok(target_dir.join("bench-baselines"))
+}
+
+impl Baseline {
+    /// Loads the baseline saved under `name`.
+    pub fn load(name: &str) -> io::Result<Baseline> {
+        Baseline::load_at(name, baseline_path(name)?)
+    }
+
+    fn load_at(name: &str, path: PathBuf) -> io::Result<Baseline> {
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to read benchmark baseline `{}`: {}", path.display(), e),
+            )
+        })?;
+        let benchmarks = Baseline::parse(&contents).map_err(|msg| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid benchmark baseline `{}`: {}", path.display(), msg),
+            )
+        })?;
+        Ok(Baseline { name: name.to_owned(), path, benchmarks })
+    }
+
+    /// Opens the baseline to save the results of this run under `name`, which keeps the results
+    /// of other benchmarks that are already saved there. This is done before running the
+    /// benchmarks, so that they don't run for nothing if the baseline can't be saved.
+    pub fn open(name: &str) -> io::Result<Baseline> {
+        Baseline::open_at(name, baseline_path(name)?)
+    }
+
+    fn open_at(name: &str, path: PathBuf) -> io::Result<Baseline> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("failed to save benchmark baseline `{}`: {}", path.display(), e),
+                )
+            })?;
+        }
+        match Baseline::load_at(name, path.clone()) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Ok(Baseline { name: name.to_owned(), path, benchmarks: BTreeMap::new() })
+            }
+            res => res,
+        }
+    }
+
+    fn parse(contents: &str) -> Result<BTreeMap<String, Entry>, String> {
+        let json = Parser::new(contents).parse_document()?;
+        let benchmarks = json
+            .get("benchmarks")
+            .and_then(Json::as_object)
+            .ok_or("missing `benchmarks` object")?;
+
+        let mut parsed = BTreeMap::new();
+        for (bench_name, entry) in benchmarks {
+            let number = |key: &str| {
+                entry
+                    .get(key)
+                    .and_then(Json::as_f64)
+                    .ok_or_else(|| format!("missing `{}` of `{}`", key, bench_name))
+            };
+            let quartiles = match entry.get("quartiles") {
+                Some(Json::Array(q)) if q.len() == 3 => {
+                    let q = |i: usize| q[i].as_f64().ok_or("invalid `quartiles`".to_owned());
+                    (q(0)?, q(1)?, q(2)?)
+                }
+                _ => return Err(format!("missing `quartiles` of `{}`", bench_name)),
+            };
+            let summary = Summary {
+                sum: number("sum")?,
+                min: number("min")?,
+                max: number("max")?,
+                mean: number("mean")?,
+                median: number("median")?,
+                var: number("var")?,
+                std_dev: number("std_dev")?,
+                std_dev_pct: number("std_dev_pct")?,
+                median_abs_dev: number("median_abs_dev")?,
+                median_abs_dev_pct: number("median_abs_dev_pct")?,
+                quartiles,
+                iqr: number("iqr")?,
+            };
+            let entry = Entry {
+                samples: number("samples")? as usize,
+                summary,
+                mb_s: number("mb_s")? as usize,
+            };
+            parsed.insert(bench_name.clone(), entry);
+        }
+        Ok(parsed)
+    }
+
+    /// Name the baseline was saved under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Compares the result of a benchmark to the baseline. Returns `None` if the benchmark isn't
+    /// part of the baseline.
+    pub fn compare(&self, bench_name: &str, bs: &BenchSamples) -> Option<BenchChange> {
+        let entry = self.benchmarks.get(bench_name)?;
+        let baseline_mean = entry.summary.mean;
+        let change_pct = if baseline_mean == 0.0 {
+            0.0
+        } else {
+            (bs.ns_iter_summ.mean - baseline_mean) / baseline_mean * 100.0
+        };
+        let p_value =
+            stats::welch_t_test(&entry.summary, entry.samples, &bs.ns_iter_summ, SAMPLE_COUNT);
+        Some(BenchChange { baseline_mean, change_pct, p_value })
+    }
+
+    /// Saves the results of the benchmarks of this run, keeping the results of other benchmarks
+    /// that were already saved.
+    pub fn save(mut self, results: &[(String, BenchSamples)]) -> io::Result<()> {
+        for (bench_name, bs) in results {
+            let entry = Entry { samples: SAMPLE_COUNT, summary: bs.ns_iter_summ, mb_s: bs.mb_s };
+            self.benchmarks.insert(bench_name.clone(), entry);
+        }
+        fs::write(&self.path, self.to_json()).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to save benchmark baseline `{}`: {}", self.path.display(), e),
+            )
+        })
+    }
+
+    fn to_json(&self) -> String {
+        let mut out = String::from("{\n  \"benchmarks\": {");
+        for (i, (bench_name, entry)) in self.benchmarks.iter().enumerate() {
+            let s = &entry.summary;
+            let fields = [
+                ("sum", s.sum),
+                ("min", s.min),
+                ("max", s.max),
+                ("mean", s.mean),
+                ("median", s.median),
+                ("var", s.var),
+                ("std_dev", s.std_dev),
+                ("std_dev_pct", s.std_dev_pct),
+                ("median_abs_dev", s.median_abs_dev),
+                ("median_abs_dev_pct", s.median_abs_dev_pct),
+                ("iqr", s.iqr),
+            ];
+            out.push_str(if i == 0 { "\n" } else { ",\n" });
+            write!(
+                out,
+                "    \"{}\": {{ \"samples\": {}, \"mb_s\": {}",
+                EscapedString(bench_name),
+                entry.samples,
+                entry.mb_s
+            )
+            .unwrap();
+            for (key, value) in fields {
+                write!(out, ", \"{}\": {}", key, JsonNumber(value)).unwrap();
+            }
+            let (q1, q2, q3) = s.quartiles;
+            write!(
+                out,
+                ", \"quartiles\": [{}, {}, {}] }}",
+                JsonNumber(q1),
+                JsonNumber(q2),
+                JsonNumber(q3)
+            )
+            .unwrap();
+        }
+        out.push_str("\n  }\n}\n");
+        out
+    }
+}
+
+/// Formats a number for JSON, which has no representation for NaN and infinities.
+pub(crate) struct JsonNumber(pub(crate) f64);
+
+impl std::fmt::Display for JsonNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_finite() {
+            write!(f, "{:?}", self.0)
+        } else {
+            f.write_str("null")
+        }
+    }
+}
+
+/// The subset of JSON needed to read baselines back in.
+#[derive(Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        self.as_object()?.get(key)
+    }
+
+    fn as_object(&self) -> Option<&BTreeMap<String, Json>> {
+        match self {
+            Json::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::Number(n) => Some(n),
+            // Written for values that aren't finite.
+            Json::Null => Some(f64::NAN),
+            _ => None,
+        }
+    }
+}
+
+/// Baselines only nest a few levels deep, anything deeper is rejected rather than risking a stack
+/// overflow.
+const MAX_DEPTH: usize = 16;
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser { input, pos: 0, depth: 0 }
+    }
+
+    fn parse_document(&mut self) -> Result<Json, String> {
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+        if self.pos != self.input.len() {
+            return Err(self.error("trailing characters"));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, msg: &str) -> String {
+        format!("{} at byte {}", msg, self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            _ => Err(self.error(&format!("expected `{}`", expected))),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{' | '[') if self.depth == MAX_DEPTH => Err(self.error("nested too deeply")),
+            Some('{') => self.nested(Self::parse_object),
+            Some('[') => self.nested(Self::parse_array),
+            Some('"') => self.parse_string().map(Json::String),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some(_) => self.parse_literal(),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Json, String>) -> Result<Json, String> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_literal(&mut self) -> Result<Json, String> {
+        for (literal, value) in
+            [("null", Json::Null), ("true", Json::Bool(true)), ("false", Json::Bool(false))]
+        {
+            if self.input[self.pos..].starts_with(literal) {
+                self.pos += literal.len();
+                return Ok(value);
+            }
+        }
+        Err(self.error("unexpected character"))
+    }
+
+    fn parse_number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while let Some('-' | '+' | '.' | 'e' | 'E' | '0'..='9') = self.peek() {
+            self.pos += 1;
+        }
+        self.input[start..self.pos]
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\x08',
+                        Some('f') => '\x0c',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let hex = self.input.get(self.pos..self.pos + 4);
+                            let code = hex.and_then(|hex| u32::from_str_radix(hex, 16).ok());
+                            self.pos += 4;
+                            // Surrogate pairs are never written for test names, which only
+                            // escape control characters.
+                            code.and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    s.push(c);
+                }
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Json::Array(values)),
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut map = BTreeMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(map));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            map.insert(key, value);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Json::Object(map)),
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,161 @@
+use super::*;
+
+fn samples(samples: &[f64], mb_s: usize) -> BenchSamples {
+    BenchSamples { ns_iter_summ: Summary::new(samples), mb_s }
+}
+
+fn baseline(benchmarks: &[(&str, &[f64])]) -> Baseline {
+    let benchmarks = benchmarks
+        .iter()
+        .map(|&(name, s)| {
+            let entry = Entry { samples: s.len(), summary: Summary::new(s), mb_s: 0 };
+            (name.to_owned(), entry)
+        })
+        .collect();
+    Baseline { name: "test".to_owned(), path: PathBuf::new(), benchmarks }
+}
+
+#[test]
+fn json_round_trip() {
+    let mut b = baseline(&[("a::bench", &[1.0, 2.0, 3.5]), ("quote \" \n", &[7.0])]);
+    b.benchmarks.get_mut("a::bench").unwrap().mb_s = 12;
+    b.benchmarks.get_mut("quote \" \n").unwrap().summary.std_dev_pct = f64::NAN;
+
+    let parsed = Baseline::parse(&b.to_json()).unwrap();
+    assert_eq!(parsed["a::bench"], b.benchmarks["a::bench"]);
+
+    // NaN is saved as `null`.
+    let single = &parsed["quote \" \n"];
+    assert_eq!(single.summary.mean, 7.0);
+    assert!(single.summary.std_dev_pct.is_nan());
+}
+
+#[test]
+fn parse_invalid_baselines() {
+    for invalid in [
+        "",
+        "[]",
+        "{}",
+        r#"{ "benchmarks": { "a": {} } }"#,
+        r#"{ "benchmarks": { "a": { "samples": 50, } } }"#,
+        r#"{ "benchmarks": {} } trailing"#,
+    ] {
+        assert!(Baseline::parse(invalid).is_err(), "{:?} was accepted", invalid);
+    }
+    assert!(Baseline::parse(r#"{ "benchmarks": {} }"#).unwrap().is_empty());
+}
+
+#[test]
+fn parse_malformed_json() {
+    let deeply_nested = format!(r#"{{ "benchmarks": {}"#, "[".repeat(100_000));
+    for malformed in [
+        "{",
+        r#"{ "benchmarks" "#,
+        r#"{ "benchmarks": { "#,
+        r#"{ "benchmarks": {} "#,
+        r#"{ "benchmarks": {}, }"#,
+        r#"{ benchmarks: {} }"#,
+        r#"{ "benchmarks": [1, 2,] }"#,
+        r#"{ "benchmarks": [1 2] }"#,
+        r#"{ "benchmarks": -}"#,
+        r#"{ "benchmarks": 1e }"#,
+        r#"{ "benchmarks": nul }"#,
+        r#"{ "benchmarks": "unterminated }"#,
+        r#"{ "benchmarks": "\q" }"#,
+        r#"{ "benchmarks": "\u12" }"#,
+        r#"{ "benchmarks": "\ud800" }"#,
+        "{ \"benchmarks\": \"\\u12\u{e9}\" }",
+        "{ \"benchmarks\": \"\\",
+        &deeply_nested,
+    ] {
+        assert!(Parser::new(malformed).parse_document().is_err(), "{:?} was accepted", malformed);
+    }
+
+    let valid = r#" { "a" : [ 1.5, -2e3, null, true, false, "\u00e9\n\"" ], "b": {} } "#;
+    let json = Parser::new(valid).parse_document().unwrap();
+    let expected = [
+        Json::Number(1.5),
+        Json::Number(-2000.0),
+        Json::Null,
+        Json::Bool(true),
+        Json::Bool(false),
+        Json::String("\u{e9}\n\"".to_owned()),
+    ];
+    assert_eq!(json.get("a"), Some(&Json::Array(expected.into())));
+    assert_eq!(json.get("b"), Some(&Json::Object(BTreeMap::new())));
+}
+
+#[test]
+fn compare_to_baseline() {
+    let b = baseline(&[("bench", &[100.0, 101.0, 99.0, 100.0, 100.5, 99.5])]);
+
+    assert_eq!(b.compare("other", &samples(&[100.0], 0)), None);
+
+    let same = b.compare("bench", &samples(&[100.5, 99.5, 100.0, 101.0, 99.0, 100.0], 0)).unwrap();
+    assert_eq!(same.baseline_mean, 100.0);
+    assert_eq!(same.change_pct, 0.0);
+    assert!(!same.is_significant());
+    assert_eq!(fmt_bench_change(Some(&same)), "[+0.00%, p = 1.000: no change]");
+
+    let slower = b.compare("bench", &samples(&[110.0, 111.0, 109.0, 110.0, 110.5, 109.5], 0));
+    let slower = slower.unwrap();
+    assert!((slower.change_pct - 10.0).abs() < 1e-9);
+    assert!(slower.is_significant());
+    assert!(fmt_bench_change(Some(&slower)).ends_with("regressed]"));
+
+    let faster = b.compare("bench", &samples(&[90.0, 91.0, 89.0, 90.0, 90.5, 89.5], 0)).unwrap();
+    assert!(faster.is_significant());
+    assert_eq!(fmt_bench_change(Some(&faster)), "[-10.00%, p = 0.000: improved]");
+
+    assert_eq!(fmt_bench_change(None), "[not in baseline]");
+}
+
+#[test]
+fn find_baseline_dir() {
+    let no_exe = || Err(io::Error::new(io::ErrorKind::Other, "no executable"));
+    let exe = |path: &'static str| move || Ok(PathBuf::from(path));
+
+    let dir = baseline_dir(Some("baselines".into()), Some("target".into()), no_exe).unwrap();
+    assert_eq!(dir, Path::new("baselines"));
+    let dir = baseline_dir(None, Some("/tmp/target".into()), no_exe).unwrap();
+    assert_eq!(dir, Path::new("/tmp/target/bench-baselines"));
+    assert!(baseline_dir(None, None, no_exe).is_err());
+
+    // Cargo doesn't set `CARGO_TARGET_DIR` for the executables it runs.
+    let dir = baseline_dir(None, None, exe("/ws/target/release/deps/bench-0123")).unwrap();
+    assert_eq!(dir, Path::new("/ws/target/bench-baselines"));
+    let dir = baseline_dir(None, None, exe("/ws/target/x86_64-linux/release/deps/b")).unwrap();
+    assert_eq!(dir, Path::new("/ws/target/bench-baselines"));
+    let dir = baseline_dir(None, None, exe("/ws/out/bench")).unwrap();
+    assert_eq!(dir, Path::new("/ws/out/bench-baselines"));
+}
+
+#[test]
+fn save_and_load() {
+    let dir = env::temp_dir().join(format!("libtest-baselines-{}", std::process::id()));
+    let path = dir.join("baselines").join("main.json");
+
+    let missing = Baseline::load_at("main", path.clone()).unwrap_err();
+    assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+
+    // Opening the baseline creates its directory, before any benchmark runs.
+    let baseline = Baseline::open_at("main", path.clone()).unwrap();
+    assert!(path.parent().unwrap().is_dir());
+    baseline.save(&[("a".to_owned(), samples(&[1.0, 2.0], 0))]).unwrap();
+    // Saving again keeps the results of benchmarks that weren't run.
+    let baseline = Baseline::open_at("main", path.clone()).unwrap();
+    baseline.save(&[("b".to_owned(), samples(&[3.0, 4.0], 5))]).unwrap();
+
+    let loaded = Baseline::load_at("main", path.clone()).unwrap();
+    assert_eq!(loaded.name(), "main");
+    assert_eq!(loaded.benchmarks.keys().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(loaded.benchmarks["b"].mb_s, 5);
+    assert_eq!(loaded.benchmarks["b"].samples, SAMPLE_COUNT);
+
+    // An invalid baseline is reported when it's opened, rather than when it's saved.
+    fs::write(&path, "{").unwrap();
+    let invalid = Baseline::open_at("main", path).unwrap_err();
+    assert_eq!(invalid.kind(), io::ErrorKind::InvalidData);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -2,9 +2,10 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use super::helpers::isatty;
-use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
+use super::options::{ColorConfig, Options, OutputFormat, RunIgnored, Shard};
 use super::time::TestTimeOptions;
 
 #[derive(Debug)]
@@ -23,9 +24,14 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub shuffle: bool,
     pub shuffle_seed: Option<u64>,
+    pub shard: Option<Shard>,
+    pub bench_save: Option<String>,
+    pub bench_baseline: Option<String>,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    pub test_timeout: Option<Duration>,
+    pub retries: usize,
     pub options: Options,
 }
 
@@ -98,8 +104,10 @@ fn optgroups() -> getopts::Options {
             pretty = Print verbose output;
             terse  = Display one character per test;
             json   = Output a json document;
-            junit  = Output a JUnit document",
-            "pretty|terse|json|junit",
+            junit  = Output a JUnit document;
+            tap    = Output a TAP version 14 stream;
+            github-actions = Print verbose output with GitHub Actions annotations for failures",
+            "pretty|terse|json|junit|tap|github-actions",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
         .optopt(
@@ -138,12 +146,47 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "test-timeout",
+            "Kill tests that are still running after SECS seconds and report them as failed.
+
+            Tests are run in a separate process each to be able to kill them. This is only
+            possible for tests that are built into the test binary, i.e. not for dynamically
+            created tests (such as doctests).",
+            "SECS",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Re-run failing tests up to N times. Tests that pass on a retry are reported
+            as flaky instead of failed. Individual tests can override N with the
+            `#[retries = \"N\"]` attribute.
+
+            Only tests that are built into the test binary can be retried, i.e. not
+            dynamically created tests (such as doctests).",
+            "N",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
             "shuffle-seed",
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Split the tests into N shards of about the same size; requires --shard-index",
+            "N",
+        )
+        .optopt("", "shard-index", "Only run the tests of shard I (counting from 0)", "I")
+        .optopt("", "bench-save", "Save the results of the benchmarks as baseline NAME", "NAME")
+        .optopt(
+            "",
+            "bench-baseline",
+            "Compare the results of the benchmarks to the baseline NAME",
+            "NAME",
         );
     opts
 }
@@ -167,6 +210,18 @@ fn usage(binary: &str, options: &getopts::Options) {
 tests in the same order again. Note that --shuffle and --shuffle-seed do not
 affect whether the tests are run in parallel.
 
+To split the tests across several test runs (e.g. on different CI machines),
+pass the same --shard-count to each of them and a different --shard-index. The
+tests are assigned to shards based on a hash of their names, after applying
+any filters, so every run has to use the same filters. --list only lists the
+tests of the selected shard.
+
+Benchmark results can be saved with --bench-save NAME and compared against in
+a later run with --bench-baseline NAME, which reports the change of every
+benchmark and whether it is significant according to Welch's t-test. Baselines
+are stored in the directory given by the RUST_TEST_BENCH_BASELINE_DIR
+environment variable, or in bench-baselines in the target directory otherwise.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
@@ -184,7 +239,9 @@ fn usage(binary: &str, options: &getopts::Options) {
     `#[ignore]`       - When applied to a function which is already attributed as a
                         test, then the test runner will ignore these tests during
                         normal test runs. Running with --ignored or --include-ignored will run
-                        these tests."#,
+                        these tests.
+    `#[retries = "N"]` - Re-runs this test up to N times if it fails, overriding the
+                        value of --retries. Requires `#![feature(test_retries)]`."#,
         usage = options.usage(&message)
     );
 }
@@ -254,8 +311,12 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let (bench_save, bench_baseline) = get_bench_baselines(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -291,9 +352,14 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         format,
         shuffle,
         shuffle_seed,
+        shard,
+        bench_save,
+        bench_baseline,
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        retries,
         options,
     };
 
@@ -329,6 +395,46 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(secs_str) => match secs_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --test-timeout must not be 0".to_string()),
+            Ok(secs) => Some(Duration::from_secs(secs)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --test-timeout must be a number > 0 \
+                     (error: {})",
+                    e
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retries must be a number >= 0 \
+                     (error: {})",
+                    e
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -369,6 +475,68 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<Shard>> {
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let (count, index) = match (count, index) {
+        (Some(count), Some(index)) => (count, index),
+        (None, None) => return Ok(None),
+        _ => return Err("--shard-count and --shard-index must be used together".to_string()),
+    };
+
+    let count = match count.parse::<usize>() {
+        Ok(0) => return Err("argument for --shard-count must not be 0".to_string()),
+        Ok(n) => n,
+        Err(e) => {
+            return Err(format!(
+                "argument for --shard-count must be a number > 0 \
+                 (error: {})",
+                e
+            ));
+        }
+    };
+    let index = match index.parse::<usize>() {
+        Ok(n) if n < count => n,
+        Ok(n) => {
+            return Err(format!(
+                "argument for --shard-index must be less than --shard-count ({} >= {})",
+                n, count
+            ));
+        }
+        Err(e) => {
+            return Err(format!(
+                "argument for --shard-index must be a number \
+                 (error: {})",
+                e
+            ));
+        }
+    };
+
+    Ok(Some(Shard { index, count }))
+}
+
+fn get_bench_baselines(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<(Option<String>, Option<String>)> {
+    let save = unstable_optopt!(matches, allow_unstable, "bench-save");
+    let baseline = unstable_optopt!(matches, allow_unstable, "bench-baseline");
+
+    // Baselines are saved as `<name>.json`, so the name can't point to another directory.
+    for (option, name) in [("--bench-save", &save), ("--bench-baseline", &baseline)] {
+        if let Some(name) = name {
+            if name.is_empty() || name.contains(&['/', '\\'][..]) {
+                return Err(format!(
+                    "argument for {} must be a name, not a path (got `{}`)",
+                    option, name
+                ));
+            }
+        }
+    }
+
+    Ok((save, baseline))
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -409,10 +577,24 @@ fn get_format(
             }
             OutputFormat::Junit
         }
+        Some("tap") => {
+            if !allow_unstable {
+                return Err("The \"tap\" format is only accepted on the nightly compiler".into());
+            }
+            OutputFormat::Tap
+        }
+        Some("github-actions") => {
+            if !allow_unstable {
+                return Err(
+                    "The \"github-actions\" format is only accepted on the nightly compiler".into(),
+                );
+            }
+            OutputFormat::GithubActions
+        }
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, json or junit (was \
-                 {})",
+                "argument for --format must be pretty, terse, json, junit, tap or github-actions \
+                 (was {})",
                 v
             ));
         }
@@ -6,11 +6,15 @@
 use std::time::Instant;
 
 use super::{
-    bench::fmt_bench_samples,
+    baseline::Baseline,
+    bench::{fmt_bench_samples, BenchSamples},
     cli::TestOpts,
     event::{CompletedTest, TestEvent},
     filter_tests,
-    formatters::{JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
+    formatters::{
+        GithubActionsFormatter, JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter,
+        TapFormatter, TerseFormatter,
+    },
     helpers::{concurrency::get_concurrency, metrics::MetricMap},
     options::{Options, OutputFormat},
     run_tests, term,
@@ -45,6 +49,7 @@ pub struct ConsoleTestState {
     pub log_out: Option<File>,
     pub total: usize,
     pub passed: usize,
+    pub flaky: usize,
     pub failed: usize,
     pub ignored: usize,
     pub filtered_out: usize,
@@ -54,6 +59,12 @@ pub struct ConsoleTestState {
     pub failures: Vec<(TestDesc, Vec<u8>)>,
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    /// Flaky tests along with the output of their last failed attempt.
+    pub flaky_tests: Vec<(TestDesc, Vec<u8>)>,
+    /// Baseline to compare the results of benchmarks to, if requested by `--bench-baseline`.
+    pub bench_baseline: Option<Baseline>,
+    /// Results of the benchmarks, to be saved if requested by `--bench-save`.
+    pub bench_results: Vec<(String, BenchSamples)>,
     pub options: Options,
 }
 
@@ -63,11 +74,16 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             Some(ref path) => Some(File::create(path)?),
             None => None,
         };
+        let bench_baseline = match opts.bench_baseline {
+            Some(ref name) => Some(Baseline::load(name)?),
+            None => None,
+        };
 
         Ok(ConsoleTestState {
             log_out,
             total: 0,
             passed: 0,
+            flaky: 0,
             failed: 0,
             ignored: 0,
             filtered_out: 0,
@@ -77,6 +93,9 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             failures: Vec::new(),
             not_failures: Vec::new(),
             time_failures: Vec::new(),
+            flaky_tests: Vec::new(),
+            bench_baseline,
+            bench_results: Vec::new(),
             options: opts.options,
         })
     }
@@ -127,6 +146,9 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrFlaky(failed_attempts) => {
+                        format!("ok (flaky, {} failed attempts)", failed_attempts)
+                    }
                 },
                 name,
             )
@@ -138,7 +160,7 @@ pub fn write_log_result(
     }
 
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured
+        self.passed + self.flaky + self.failed + self.ignored + self.measured
     }
 }
 
@@ -151,6 +173,7 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
 
     let quiet = opts.format == OutputFormat::Terse;
     let mut st = ConsoleTestState::new(opts)?;
+    let bench_save = opts.bench_save.as_deref().map(Baseline::open).transpose()?;
 
     let mut ntest = 0;
     let mut nbench = 0;
@@ -187,7 +210,11 @@ fn plural(count: u32, s: &str) -> String {
             writeln!(output)?;
         }
 
-        writeln!(output, "{}, {}", plural(ntest, "test"), plural(nbench, "benchmark"))?;
+        let shard = match opts.shard {
+            Some(shard) => format!(" in shard {} of {}", shard.index, shard.count),
+            None => String::new(),
+        };
+        writeln!(output, "{}, {}{}", plural(ntest, "test"), plural(nbench, "benchmark"), shard)?;
     }
 
     Ok(())
@@ -209,6 +236,7 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
                 bs.ns_iter_summ.median,
                 bs.ns_iter_summ.max - bs.ns_iter_summ.min,
             );
+            st.bench_results.push((test.name.as_slice().to_owned(), bs));
             st.measured += 1
         }
         TestResult::TrFailed => {
@@ -225,6 +253,10 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrFlaky(_) => {
+            st.flaky += 1;
+            st.flaky_tests.push((test, stdout));
+        }
     }
 }
 
@@ -289,8 +321,11 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         }
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
         OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
+        OutputFormat::Tap => Box::new(TapFormatter::new(output)),
+        OutputFormat::GithubActions => Box::new(GithubActionsFormatter::new(output)),
     };
     let mut st = ConsoleTestState::new(opts)?;
+    let bench_save = opts.bench_save.as_deref().map(Baseline::open).transpose()?;
 
     // Prevent the usage of `Instant` in some cases:
     // - It's currently not supported for wasm targets.
@@ -303,7 +338,18 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
 
     assert!(st.current_test_count() == st.total);
 
-    out.write_run_finish(&st)
+    let success = out.write_run_finish(&st)?;
+
+    // The baseline is saved once the output is complete, so that failing to save it doesn't cut
+    // the output short.
+    if let Some(baseline) = bench_save {
+        if let Err(e) = baseline.save(&st.bench_results) {
+            eprintln!("error: {}", e);
+            return Ok(false);
+        }
+    }
+
+    Ok(success)
 }
 
 // Calculates padding for given test description.
@@ -0,0 +1,208 @@
+use std::{fmt, io, io::prelude::Write};
+
+use super::OutputFormatter;
+use crate::{
+    baseline::fmt_bench_change,
+    bench::fmt_bench_samples,
+    console::{ConsoleTestState, OutputLocation},
+    test_result::TestResult,
+    time,
+    types::{TestDesc, TestType},
+};
+
+/// Formatter printing the results in plain text, with a GitHub Actions [workflow command] for
+/// every failure so that CI shows them as annotations, as soon as the test finishes.
+///
+/// [workflow command]: https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions
+pub(crate) struct GithubActionsFormatter<T> {
+    out: OutputLocation<T>,
+}
+
+impl<T: Write> GithubActionsFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn write_plain<S: AsRef<str>>(&mut self, s: S) -> io::Result<()> {
+        let s = s.as_ref();
+        self.out.write_all(s.as_bytes())?;
+        self.out.flush()
+    }
+
+    /// Writes an `::error::` or `::warning::` command about the given test.
+    fn write_annotation(
+        &mut self,
+        command: &str,
+        desc: &TestDesc,
+        title: &str,
+        message: &str,
+    ) -> io::Result<()> {
+        let mut properties = String::new();
+        if let Some((file, line)) = doctest_location(desc) {
+            properties.push_str(&format!(
+                "file={},line={},",
+                EscapedProperty(file),
+                EscapedProperty(line)
+            ));
+        }
+        properties
+            .push_str(&format!("title={}", EscapedProperty(&format!("{}: {}", title, desc.name))));
+        self.write_plain(&format!("::{} {}::{}\n", command, properties, EscapedData(message)))
+    }
+}
+
+impl<T: Write> OutputFormatter for GithubActionsFormatter<T> {
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        let noun = if test_count != 1 { "tests" } else { "test" };
+        let shuffle_seed_msg = if let Some(shuffle_seed) = shuffle_seed {
+            format!(" (shuffle seed: {})", shuffle_seed)
+        } else {
+            String::new()
+        };
+        self.write_plain(&format!("\nrunning {} {}{}\n", test_count, noun, shuffle_seed_msg))
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.write_annotation(
+            "warning",
+            desc,
+            "slow test",
+            &format!("has been running for over {} seconds", time::TEST_WARN_TIMEOUT_S),
+        )
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        _exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let (status, failure) = match *result {
+            TestResult::TrOk => ("ok".to_owned(), None),
+            TestResult::TrFlaky(_) => ("ok (flaky)".to_owned(), None),
+            TestResult::TrIgnored => ("ignored".to_owned(), None),
+            TestResult::TrBench(ref bs) => {
+                let mut status = format!("bench: {}", fmt_bench_samples(bs));
+                if let Some(ref baseline) = state.bench_baseline {
+                    let change = baseline.compare(desc.name.as_slice(), bs);
+                    status.push_str(&format!(" {}", fmt_bench_change(change.as_ref())));
+                }
+                (status, None)
+            }
+            TestResult::TrFailed => ("FAILED".to_owned(), Some(String::new())),
+            TestResult::TrFailedMsg(ref msg) => ("FAILED".to_owned(), Some(format!("{}\n", msg))),
+            TestResult::TrTimedFail => (
+                "FAILED (time limit exceeded)".to_owned(),
+                Some("time limit exceeded\n".to_owned()),
+            ),
+        };
+        self.write_plain(&format!("test {} ... {}\n", desc.name, status))?;
+
+        if let Some(mut message) = failure {
+            message.push_str(&String::from_utf8_lossy(stdout));
+            if message.is_empty() {
+                message.push_str("test failed");
+            }
+            self.write_annotation("error", desc, "test failed", message.trim_end())?;
+        }
+        if let (TestResult::TrBench(ref bs), Some(baseline)) = (result, &state.bench_baseline) {
+            let change = baseline.compare(desc.name.as_slice(), bs);
+            if let Some(change) = change.filter(|c| c.is_significant() && c.change_pct > 0.0) {
+                let message = format!(
+                    "{:+.2}% compared to baseline `{}` (p = {:.3})",
+                    change.change_pct,
+                    baseline.name(),
+                    change.p_value
+                );
+                self.write_annotation("warning", desc, "benchmark regressed", &message)?;
+            }
+        }
+        if let TestResult::TrFlaky(failed_attempts) = *result {
+            let mut message = format!("passed after {} failed attempts\n", failed_attempts);
+            message.push_str(&String::from_utf8_lossy(stdout));
+            self.write_annotation("warning", desc, "flaky test", message.trim_end())?;
+        }
+        Ok(())
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let success = state.failed == 0;
+        let flaky =
+            if state.flaky > 0 { format!("{} flaky; ", state.flaky) } else { String::new() };
+        let mut s = format!(
+            "\ntest result: {}. {} passed; {}{} failed; {} ignored; {} measured; {} filtered out",
+            if success { "ok" } else { "FAILED" },
+            state.passed,
+            flaky,
+            state.failed,
+            state.ignored,
+            state.measured,
+            state.filtered_out
+        );
+
+        if let Some(ref exec_time) = state.exec_time {
+            s.push_str(&format!("; finished in {}", exec_time));
+        }
+
+        self.write_plain(&format!("{}\n\n", s))?;
+
+        Ok(success)
+    }
+}
+
+/// Returns the file and line of a doctest, which are part of its name
+/// (e.g. `src/lib.rs - foo::bar (line 12)`).
+fn doctest_location(desc: &TestDesc) -> Option<(&str, &str)> {
+    if desc.test_type != TestType::DocTest {
+        return None;
+    }
+    let (file, rest) = desc.name.as_slice().split_once(" - ")?;
+    let (_, line) = rest.rsplit_once("(line ")?;
+    Some((file.trim(), line.strip_suffix(')')?))
+}
+
+/// Escapes the message of a workflow command.
+struct EscapedData<'a>(&'a str);
+
+impl fmt::Display for EscapedData<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '%' => f.write_str("%25")?,
+                '\r' => f.write_str("%0D")?,
+                '\n' => f.write_str("%0A")?,
+                c => fmt::Write::write_char(f, c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Escapes a property value of a workflow command, which additionally can't contain the
+/// separators between properties.
+struct EscapedProperty<'a>(&'a str);
+
+impl fmt::Display for EscapedProperty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                ':' => f.write_str("%3A")?,
+                ',' => f.write_str("%2C")?,
+                c => EscapedData(c.encode_utf8(&mut [0; 4])).fmt(f)?,
+            }
+        }
+        Ok(())
+    }
+}
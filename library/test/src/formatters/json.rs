@@ -2,6 +2,7 @@
 
 use super::OutputFormatter;
 use crate::{
+    baseline::JsonNumber,
     console::{ConsoleTestState, OutputLocation},
     test_result::TestResult,
     time,
@@ -124,6 +125,15 @@ fn write_result(
                 self.write_event("test", desc.name.as_slice(), "ignored", exec_time, stdout, None)
             }
 
+            TestResult::TrFlaky(failed_attempts) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""failed_attempts": {}"#, failed_attempts)),
+            ),
+
             TestResult::TrBench(ref bs) => {
                 let median = bs.ns_iter_summ.median as usize;
                 let deviation = (bs.ns_iter_summ.max - bs.ns_iter_summ.min) as usize;
@@ -134,15 +144,29 @@ fn write_result(
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
 
+                let change = match state.bench_baseline {
+                    Some(ref baseline) => match baseline.compare(desc.name.as_slice(), bs) {
+                        Some(change) => format!(
+                            r#", "baseline_mean": {}, "change_pct": {}, "p_value": {}"#,
+                            JsonNumber(change.baseline_mean),
+                            JsonNumber(change.change_pct),
+                            JsonNumber(change.p_value)
+                        ),
+                        None => r#", "baseline_mean": null"#.to_owned(),
+                    },
+                    None => String::new(),
+                };
+
                 let line = format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{}\", \
                      \"median\": {}, \
-                     \"deviation\": {}{} }}",
+                     \"deviation\": {}{}{} }}",
                     EscapedString(desc.name.as_slice()),
                     median,
                     deviation,
-                    mbps
+                    mbps,
+                    change
                 );
 
                 self.writeln_message(&*line)
@@ -158,16 +182,20 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
     }
 
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        // Flaky tests are only mentioned if there are any, to keep the schema stable otherwise.
+        let flaky =
+            if state.flaky > 0 { format!("\"flaky\": {}, ", state.flaky) } else { String::new() };
         self.write_message(&*format!(
             "{{ \"type\": \"suite\", \
              \"event\": \"{}\", \
              \"passed\": {}, \
-             \"failed\": {}, \
+             {}\"failed\": {}, \
              \"ignored\": {}, \
              \"measured\": {}, \
              \"filtered_out\": {}",
             if state.failed == 0 { "ok" } else { "failed" },
             state.passed,
+            flaky,
             state.failed,
             state.ignored,
             state.measured,
@@ -187,7 +215,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
 /// A formatting utility used to print strings with characters in need of escaping.
 /// Base code taken form `libserialize::json::escape_str`
-struct EscapedString<S: AsRef<str>>(S);
+pub(crate) struct EscapedString<S: AsRef<str>>(pub(crate) S);
 
 impl<S: AsRef<str>> std::fmt::Display for EscapedString<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> ::std::fmt::Result {
@@ -2,7 +2,8 @@
 
 use super::OutputFormatter;
 use crate::{
-    bench::fmt_bench_samples,
+    baseline::{fmt_bench_change, Baseline},
+    bench::{fmt_bench_samples, BenchSamples},
     console::{ConsoleTestState, OutputLocation},
     term,
     test_result::TestResult,
@@ -49,6 +50,10 @@ pub fn write_ignored(&mut self) -> io::Result<()> {
         self.write_short_result("ignored", term::color::YELLOW)
     }
 
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("ok (flaky)", term::color::YELLOW)
+    }
+
     pub fn write_time_failed(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
@@ -57,6 +62,24 @@ pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
 
+    pub fn write_bench_change(
+        &mut self,
+        desc: &TestDesc,
+        bs: &BenchSamples,
+        baseline: &Baseline,
+    ) -> io::Result<()> {
+        let change = baseline.compare(desc.name.as_slice(), bs);
+        let color = match change {
+            Some(ref change) if change.is_significant() && change.change_pct > 0.0 => {
+                term::color::RED
+            }
+            Some(ref change) if change.is_significant() => term::color::GREEN,
+            _ => term::color::YELLOW,
+        };
+        self.write_plain(" ")?;
+        self.write_pretty(&fmt_bench_change(change.as_ref()), color)
+    }
+
     pub fn write_short_result(
         &mut self,
         result: &str,
@@ -164,6 +187,10 @@ pub fn write_time_failures(&mut self, state: &ConsoleTestState) -> io::Result<()
         self.write_results(&state.time_failures, "failures (time limit exceeded)")
     }
 
+    pub fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_results(&state.flaky_tests, "flaky tests")
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         if let Some(test_mode) = desc.test_mode() {
@@ -205,7 +232,7 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         _: &[u8],
-        _: &ConsoleTestState,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         if self.is_multithreaded {
             self.write_test_name(desc)?;
@@ -218,8 +245,12 @@ fn write_result(
             TestResult::TrBench(ref bs) => {
                 self.write_bench()?;
                 self.write_plain(&format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(ref baseline) = state.bench_baseline {
+                    self.write_bench_change(desc, bs, baseline)?;
+                }
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrFlaky(_) => self.write_flaky()?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -238,6 +269,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_successes(state)?;
         }
+        if !state.flaky_tests.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
         let success = state.failed == 0;
         if !success {
             if !state.failures.is_empty() {
@@ -258,9 +292,12 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        // Flaky tests are only mentioned if there are any, to keep the summary stable otherwise.
+        let flaky =
+            if state.flaky > 0 { format!("{} flaky; ", state.flaky) } else { String::new() };
         let s = format!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out",
-            state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+            ". {} passed; {}{} failed; {} ignored; {} measured; {} filtered out",
+            state.passed, flaky, state.failed, state.ignored, state.measured, state.filtered_out
         );
 
         self.write_plain(&s)?;
@@ -0,0 +1,190 @@
+use std::{io, io::prelude::Write};
+
+use super::OutputFormatter;
+use crate::{
+    console::{ConsoleTestState, OutputLocation},
+    test_result::TestResult,
+    time,
+    types::TestDesc,
+};
+
+/// Formatter producing a [TAP version 14](https://testanything.org/tap-version-14-specification.html)
+/// stream.
+///
+/// Every result is written as soon as the test finishes, so a consumer still sees the results
+/// of all completed tests if the test binary gets killed. Tests with a special mode (e.g.
+/// `should_panic`) are reported as a subtest checking that mode, ignored tests use the `SKIP`
+/// directive and failure details are attached as a YAML diagnostic block.
+pub(crate) struct TapFormatter<T> {
+    out: OutputLocation<T>,
+    /// Number of the last written test point.
+    test_count: usize,
+}
+
+impl<T: Write> TapFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out, test_count: 0 }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn write_plain<S: AsRef<str>>(&mut self, s: S) -> io::Result<()> {
+        let s = s.as_ref();
+        self.out.write_all(s.as_bytes())?;
+        self.out.flush()
+    }
+
+    /// Writes the YAML diagnostic block of a test point, using block scalars for all values so
+    /// that nothing needs to be quoted.
+    fn write_yaml_block(&mut self, fields: &[(&str, String)]) -> io::Result<()> {
+        if fields.is_empty() {
+            return Ok(());
+        }
+        let mut block = String::from("  ---\n");
+        for (key, value) in fields {
+            block.push_str(&format!("  {}: |\n", key));
+            for line in value.lines() {
+                block.push_str(&format!("    {}\n", line));
+            }
+        }
+        block.push_str("  ...\n");
+        self.write_plain(&block)
+    }
+}
+
+impl<T: Write> OutputFormatter for TapFormatter<T> {
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        self.write_plain("TAP version 14\n")?;
+        if let Some(shuffle_seed) = shuffle_seed {
+            self.write_plain(&format!("# shuffle seed: {}\n", shuffle_seed))?;
+        }
+        self.write_plain(&format!("1..{}\n", test_count))
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.write_plain(&format!(
+            "# test {} has been running for over {} seconds\n",
+            TapEscaped(desc.name.as_slice()),
+            time::TEST_WARN_TIMEOUT_S
+        ))
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        self.test_count += 1;
+        let name = TapEscaped(desc.name.as_slice());
+        let ok = match *result {
+            TestResult::TrOk
+            | TestResult::TrIgnored
+            | TestResult::TrBench(_)
+            | TestResult::TrFlaky(_) => "ok",
+            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => "not ok",
+        };
+
+        if let Some(test_mode) = desc.test_mode() {
+            // The test itself has a single assertion: that it behaved according to its mode.
+            self.write_plain(&format!("    # Subtest: {}\n    1..1\n", name))?;
+            self.write_plain(&format!("    {} 1 - {}\n", ok, test_mode))?;
+        }
+
+        let mut directive = String::new();
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        match *result {
+            TestResult::TrOk | TestResult::TrFailed => {}
+            TestResult::TrFailedMsg(ref msg) => fields.push(("message", msg.clone())),
+            TestResult::TrTimedFail => fields.push(("message", "time limit exceeded".to_owned())),
+            TestResult::TrFlaky(failed_attempts) => {
+                fields.push(("failed_attempts", failed_attempts.to_string()))
+            }
+            TestResult::TrIgnored => {
+                directive.push_str(" # SKIP");
+                #[cfg(not(bootstrap))]
+                if let Some(msg) = desc.ignore_message {
+                    directive.push_str(&format!(" {}", TapEscaped(msg)));
+                }
+            }
+            TestResult::TrBench(ref bs) => {
+                fields.push(("median", format!("{} ns/iter", bs.ns_iter_summ.median)));
+                fields.push((
+                    "deviation",
+                    format!("{} ns/iter", bs.ns_iter_summ.max - bs.ns_iter_summ.min),
+                ));
+                if bs.mb_s != 0 {
+                    fields.push(("mib_per_second", bs.mb_s.to_string()));
+                }
+                if let Some(change) = state
+                    .bench_baseline
+                    .as_ref()
+                    .and_then(|baseline| baseline.compare(desc.name.as_slice(), bs))
+                {
+                    fields.push(("baseline_mean", format!("{} ns/iter", change.baseline_mean)));
+                    fields.push(("change_pct", format!("{:.2}", change.change_pct)));
+                    fields.push(("p_value", format!("{:.3}", change.p_value)));
+                }
+            }
+        }
+        self.write_plain(&format!("{} {} - {}{}\n", ok, self.test_count, name, directive))?;
+
+        if let Some(exec_time) = exec_time {
+            fields.push(("duration_ms", format!("{}", exec_time.0.as_secs_f64() * 1000.0)));
+        }
+        // The output of a flaky test is the one of its last failed attempt.
+        let display_stdout = state.options.display_output
+            || ok == "not ok"
+            || matches!(result, TestResult::TrFlaky(_));
+        if display_stdout && !stdout.is_empty() {
+            fields.push(("stdout", String::from_utf8_lossy(stdout).into_owned()));
+        }
+        self.write_yaml_block(&fields)
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        self.write_plain(&format!("# passed: {}\n", state.passed))?;
+        if state.flaky > 0 {
+            self.write_plain(&format!("# flaky: {}\n", state.flaky))?;
+        }
+        self.write_plain(&format!(
+            "# failed: {}\n# ignored: {}\n# measured: {}\n# filtered out: {}\n",
+            state.failed, state.ignored, state.measured, state.filtered_out
+        ))?;
+
+        if let Some(ref exec_time) = state.exec_time {
+            self.write_plain(&format!("# finished in {}\n", exec_time))?;
+        }
+
+        Ok(state.failed == 0)
+    }
+}
+
+/// Escapes `#` and `\` in test point descriptions, so that they are not mistaken for a
+/// directive.
+struct TapEscaped<'a>(&'a str);
+
+impl std::fmt::Display for TapEscaped<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '#' => f.write_str("\\#")?,
+                '\\' => f.write_str("\\\\")?,
+                // Descriptions can't span multiple lines.
+                '\n' | '\r' => f.write_str(" ")?,
+                c => std::fmt::Write::write_char(f, c)?,
+            }
+        }
+        Ok(())
+    }
+}
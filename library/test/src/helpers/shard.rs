@@ -0,0 +1,23 @@
+use super::shuffle::calculate_hash;
+use crate::options::Shard;
+use crate::types::TestDescAndFn;
+
+/// Returns the tests of the given shard, in their original order.
+///
+/// The tests are dealt out to the shards in the order of the hashes of their names, so that the
+/// shards differ by at most one test in size and the tests of a module are spread across them.
+pub fn shard_tests(shard: Shard, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
+    let mut by_hash: Vec<(u64, usize)> = tests
+        .iter()
+        .enumerate()
+        .map(|(i, test)| (calculate_hash(&test.desc.name.as_slice()), i))
+        .collect();
+    by_hash.sort_unstable();
+
+    let mut in_shard = vec![false; tests.len()];
+    for (n, &(_, i)) in by_hash.iter().enumerate() {
+        in_shard[i] = n % shard.count == shard.index;
+    }
+
+    tests.into_iter().zip(in_shard).filter_map(|(test, in_shard)| in_shard.then(|| test)).collect()
+}
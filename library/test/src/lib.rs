@@ -27,7 +27,7 @@
 // Public reexports
 pub use self::bench::{black_box, Bencher};
 pub use self::console::run_tests_console;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, Shard, ShouldPanic};
 pub use self::types::TestName::*;
 pub use self::types::*;
 pub use self::ColorConfig::*;
@@ -56,14 +56,16 @@ pub mod test {
     collections::VecDeque,
     env, io,
     io::prelude::Write,
+    mem,
     panic::{self, catch_unwind, AssertUnwindSafe, PanicInfo},
     process::{self, Command, Termination},
-    sync::mpsc::{channel, Sender},
+    sync::mpsc::{self, channel, Sender},
     sync::{Arc, Mutex},
     thread,
     time::{Duration, Instant},
 };
 
+mod baseline;
 pub mod bench;
 mod cli;
 mod console;
@@ -83,6 +85,7 @@ pub mod test {
 use event::{CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
 use helpers::exit_code::get_exit_code;
+use helpers::shard::shard_tests;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use options::{Concurrent, RunStrategy};
 use test_result::*;
@@ -130,6 +133,9 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=unwind.
 pub fn test_main_static(tests: &[&TestDescAndFn]) {
+    // Tests are run in subprocesses when `--test-timeout` is used.
+    run_secondary_test_if_requested(tests);
+
     let args = env::args().collect::<Vec<_>>();
     let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
     test_main(&args, owned_tests, None)
@@ -144,8 +150,16 @@ pub fn test_main_static(tests: &[&TestDescAndFn]) {
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=abort.
 pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
-    // If we're being run in SpawnedSecondary mode, run the test here. run_test
-    // will then exit the process.
+    run_secondary_test_if_requested(tests);
+
+    let args = env::args().collect::<Vec<_>>();
+    let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
+    test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
+}
+
+/// If we're being run in SpawnedSecondary mode, runs the requested test here.
+/// `run_test_in_spawned_subprocess` will then exit the process.
+fn run_secondary_test_if_requested(tests: &[&TestDescAndFn]) {
     if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
         env::remove_var(SECONDARY_TEST_INVOKER_VAR);
         let test = tests
@@ -161,10 +175,6 @@ pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
         };
         run_test_in_spawned_subprocess(desc, Box::new(testfn));
     }
-
-    let args = env::args().collect::<Vec<_>>();
-    let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
-    test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
 }
 
 /// Clones static values for putting into a dynamic vector, which test_main()
@@ -218,6 +228,17 @@ struct TimeoutEntry {
         timeout: Instant,
     }
 
+    struct RetryState {
+        testfn: fn(),
+        retries_left: usize,
+        failed_attempts: usize,
+        /// Output of the last failed attempt.
+        stdout: Vec<u8>,
+    }
+
+    type RetryMap =
+        HashMap<TestId, RetryState, BuildHasherDefault<collections::hash_map::DefaultHasher>>;
+
     let tests_len = tests.len();
 
     let mut filtered_tests = filter_tests(opts, tests);
@@ -261,8 +282,24 @@ struct TimeoutEntry {
     }
     let mut pending = 0;
 
+    // Only static tests can be run again, dynamic test functions are consumed by running them.
+    let mut retry_states: RetryMap = remaining
+        .iter()
+        .filter_map(|(id, test)| match test.testfn {
+            StaticTestFn(testfn) => {
+                let retries_left = test.desc.max_retries(opts.retries);
+                let state =
+                    RetryState { testfn, retries_left, failed_attempts: 0, stdout: Vec::new() };
+                (retries_left > 0).then(|| (*id, state))
+            }
+            _ => None,
+        })
+        .collect();
+
     let (tx, rx) = channel::<CompletedTest>();
-    let run_strategy = if opts.options.panic_abort && !opts.force_run_in_process {
+    // Tests can only be killed after a timeout if they run in their own process.
+    let needs_subprocess = opts.options.panic_abort || opts.test_timeout.is_some();
+    let run_strategy = if needs_subprocess && !opts.force_run_in_process {
         RunStrategy::SpawnPrimary
     } else {
         RunStrategy::InProcess
@@ -296,6 +333,37 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         })
     }
 
+    /// Returns the test to run again if it failed and has retries left. Otherwise, the result of
+    /// a test that passed after failing before is turned into `TrFlaky`.
+    fn retry_if_failed(
+        retry_states: &mut RetryMap,
+        completed_test: &mut CompletedTest,
+    ) -> Option<TestDescAndFn> {
+        let state = retry_states.get_mut(&completed_test.id)?;
+        match completed_test.result {
+            TrFailed | TrFailedMsg(_) | TrTimedFail if state.retries_left > 0 => {
+                state.retries_left -= 1;
+                state.failed_attempts += 1;
+                state.stdout = mem::take(&mut completed_test.stdout);
+                if let TrFailedMsg(ref msg) = completed_test.result {
+                    state.stdout.extend_from_slice(format!("note: {}\n", msg).as_bytes());
+                }
+                return Some(TestDescAndFn {
+                    desc: completed_test.desc.clone(),
+                    testfn: StaticTestFn(state.testfn),
+                });
+            }
+            TrOk if state.failed_attempts > 0 => {
+                completed_test.result = TrFlaky(state.failed_attempts);
+                // The output of the failure is what's interesting about a flaky test.
+                completed_test.stdout = mem::take(&mut state.stdout);
+            }
+            _ => {}
+        }
+        retry_states.remove(&completed_test.id);
+        None
+    }
+
     if concurrency == 1 {
         while !remaining.is_empty() {
             let (id, test) = remaining.pop().unwrap();
@@ -304,7 +372,20 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             let join_handle =
                 run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone(), Concurrent::No);
             assert!(join_handle.is_none());
-            let completed_test = rx.recv().unwrap();
+            let mut completed_test = rx.recv().unwrap();
+            while let Some(test) = retry_if_failed(&mut retry_states, &mut completed_test) {
+                let join_handle = run_test(
+                    opts,
+                    !opts.run_tests,
+                    id,
+                    test,
+                    run_strategy,
+                    tx.clone(),
+                    Concurrent::No,
+                );
+                assert!(join_handle.is_none());
+                completed_test = rx.recv().unwrap();
+            }
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
@@ -367,6 +448,27 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
                 }
             }
 
+            if let Some(test) = retry_if_failed(&mut retry_states, &mut completed_test) {
+                // The retry takes over the slot of the failed attempt.
+                let id = completed_test.id;
+                let timeout = time::get_default_test_timeout();
+                let desc = test.desc.clone();
+                let join_handle = run_test(
+                    opts,
+                    !opts.run_tests,
+                    id,
+                    test,
+                    run_strategy,
+                    tx.clone(),
+                    Concurrent::Yes,
+                );
+                running_tests.insert(id, RunningTest { join_handle });
+                // The timeout of the failed attempt would otherwise apply to the retry as well.
+                timeout_queue.retain(|entry| entry.id != id);
+                timeout_queue.push_back(TimeoutEntry { id, desc, timeout });
+                continue;
+            }
+
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
             pending -= 1;
@@ -427,6 +529,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
     // Sort the tests alphabetically
     filtered.sort_by(|t1, t2| t1.desc.name.as_slice().cmp(t2.desc.name.as_slice()));
 
+    // Only keep the tests of the requested shard
+    if let Some(shard) = opts.shard {
+        filtered = shard_tests(shard, filtered);
+    }
+
     filtered
 }
 
@@ -476,6 +583,7 @@ struct TestRunOpts {
         pub nocapture: bool,
         pub concurrency: Concurrent,
         pub time: Option<time::TestTimeOptions>,
+        pub timeout: Option<Duration>,
     }
 
     fn run_test_inner(
@@ -505,6 +613,7 @@ fn run_test_inner(
                 opts.time.is_some(),
                 monitor_ch,
                 opts.time,
+                opts.timeout,
             ),
         };
 
@@ -532,8 +641,13 @@ fn run_test_inner(
         }
     }
 
-    let test_run_opts =
-        TestRunOpts { strategy, nocapture: opts.nocapture, concurrency, time: opts.time_options };
+    let mut test_run_opts = TestRunOpts {
+        strategy,
+        nocapture: opts.nocapture,
+        concurrency,
+        time: opts.time_options,
+        timeout: opts.test_timeout,
+    };
 
     match testfn {
         DynBenchFn(benchfn) => {
@@ -549,6 +663,12 @@ fn run_test_inner(
         DynTestFn(f) => {
             match strategy {
                 RunStrategy::InProcess => (),
+                // Dynamic tests can't be looked up by name in a subprocess. As long as a panic
+                // doesn't abort the whole runner, they can still be run in-process, without
+                // enforcing `--test-timeout`.
+                RunStrategy::SpawnPrimary if !opts.options.panic_abort => {
+                    test_run_opts.strategy = RunStrategy::InProcess;
+                }
                 _ => panic!("Cannot run dynamic test fn out-of-process"),
             };
             run_test_inner(
@@ -619,6 +739,7 @@ fn spawn_test_subprocess(
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    timeout: Option<Duration>,
 ) {
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
@@ -629,26 +750,45 @@ fn spawn_test_subprocess(
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
-            Ok(out) => out,
+        let child = match command.spawn() {
+            Ok(child) => child,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
                 return (TrFailed, err.into_bytes(), None);
             }
         };
+        let output = match wait_with_timeout(child, timeout) {
+            Ok(out) => out,
+            Err(e) => {
+                let err = format!("Failed to wait for {} as child for test: {:?}", args[0], e);
+                return (TrFailed, err.into_bytes(), None);
+            }
+        };
         let exec_time = start.map(|start| {
             let duration = start.elapsed();
             TestExecTime(duration)
         });
 
-        let std::process::Output { stdout, stderr, status } = output;
+        let (status, stdout, stderr) = output;
         let mut test_output = stdout;
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
+        let status = match status {
+            Some(status) => status,
+            None => {
+                let secs = timeout.map_or(0, |timeout| timeout.as_secs());
+                let msg = format!("timed out after {}s", secs);
+                return (TrFailedMsg(msg), test_output, exec_time);
+            }
+        };
+
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
             Ok(get_result_from_exit_code(&desc, exit_code, &time_opts, &exec_time))
@@ -667,6 +807,93 @@ fn spawn_test_subprocess(
     monitor_ch.send(message).unwrap();
 }
 
+/// Waits for the test subprocess to exit and collects its output, like `Child::wait_with_output`.
+///
+/// If the process is still running after `timeout`, it is killed and `None` is returned instead
+/// of its exit status. Processes spawned by the test aren't killed, and can keep the pipes of its
+/// output open, so its output is only read for a little longer than the timeout.
+fn wait_with_timeout(
+    mut child: process::Child,
+    timeout: Option<Duration>,
+) -> io::Result<(Option<process::ExitStatus>, Vec<u8>, Vec<u8>)> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => {
+            let process::Output { status, stdout, stderr } = child.wait_with_output()?;
+            return Ok((Some(status), stdout, stderr));
+        }
+    };
+
+    // The pipes are drained in the background, so that a test writing a lot of output can't
+    // block before we get to kill it.
+    let stdout = PipeReader::new(child.stdout.take());
+    let stderr = PipeReader::new(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let mut poll_interval = Duration::from_millis(1);
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            child.kill()?;
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(poll_interval.min(deadline - now));
+        poll_interval = (poll_interval * 2).min(Duration::from_millis(100));
+    };
+
+    // Give the pipes some time to be closed after a killed test, as the pipes may only be at EOF
+    // once the system is done with the process.
+    let pipe_deadline = deadline.max(Instant::now() + Duration::from_secs(1));
+    let stdout = stdout.finish(pipe_deadline)?;
+    let stderr = stderr.finish(pipe_deadline)?;
+    Ok((status, stdout, stderr))
+}
+
+/// A pipe of a test subprocess, read in the background.
+struct PipeReader {
+    output: Arc<Mutex<Vec<u8>>>,
+    done: mpsc::Receiver<io::Result<()>>,
+}
+
+impl PipeReader {
+    fn new<R: io::Read + Send + 'static>(pipe: Option<R>) -> PipeReader {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let (done_tx, done) = mpsc::channel();
+        let thread_output = output.clone();
+        thread::spawn(move || {
+            let read = || -> io::Result<()> {
+                if let Some(mut pipe) = pipe {
+                    let mut buf = [0; 4096];
+                    loop {
+                        let n = pipe.read(&mut buf)?;
+                        if n == 0 {
+                            return Ok(());
+                        }
+                        thread_output.lock().unwrap().extend_from_slice(&buf[..n]);
+                    }
+                }
+                Ok(())
+            };
+            let _ = done_tx.send(read());
+        });
+        PipeReader { output, done }
+    }
+
+    /// Waits until the pipe is closed or `deadline` passes, and returns what was read from it.
+    /// If the pipe is still open, the thread reading it is left behind.
+    fn finish(self, deadline: Instant) -> io::Result<Vec<u8>> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        if let Ok(result) = self.done.recv_timeout(timeout) {
+            result?;
+        }
+        Ok(mem::take(&mut *self.output.lock().unwrap()))
+    }
+}
+
 fn run_test_in_spawned_subprocess(desc: TestDesc, testfn: Box<dyn FnOnce() + Send>) -> ! {
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicInfo<'_>>| {
@@ -41,6 +41,10 @@ pub enum OutputFormat {
     Json,
     /// JUnit output
     Junit,
+    /// TAP version 14 output
+    Tap,
+    /// Verbose output with GitHub Actions annotations for failures
+    GithubActions,
 }
 
 /// Whether ignored test should be run or not
@@ -52,6 +56,15 @@ pub enum RunIgnored {
     Only,
 }
 
+/// Part of the tests to run when they are split across several test runs
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// Index of this part, counting from 0
+    pub index: usize,
+    /// Number of parts the tests are split into
+    pub count: usize,
+}
+
 #[derive(Clone, Copy)]
 pub enum RunStrategy {
     /// Runs the test in the current process, and sends the result back over the
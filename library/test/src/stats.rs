@@ -300,3 +300,116 @@ pub fn winsorize(samples: &mut [f64], pct: f64) {
         }
     }
 }
+
+/// Welch's t-test for the difference between the means of two sample sets, which doesn't assume
+/// that they have the same variance. Takes the summaries and sizes of the sample sets and returns
+/// the two-sided p-value, i.e. the probability of seeing a difference at least as large if both
+/// sample sets had the same mean.
+///
+/// See: <https://en.wikipedia.org/wiki/Welch%27s_t-test>
+pub fn welch_t_test(a: &Summary, a_len: usize, b: &Summary, b_len: usize) -> f64 {
+    if a_len < 2 || b_len < 2 {
+        return 1.0;
+    }
+    let a_var = a.var / a_len as f64;
+    let b_var = b.var / b_len as f64;
+    let var = a_var + b_var;
+    if var == 0.0 {
+        return if a.mean == b.mean { 1.0 } else { 0.0 };
+    }
+
+    let t = (a.mean - b.mean) / var.sqrt();
+    // Welch–Satterthwaite equation
+    let df = var * var / (a_var * a_var / (a_len - 1) as f64 + b_var * b_var / (b_len - 1) as f64);
+    student_t_p_value(t, df)
+}
+
+/// Two-sided p-value of the t statistic `t` in a Student's t-distribution with `df` degrees of
+/// freedom.
+fn student_t_p_value(t: f64, df: f64) -> f64 {
+    regularized_incomplete_beta(df / (df + t * t), df / 2.0, 0.5)
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`, evaluated with its continued fraction
+/// representation.
+///
+/// See: <https://en.wikipedia.org/wiki/Beta_function#Incomplete_beta_function>
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly below this point, and the symmetry
+    // `I_x(a, b) = 1 - I_(1-x)(b, a)` is used above it.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Evaluates the continued fraction of the incomplete beta function with the modified Lentz's
+/// method.
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 300;
+    const EPSILON: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+    let not_tiny = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / not_tiny(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        // Even step
+        let num = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / not_tiny(1.0 + num * d);
+        c = not_tiny(1.0 + num / c);
+        h *= d * c;
+        // Odd step
+        let num = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / not_tiny(1.0 + num * d);
+        c = not_tiny(1.0 + num / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// Natural logarithm of the gamma function for positive `x`, using the Lanczos approximation.
+///
+/// See: <https://en.wikipedia.org/wiki/Lanczos_approximation>
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.99999999999980993,
+        676.5203681218851,
+        -1259.1392167224028,
+        771.32342877765313,
+        -176.61502916214059,
+        12.507343278686905,
+        -0.13857109526572012,
+        9.9843695780195716e-6,
+        1.5056327351493116e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (i, &coefficient) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += coefficient / (x + i as f64);
+    }
+    let t = x + G + 0.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
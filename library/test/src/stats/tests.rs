@@ -571,6 +571,38 @@ fn test_sum_f64_between_ints_that_sum_to_0() {
     assert_eq!([1e30f64, 1.2f64, -1e30f64].sum(), 1.2);
 }
 
+#[test]
+fn test_ln_gamma() {
+    // Γ(1) = Γ(2) = 1, Γ(5) = 24, Γ(0.5) = √π
+    assert_approx_eq!(ln_gamma(1.0), 0.0);
+    assert_approx_eq!(ln_gamma(2.0), 0.0);
+    assert_approx_eq!(ln_gamma(5.0), 24f64.ln());
+    assert_approx_eq!(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln());
+}
+
+#[test]
+fn test_student_t_p_value() {
+    // The t-distribution with one degree of freedom is the Cauchy distribution.
+    assert_approx_eq!(student_t_p_value(1.0, 1.0), 0.5);
+    // With two degrees of freedom, P(|T| > t) = 1 - t / sqrt(2 + t²).
+    assert_approx_eq!(student_t_p_value(2.0, 2.0), 1.0 - 2.0 / 6f64.sqrt());
+    assert_approx_eq!(student_t_p_value(-2.0, 2.0), 1.0 - 2.0 / 6f64.sqrt());
+    assert_approx_eq!(student_t_p_value(0.0, 10.0), 1.0);
+}
+
+#[test]
+fn test_welch_t_test() {
+    let a = [10.0, 11.0, 9.0, 10.5, 9.5, 10.0, 10.2, 9.8];
+    let b = [12.0, 12.5, 11.5, 12.2, 11.8, 12.0, 12.1, 11.9];
+    let (a_summ, b_summ) = (Summary::new(&a), Summary::new(&b));
+
+    assert!(welch_t_test(&a_summ, a.len(), &b_summ, b.len()) < 0.001);
+    assert_approx_eq!(welch_t_test(&a_summ, a.len(), &a_summ, a.len()), 1.0);
+
+    let c = [10.1, 10.9, 9.2, 10.4, 9.4, 10.1, 10.0, 9.9];
+    assert!(welch_t_test(&a_summ, a.len(), &Summary::new(&c), c.len()) > 0.5);
+}
+
 #[bench]
 pub fn sum_three_items(b: &mut Bencher) {
     b.iter(|| {
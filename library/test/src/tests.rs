@@ -3,7 +3,7 @@
 use crate::{
     bench::Bencher,
     console::OutputLocation,
-    formatters::PrettyFormatter,
+    formatters::{GithubActionsFormatter, OutputFormatter, PrettyFormatter, TapFormatter},
     options::OutputFormat,
     test::{
         filter_tests,
@@ -27,8 +27,9 @@
     },
     time::{TestTimeOptions, TimeThreshold},
 };
+use std::process::{Command, Stdio};
 use std::sync::mpsc::channel;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 impl TestOpts {
     fn new() -> TestOpts {
@@ -47,9 +48,14 @@ fn new() -> TestOpts {
             format: OutputFormat::Pretty,
             shuffle: false,
             shuffle_seed: None,
+            shard: None,
+            bench_save: None,
+            bench_baseline: None,
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            retries: 0,
             options: Options::new(),
         }
     }
@@ -63,6 +69,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 ignore: true,
                 #[cfg(not(bootstrap))]
                 ignore_message: None,
+                #[cfg(not(bootstrap))]
+                retries: None,
                 should_panic: ShouldPanic::No,
                 compile_fail: false,
                 no_run: false,
@@ -76,6 +84,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 ignore: false,
                 #[cfg(not(bootstrap))]
                 ignore_message: None,
+                #[cfg(not(bootstrap))]
+                retries: None,
                 should_panic: ShouldPanic::No,
                 compile_fail: false,
                 no_run: false,
@@ -97,6 +107,8 @@ fn f() {
             ignore: true,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::No,
             compile_fail: false,
             no_run: false,
@@ -119,6 +131,8 @@ fn f() {}
             ignore: true,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::No,
             compile_fail: false,
             no_run: false,
@@ -145,6 +159,8 @@ fn f() {
             ignore: false,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::Yes,
             compile_fail: false,
             no_run: false,
@@ -171,6 +187,8 @@ fn f() {
             ignore: false,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::YesWithMessage("error message"),
             compile_fail: false,
             no_run: false,
@@ -202,6 +220,8 @@ fn f() {
             ignore: false,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::YesWithMessage(expected),
             compile_fail: false,
             no_run: false,
@@ -237,6 +257,8 @@ fn f() {
             ignore: false,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::YesWithMessage(expected),
             compile_fail: false,
             no_run: false,
@@ -264,6 +286,8 @@ fn f() {}
                 ignore: false,
                 #[cfg(not(bootstrap))]
                 ignore_message: None,
+                #[cfg(not(bootstrap))]
+                retries: None,
                 should_panic,
                 compile_fail: false,
                 no_run: false,
@@ -299,6 +323,8 @@ fn f() {}
             ignore: false,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::No,
             compile_fail: false,
             no_run: false,
@@ -335,6 +361,8 @@ fn f() {}
             ignore: false,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::No,
             compile_fail: false,
             no_run: false,
@@ -375,6 +403,8 @@ fn typed_test_desc(test_type: TestType) -> TestDesc {
         ignore: false,
         #[cfg(not(bootstrap))]
         ignore_message: None,
+        #[cfg(not(bootstrap))]
+        retries: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -434,6 +464,85 @@ fn parse_show_output_flag() {
     assert!(opts.options.display_output);
 }
 
+#[test]
+fn parse_test_timeout_option() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout=30".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(30)));
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout=0".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args = vec!["progname".to_string(), "--test-timeout=30".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+#[cfg(unix)]
+fn hung_test_process_is_killed() {
+    // The background `sleep` keeps the pipes open after the shell is killed.
+    let child = Command::new("sh")
+        .args(["-c", "echo started; sleep 10 & sleep 10"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let start = Instant::now();
+    let (status, stdout, _) = wait_with_timeout(child, Some(Duration::from_millis(500))).unwrap();
+    assert!(status.is_none());
+    assert_eq!(stdout, b"started\n");
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--shard-count=4".to_string(),
+        "--shard-index=3".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.shard, Some(Shard { index: 3, count: 4 }));
+
+    for invalid in [&["--shard-count=4"][..], &["--shard-count=4", "--shard-index=4"]] {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(invalid.iter().map(|arg| arg.to_string()));
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+}
+
+#[test]
+fn parse_bench_baseline_options() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--bench-save=new".to_string(),
+        "--bench-baseline=main".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.bench_save.as_deref(), Some("new"));
+    assert_eq!(opts.bench_baseline.as_deref(), Some("main"));
+
+    let args = vec!["progname".to_string(), "--bench-save=new".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--bench-save=../x".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
 #[test]
 fn parse_include_ignored_flag() {
     let args = vec!["progname".to_string(), "filter".to_string(), "--include-ignored".to_string()];
@@ -488,6 +597,8 @@ pub fn exclude_should_panic_option() {
             ignore: false,
             #[cfg(not(bootstrap))]
             ignore_message: None,
+            #[cfg(not(bootstrap))]
+            retries: None,
             should_panic: ShouldPanic::Yes,
             compile_fail: false,
             no_run: false,
@@ -502,6 +613,52 @@ pub fn exclude_should_panic_option() {
     assert!(filtered.iter().all(|test| test.desc.should_panic == ShouldPanic::No));
 }
 
+#[test]
+pub fn failed_tests_are_retried() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FLAKY_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+    fn flaky() {
+        // Fails on the first two attempts.
+        assert!(FLAKY_ATTEMPTS.fetch_add(1, Ordering::SeqCst) >= 2);
+    }
+    fn failing() {
+        panic!();
+    }
+
+    for test_threads in [Some(1), None] {
+        FLAKY_ATTEMPTS.store(0, Ordering::SeqCst);
+        let tests = [("flaky", flaky as fn()), ("failing", failing)]
+            .into_iter()
+            .map(|(name, testfn)| TestDescAndFn {
+                desc: TestDesc {
+                    name: StaticTestName(name),
+                    ..typed_test_desc(TestType::UnitTest)
+                },
+                testfn: StaticTestFn(testfn),
+            })
+            .collect();
+
+        let mut opts = TestOpts::new();
+        opts.run_tests = true;
+        opts.test_threads = test_threads;
+        opts.retries = 3;
+
+        let mut results = Vec::new();
+        run_tests(&opts, tests, |event| {
+            if let TestEvent::TeResult(completed_test) = event {
+                results.push((completed_test.desc.name.to_string(), completed_test.result));
+            }
+            Ok(())
+        })
+        .unwrap();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(results, [("failing".to_string(), TrFailed), ("flaky".to_string(), TrFlaky(2))]);
+        assert_eq!(FLAKY_ATTEMPTS.load(Ordering::SeqCst), 3);
+    }
+}
+
 #[test]
 pub fn exact_filter_match() {
     fn tests() -> Vec<TestDescAndFn> {
@@ -513,6 +670,8 @@ fn tests() -> Vec<TestDescAndFn> {
                     ignore: false,
                     #[cfg(not(bootstrap))]
                     ignore_message: None,
+                    #[cfg(not(bootstrap))]
+                    retries: None,
                     should_panic: ShouldPanic::No,
                     compile_fail: false,
                     no_run: false,
@@ -603,6 +762,8 @@ fn testfn() {}
                 ignore: false,
                 #[cfg(not(bootstrap))]
                 ignore_message: None,
+                #[cfg(not(bootstrap))]
+                retries: None,
                 should_panic: ShouldPanic::No,
                 compile_fail: false,
                 no_run: false,
@@ -642,6 +803,35 @@ pub fn sort_tests() {
     }
 }
 
+#[test]
+pub fn shard_tests() {
+    let mut opts = TestOpts::new();
+    opts.run_tests = true;
+    let all_tests: Vec<String> =
+        filter_tests(&opts, sample_tests()).iter().map(|t| t.desc.name.to_string()).collect();
+
+    let mut sharded_tests = Vec::new();
+    for index in 0..3 {
+        opts.shard = Some(Shard { index, count: 3 });
+        let shard: Vec<String> =
+            filter_tests(&opts, sample_tests()).iter().map(|t| t.desc.name.to_string()).collect();
+
+        // Shards are balanced and keep the alphabetical order.
+        assert!(shard.len() == 3 || shard.len() == 4);
+        assert!(shard.windows(2).all(|w| w[0] < w[1]));
+        // Sharding is deterministic.
+        let again: Vec<String> =
+            filter_tests(&opts, sample_tests()).iter().map(|t| t.desc.name.to_string()).collect();
+        assert_eq!(shard, again);
+
+        sharded_tests.extend(shard);
+    }
+
+    // Every test is in exactly one shard.
+    sharded_tests.sort();
+    assert_eq!(sharded_tests, all_tests);
+}
+
 #[test]
 pub fn shuffle_tests() {
     let mut opts = TestOpts::new();
@@ -755,6 +945,8 @@ fn f(_: &mut Bencher) {}
         ignore: false,
         #[cfg(not(bootstrap))]
         ignore_message: None,
+        #[cfg(not(bootstrap))]
+        retries: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -778,6 +970,8 @@ fn f(b: &mut Bencher) {
         ignore: false,
         #[cfg(not(bootstrap))]
         ignore_message: None,
+        #[cfg(not(bootstrap))]
+        retries: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -795,6 +989,8 @@ fn should_sort_failures_before_printing_them() {
         ignore: false,
         #[cfg(not(bootstrap))]
         ignore_message: None,
+        #[cfg(not(bootstrap))]
+        retries: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -806,6 +1002,8 @@ fn should_sort_failures_before_printing_them() {
         ignore: false,
         #[cfg(not(bootstrap))]
         ignore_message: None,
+        #[cfg(not(bootstrap))]
+        retries: None,
         should_panic: ShouldPanic::No,
         compile_fail: false,
         no_run: false,
@@ -818,6 +1016,7 @@ fn should_sort_failures_before_printing_them() {
         log_out: None,
         total: 0,
         passed: 0,
+        flaky: 0,
         failed: 0,
         ignored: 0,
         filtered_out: 0,
@@ -828,6 +1027,9 @@ fn should_sort_failures_before_printing_them() {
         options: Options::new(),
         not_failures: Vec::new(),
         time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
+        bench_baseline: None,
+        bench_results: Vec::new(),
     };
 
     out.write_failures(&st).unwrap();
@@ -840,3 +1042,101 @@ fn should_sort_failures_before_printing_them() {
     let bpos = s.find("b").unwrap();
     assert!(apos < bpos);
 }
+
+fn formatter_test_state() -> console::ConsoleTestState {
+    console::ConsoleTestState {
+        log_out: None,
+        total: 3,
+        passed: 1,
+        flaky: 0,
+        failed: 1,
+        ignored: 1,
+        filtered_out: 0,
+        measured: 0,
+        exec_time: None,
+        metrics: MetricMap::new(),
+        failures: Vec::new(),
+        options: Options::new(),
+        not_failures: Vec::new(),
+        time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
+        bench_baseline: None,
+        bench_results: Vec::new(),
+    }
+}
+
+#[test]
+fn tap_formatter_output() {
+    let ok = typed_test_desc(TestType::UnitTest);
+    let mut should_panic = typed_test_desc(TestType::UnitTest);
+    should_panic.name = StaticTestName("panics #1");
+    should_panic.should_panic = ShouldPanic::Yes;
+    let mut ignored = typed_test_desc(TestType::UnitTest);
+    ignored.name = StaticTestName("ignored");
+    ignored.ignore = true;
+
+    let st = formatter_test_state();
+    let mut out = TapFormatter::new(OutputLocation::Raw(Vec::new()));
+    out.write_run_start(3, None).unwrap();
+    out.write_result(&ok, &TrOk, None, b"", &st).unwrap();
+    let failed = TrFailedMsg("test did not panic as expected".to_string());
+    out.write_result(&should_panic, &failed, None, b"some output\n", &st).unwrap();
+    out.write_result(&ignored, &TrIgnored, None, b"", &st).unwrap();
+    out.write_run_finish(&st).unwrap();
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]).into_owned(),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    assert_eq!(
+        s,
+        "TAP version 14
+1..3
+ok 1 - whatever
+    # Subtest: panics \\#1
+    1..1
+    not ok 1 - should panic
+not ok 2 - panics \\#1
+  ---
+  message: |
+    test did not panic as expected
+  stdout: |
+    some output
+  ...
+ok 3 - ignored # SKIP
+# passed: 1
+# failed: 1
+# ignored: 1
+# measured: 0
+# filtered out: 0
+"
+    );
+}
+
+#[test]
+fn github_actions_formatter_output() {
+    let mut doctest = typed_test_desc(TestType::DocTest);
+    doctest.name = StaticTestName("src/lib.rs - foo::bar (line 12)");
+    let unit = typed_test_desc(TestType::UnitTest);
+
+    let st = formatter_test_state();
+    let mut out = GithubActionsFormatter::new(OutputLocation::Raw(Vec::new()));
+    out.write_result(&doctest, &TrFailed, None, b"line 1\nline 2: 100%\n", &st).unwrap();
+    out.write_result(&unit, &TrFailedMsg("boom".to_string()), None, b"", &st).unwrap();
+    out.write_result(&unit, &TrOk, None, b"", &st).unwrap();
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]).into_owned(),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    assert_eq!(
+        s,
+        "test src/lib.rs - foo::bar (line 12) ... FAILED
+::error file=src/lib.rs,line=12,title=test failed%3A src/lib.rs - foo%3A%3Abar (line 12)::\
+line 1%0Aline 2: 100%25
+test whatever ... FAILED
+::error title=test failed%3A whatever::boom
+test whatever ... ok
+"
+    );
+}
@@ -119,6 +119,8 @@ pub struct TestDesc {
     pub ignore: bool,
     #[cfg(not(bootstrap))]
     pub ignore_message: Option<&'static str>,
+    #[cfg(not(bootstrap))]
+    pub retries: Option<usize>,
     pub should_panic: options::ShouldPanic,
     pub compile_fail: bool,
     pub no_run: bool,
@@ -139,6 +141,16 @@ pub fn padded_name(&self, column_count: usize, align: NamePadding) -> String {
         }
     }
 
+    /// Returns how many times the test is re-run after failing: the value of its
+    /// `#[retries]` attribute if there is one, `default` otherwise.
+    pub fn max_retries(&self, default: usize) -> usize {
+        #[cfg(not(bootstrap))]
+        if let Some(retries) = self.retries {
+            return retries;
+        }
+        default
+    }
+
     /// Returns None for ignored test or that that are just run, otherwise give a description of the type of test.
     /// Descriptions include "should panic", "compile fail" and "compile".
     pub fn test_mode(&self) -> Option<&'static str> {
@@ -32,6 +32,8 @@
 crate enum OutputFormat {
     Json,
     Html,
+    Markdown,
+    Graph,
 }
 
 impl Default for OutputFormat {
@@ -53,11 +55,22 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "json" => Ok(OutputFormat::Json),
             "html" => Ok(OutputFormat::Html),
+            "markdown" => Ok(OutputFormat::Markdown),
+            "graph" => Ok(OutputFormat::Graph),
             _ => Err(format!("unknown output format `{}`", value)),
         }
     }
 }
 
+/// Options for the results of `--show-coverage`.
+#[derive(Clone, Copy, Debug, Default)]
+crate struct CoverageOptions {
+    /// Whether to list the items that are missing documentation or examples.
+    crate details: bool,
+    /// The percentage of documented items below which rustdoc fails.
+    crate threshold: Option<f64>,
+}
+
 /// Configuration options for rustdoc.
 #[derive(Clone)]
 crate struct Options {
@@ -114,6 +127,15 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
     /// Optional path to persist the doctest executables to, defaults to a
     /// temporary directory if not set.
     crate persist_doctests: Option<PathBuf>,
+    /// Optional directory in which to record the doctests that passed, to skip them in later
+    /// runs as long as they haven't changed.
+    crate doctest_cache_dir: Option<PathBuf>,
+    /// The environment variables which can change the outcome of doctests, besides `RUSTFLAGS`
+    /// and `RUSTDOCFLAGS`, to include in the keys of the doctest cache.
+    crate doctest_cache_env: Vec<String>,
+    /// The files and directories which can change the outcome of doctests, besides the crates
+    /// they link to, to include in the keys of the doctest cache.
+    crate doctest_cache_paths: Vec<PathBuf>,
     /// Runtool to run doctests with
     crate runtool: Option<String>,
     /// Arguments to pass to the runtool
@@ -124,6 +146,8 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
     crate enable_per_target_ignores: bool,
     /// Do not run doctests, compile them if should_test is active.
     crate no_run: bool,
+    /// Compile the doctests that allow it together, instead of one by one.
+    crate merge_doctests: bool,
 
     /// The path to a rustc-like binary to build tests with. If not set, we
     /// default to loading from `$sysroot/bin/rustc`.
@@ -133,6 +157,8 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
     /// Whether to run the `calculate-doc-coverage` pass, which counts the number of public items
     /// with and without documentation.
     crate show_coverage: bool,
+    /// What else to do with the results of `--show-coverage`.
+    crate coverage_options: CoverageOptions,
 
     // Options that alter generated documentation pages
     /// Crate version to note on the sidebar of generated docs.
@@ -146,6 +172,9 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
     /// If this option is set to `true`, rustdoc will only run checks and not generate
     /// documentation.
     crate run_check: bool,
+    /// The JSON output of an older version of the crate, to compare the JSON output in `input` to
+    /// with `--api-diff` instead of documenting a crate.
+    crate api_diff: Option<PathBuf>,
     /// Whether doctests should emit unused externs
     crate json_unused_externs: bool,
     /// Whether to skip capturing stdout and stderr of tests.
@@ -187,14 +216,20 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             .field("test_args", &self.test_args)
             .field("test_run_directory", &self.test_run_directory)
             .field("persist_doctests", &self.persist_doctests)
+            .field("doctest_cache_dir", &self.doctest_cache_dir)
+            .field("doctest_cache_env", &self.doctest_cache_env)
+            .field("doctest_cache_paths", &self.doctest_cache_paths)
             .field("show_coverage", &self.show_coverage)
+            .field("coverage_options", &self.coverage_options)
             .field("crate_version", &self.crate_version)
             .field("render_options", &self.render_options)
             .field("runtool", &self.runtool)
             .field("runtool_args", &self.runtool_args)
             .field("enable-per-target-ignores", &self.enable_per_target_ignores)
             .field("run_check", &self.run_check)
+            .field("api_diff", &self.api_diff)
             .field("no_run", &self.no_run)
+            .field("merge_doctests", &self.merge_doctests)
             .field("nocapture", &self.nocapture)
             .field("scrape_examples_options", &self.scrape_examples_options)
             .finish()
@@ -268,6 +303,11 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     crate generate_redirect_map: bool,
     /// Show the memory layout of types in the docs.
     crate show_type_layout: bool,
+    /// Generate an index of the words in the documentation of items, for searching by them.
+    crate full_text_search: bool,
+    /// The version of the documentation, if several versions are documented in subdirectories of
+    /// the same site. `output` is then the subdirectory of this version.
+    crate doc_version: Option<String>,
     crate unstable_features: rustc_feature::UnstableFeatures,
     crate emit: Vec<EmitType>,
     /// If `true`, HTML source pages will generate links for items to their definition.
@@ -490,6 +530,7 @@ fn println_condition(condition: Condition) {
 
         let should_test = matches.opt_present("test");
         let no_run = matches.opt_present("no-run");
+        let merge_doctests = matches.opt_present("merge-doctests");
 
         if !should_test && no_run {
             diag.err("the `--test` flag must be passed to enable `--no-run`");
@@ -597,6 +638,27 @@ fn println_condition(condition: Condition) {
         let target = parse_target_triple(matches, error_format);
 
         let show_coverage = matches.opt_present("show-coverage");
+        let coverage_details = matches.opt_present("coverage-details");
+        let coverage_threshold = match matches.opt_str("coverage-threshold") {
+            Some(s) => match s.parse::<f64>() {
+                Ok(threshold) if (0.0..=100.0).contains(&threshold) => Some(threshold),
+                _ => {
+                    diag.struct_err("--coverage-threshold must be a percentage between 0 and 100")
+                        .emit();
+                    return Err(1);
+                }
+            },
+            None => None,
+        };
+        if (coverage_details || coverage_threshold.is_some()) && !show_coverage {
+            diag.struct_err(
+                "--coverage-details and --coverage-threshold can only be used with --show-coverage",
+            )
+            .emit();
+            return Err(1);
+        }
+        let coverage_options =
+            CoverageOptions { details: coverage_details, threshold: coverage_threshold };
 
         let crate_types = match parse_crate_types_from_list(matches.opt_strs("crate-type")) {
             Ok(types) => types,
@@ -610,9 +672,10 @@ fn println_condition(condition: Condition) {
             Some(s) => match OutputFormat::try_from(s.as_str()) {
                 Ok(out_fmt) => {
                     if !out_fmt.is_json() && show_coverage {
-                        diag.struct_err(
-                            "html output format isn't supported for the --show-coverage option",
-                        )
+                        diag.struct_err(&format!(
+                            "{} output format isn't supported for the --show-coverage option",
+                            s
+                        ))
                         .emit();
                         return Err(1);
                     }
@@ -640,6 +703,10 @@ fn println_condition(condition: Condition) {
         let static_root_path = matches.opt_str("static-root-path");
         let test_run_directory = matches.opt_str("test-run-directory").map(PathBuf::from);
         let persist_doctests = matches.opt_str("persist-doctests").map(PathBuf::from);
+        let doctest_cache_dir = matches.opt_str("doctest-cache-dir").map(PathBuf::from);
+        let doctest_cache_env = matches.opt_strs("doctest-cache-env");
+        let doctest_cache_paths =
+            matches.opt_strs("doctest-cache-path").into_iter().map(PathBuf::from).collect();
         let test_builder = matches.opt_str("test-builder").map(PathBuf::from);
         let codegen_options_strs = matches.opt_strs("C");
         let debugging_opts_strs = matches.opt_strs("Z");
@@ -651,8 +718,11 @@ fn println_condition(condition: Condition) {
         let document_private = matches.opt_present("document-private-items");
         let document_hidden = matches.opt_present("document-hidden-items");
         let run_check = matches.opt_present("check");
+        let api_diff = matches.opt_str("api-diff").map(PathBuf::from);
         let generate_redirect_map = matches.opt_present("generate-redirect-map");
         let show_type_layout = matches.opt_present("show-type-layout");
+        let full_text_search = matches.opt_present("full-text-search");
+        let doc_version = matches.opt_str("doc-version");
         let nocapture = matches.opt_present("nocapture");
         let generate_link_to_definition = matches.opt_present("generate-link-to-definition");
         let extern_html_root_takes_precedence =
@@ -666,6 +736,28 @@ fn println_condition(condition: Condition) {
             return Err(1);
         }
 
+        let mut output = output;
+        if let Some(ref version) = doc_version {
+            if show_coverage || output_format != OutputFormat::Html {
+                diag.struct_err("--doc-version option can only be used with HTML output format")
+                    .emit();
+                return Err(1);
+            }
+            if version.is_empty()
+                || version == "."
+                || version == ".."
+                || version.contains(|c| c == '/' || c == '\\')
+            {
+                diag.struct_err(&format!(
+                    "--doc-version must be usable as a directory name, not `{}`",
+                    version
+                ))
+                .emit();
+                return Err(1);
+            }
+            output.push(version);
+        }
+
         let scrape_examples_options = ScrapeExamplesOptions::new(&matches, &diag)?;
         let with_examples = matches.opt_strs("with-examples");
         let call_locations = crate::scrape_examples::load_call_locations(with_examples, &diag)?;
@@ -695,15 +787,21 @@ fn println_condition(condition: Condition) {
             should_test,
             test_args,
             show_coverage,
+            coverage_options,
             crate_version,
             test_run_directory,
             persist_doctests,
+            doctest_cache_dir,
+            doctest_cache_env,
+            doctest_cache_paths,
             runtool,
             runtool_args,
             enable_per_target_ignores,
             test_builder,
             run_check,
+            api_diff,
             no_run,
+            merge_doctests,
             nocapture,
             render_options: RenderOptions {
                 output,
@@ -728,6 +826,8 @@ fn println_condition(condition: Condition) {
                 document_hidden,
                 generate_redirect_map,
                 show_type_layout,
+                full_text_search,
+                doc_version,
                 unstable_features: rustc_feature::UnstableFeatures::from_environment(
                     crate_name.as_deref(),
                 ),
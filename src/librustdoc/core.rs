@@ -1,7 +1,9 @@
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync::{self, Lrc};
 use rustc_errors::emitter::{Emitter, EmitterWriter};
+use rustc_errors::json::lsp::LspEmitter;
 use rustc_errors::json::JsonEmitter;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_feature::UnstableFeatures;
 use rustc_hir::def::Res;
 use rustc_hir::def_id::{DefId, DefIdMap, LocalDefId};
@@ -26,7 +28,7 @@
 
 use crate::clean::inline::build_external_trait;
 use crate::clean::{self, ItemId, TraitWithExtraInfo};
-use crate::config::{Options as RustdocOptions, OutputFormat, RenderOptions};
+use crate::config::{CoverageOptions, Options as RustdocOptions, OutputFormat, RenderOptions};
 use crate::formats::cache::Cache;
 use crate::passes::{self, Condition::*};
 
@@ -76,6 +78,8 @@
     crate inlined: FxHashSet<ItemId>,
     /// Used by `calculate_doc_coverage`.
     crate output_format: OutputFormat,
+    /// Used by `calculate_doc_coverage`.
+    crate coverage_options: CoverageOptions,
 }
 
 impl<'tcx> DocContext<'tcx> {
@@ -136,8 +140,8 @@ impl<'tcx> DocContext<'tcx> {
 
 /// Creates a new diagnostic `Handler` that can be used to emit warnings and errors.
 ///
-/// If the given `error_format` is `ErrorOutputType::Json` and no `SourceMap` is given, a new one
-/// will be created for the handler.
+/// If the given `error_format` is `ErrorOutputType::Json`, `ErrorOutputType::Sarif` or
+/// `ErrorOutputType::Lsp` and no `SourceMap` is given, a new one will be created for the handler.
 crate fn new_handler(
     error_format: ErrorOutputType,
     source_map: Option<Lrc<source_map::SourceMap>>,
@@ -174,6 +178,18 @@ impl<'tcx> DocContext<'tcx> {
                 .ui_testing(debugging_opts.ui_testing),
             )
         }
+        ErrorOutputType::Sarif => {
+            let source_map = source_map.unwrap_or_else(|| {
+                Lrc::new(source_map::SourceMap::new(source_map::FilePathMapping::empty()))
+            });
+            Box::new(SarifEmitter::stderr(None, source_map))
+        }
+        ErrorOutputType::Lsp => {
+            let source_map = source_map.unwrap_or_else(|| {
+                Lrc::new(source_map::SourceMap::new(source_map::FilePathMapping::empty()))
+            });
+            Box::new(LspEmitter::stderr(source_map))
+        }
     };
 
     rustc_errors::Handler::with_emitter_and_flags(
@@ -305,6 +321,7 @@ impl<'tcx> DocContext<'tcx> {
     show_coverage: bool,
     render_options: RenderOptions,
     output_format: OutputFormat,
+    coverage_options: CoverageOptions,
 ) -> (clean::Crate, RenderOptions, Cache) {
     // Certain queries assume that some checks were run elsewhere
     // (see https://github.com/rust-lang/rust/pull/73566#issuecomment-656954425),
@@ -356,6 +373,7 @@ impl<'tcx> DocContext<'tcx> {
         cache: Cache::new(access_levels, render_options.document_private),
         inlined: FxHashSet::default(),
         output_format,
+        coverage_options,
         render_options,
     };
 
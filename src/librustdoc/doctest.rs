@@ -23,18 +23,23 @@
 use std::env;
 use std::io::{self, Write};
 use std::panic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{self, Command, Stdio};
 use std::str;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
+use self::cache::DoctestCache;
+use self::merged::MergedDoctests;
 use crate::clean::{types::AttributesExt, Attributes};
 use crate::config::Options as RustdocOptions;
 use crate::html::markdown::{self, ErrorCodes, Ignore, LangString};
 use crate::lint::init_lints;
 use crate::passes::span_of_attrs;
 
+mod cache;
+mod merged;
+
 /// Options that apply to all doctests in a crate or Markdown file (for `rustdoc foo.md`).
 #[derive(Clone, Default)]
 crate struct GlobalTestOptions {
@@ -154,17 +159,22 @@
 
             let unused_extern_reports = collector.unused_extern_reports.clone();
             let compiling_test_count = collector.compiling_test_count.load(Ordering::SeqCst);
-            let ret: Result<_, ErrorGuaranteed> =
-                Ok((collector.tests, unused_extern_reports, compiling_test_count));
+            let cached_test_count = collector.cached_test_count;
+            let ret: Result<_, ErrorGuaranteed> = Ok((
+                collector.tests,
+                unused_extern_reports,
+                compiling_test_count,
+                cached_test_count,
+            ));
             ret
         })
     });
-    let (tests, unused_extern_reports, compiling_test_count) = match res {
+    let (tests, unused_extern_reports, compiling_test_count, cached_test_count) = match res {
         Ok(res) => res,
         Err(ErrorGuaranteed) => return Err(ErrorGuaranteed),
     };
 
-    run_tests(test_args, nocapture, tests);
+    run_tests(test_args, nocapture, tests, cached_test_count);
 
     // Collect and warn about unused externs, but only if we've gotten
     // reports for each doctest
@@ -207,11 +217,24 @@
     Ok(())
 }
 
-crate fn run_tests(mut test_args: Vec<String>, nocapture: bool, tests: Vec<test::TestDescAndFn>) {
+crate fn run_tests(
+    mut test_args: Vec<String>,
+    nocapture: bool,
+    tests: Vec<test::TestDescAndFn>,
+    cached_test_count: usize,
+) {
     test_args.insert(0, "rustdoctest".to_string());
     if nocapture {
         test_args.push("--nocapture".to_string());
     }
+    // This goes to stderr, so that it doesn't get in the way of `--format json`.
+    if cached_test_count > 0 {
+        let doctests = if cached_test_count == 1 { "doctest" } else { "doctests" };
+        eprintln!(
+            "note: {} {} passed in an earlier run with the same inputs, and won't be run again",
+            cached_test_count, doctests,
+        );
+    }
     test::test_main(&test_args, tests, None);
 }
 
@@ -282,7 +305,7 @@ fn path(&self) -> &std::path::Path {
 // and cargo.
 // We could unify this struct the one in rustc but they have different
 // ownership semantics, so doing so would create wasteful allocations.
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 struct UnusedExterns {
     /// Lint level of the unused_crate_dependencies lint
     lint_level: String,
@@ -290,28 +313,16 @@ struct UnusedExterns {
     unused_extern_names: Vec<String>,
 }
 
-fn run_test(
-    test: &str,
-    crate_name: &str,
-    line: usize,
-    rustdoc_options: RustdocOptions,
-    mut lang_string: LangString,
+/// Creates the command that compiles a doctest from its standard input, apart from the path of
+/// its output.
+fn make_compiler_command(
+    rustdoc_options: &RustdocOptions,
+    lang_string: &LangString,
     no_run: bool,
-    runtool: Option<String>,
-    runtool_args: Vec<String>,
-    target: TargetTriple,
-    opts: &GlobalTestOptions,
+    target: &TargetTriple,
     edition: Edition,
-    outdir: DirState,
-    path: PathBuf,
-    test_id: &str,
-    report_unused_externs: impl Fn(UnusedExterns),
-) -> Result<(), TestFailure> {
-    let (test, line_offset, supports_color) =
-        make_test(test, Some(crate_name), lang_string.test_harness, opts, edition, Some(test_id));
-
-    let output_file = outdir.path().join("rust_out");
-
+    supports_color: bool,
+) -> Command {
     let rustc_binary = rustdoc_options
         .test_builder
         .as_deref()
@@ -327,13 +338,10 @@ fn run_test(
             compiler.arg("--check-cfg").arg(&check_cfg);
         }
     }
-    if let Some(sysroot) = rustdoc_options.maybe_sysroot {
+    if let Some(sysroot) = &rustdoc_options.maybe_sysroot {
         compiler.arg("--sysroot").arg(sysroot);
     }
     compiler.arg("--edition").arg(&edition.to_string());
-    compiler.env("UNSTABLE_RUSTDOC_TEST_PATH", path);
-    compiler.env("UNSTABLE_RUSTDOC_TEST_LINE", format!("{}", line as isize - line_offset as isize));
-    compiler.arg("-o").arg(&output_file);
     if lang_string.test_harness {
         compiler.arg("--test");
     }
@@ -360,10 +368,8 @@ fn run_test(
         compiler.arg("--emit=metadata");
     }
     compiler.arg("--target").arg(match target {
-        TargetTriple::TargetTriple(s) => s,
-        TargetTriple::TargetPath(path) => {
-            path.to_str().expect("target path must be valid unicode").to_string()
-        }
+        TargetTriple::TargetTriple(s) => s.as_str(),
+        TargetTriple::TargetPath(path) => path.to_str().expect("target path must be valid unicode"),
     });
     if let ErrorOutputType::HumanReadable(kind) = rustdoc_options.error_format {
         let (short, color_config) = kind.unzip();
@@ -385,6 +391,42 @@ fn run_test(
         }
     }
 
+    compiler
+}
+
+fn run_test(
+    test: &str,
+    crate_name: &str,
+    line: usize,
+    rustdoc_options: RustdocOptions,
+    mut lang_string: LangString,
+    no_run: bool,
+    runtool: Option<String>,
+    runtool_args: Vec<String>,
+    target: TargetTriple,
+    opts: &GlobalTestOptions,
+    edition: Edition,
+    outdir: DirState,
+    path: PathBuf,
+    test_id: &str,
+    report_unused_externs: impl Fn(UnusedExterns),
+) -> Result<(), TestFailure> {
+    let (test, line_offset, supports_color) =
+        make_test(test, Some(crate_name), lang_string.test_harness, opts, edition, Some(test_id));
+
+    let output_file = outdir.path().join("rust_out");
+
+    let mut compiler = make_compiler_command(
+        &rustdoc_options,
+        &lang_string,
+        no_run,
+        &target,
+        edition,
+        supports_color,
+    );
+    compiler.env("UNSTABLE_RUSTDOC_TEST_PATH", path);
+    compiler.env("UNSTABLE_RUSTDOC_TEST_LINE", format!("{}", line as isize - line_offset as isize));
+    compiler.arg("-o").arg(&output_file);
     compiler.arg("-");
     compiler.stdin(Stdio::piped());
     compiler.stderr(Stdio::piped());
@@ -449,17 +491,30 @@ fn drop(&mut self) {
         return Ok(());
     }
 
+    run_binary(&output_file, &[], runtool, runtool_args, &rustdoc_options, &lang_string)
+}
+
+/// Runs a compiled doctest with `args`, and checks that it only failed if it's `should_panic`.
+fn run_binary(
+    binary: &Path,
+    args: &[String],
+    runtool: Option<String>,
+    runtool_args: Vec<String>,
+    rustdoc_options: &RustdocOptions,
+    lang_string: &LangString,
+) -> Result<(), TestFailure> {
     // Run the code!
     let mut cmd;
 
     if let Some(tool) = runtool {
         cmd = Command::new(tool);
         cmd.args(runtool_args);
-        cmd.arg(output_file);
+        cmd.arg(binary);
     } else {
-        cmd = Command::new(output_file);
+        cmd = Command::new(binary);
     }
-    if let Some(run_directory) = rustdoc_options.test_run_directory {
+    cmd.args(args);
+    if let Some(run_directory) = &rustdoc_options.test_run_directory {
         cmd.current_dir(run_directory);
     }
 
@@ -496,6 +551,30 @@ fn drop(&mut self) {
     edition: Edition,
     test_id: Option<&str>,
 ) -> (String, usize, bool) {
+    let test = wrap_test(s, crate_name, dont_insert_main, opts, edition, test_id);
+    (test.source, test.line_offset, test.supports_color)
+}
+
+/// A test transformed by [`wrap_test`].
+crate struct WrappedTest {
+    crate source: String,
+    /// The number of lines before the test code begins.
+    crate line_offset: usize,
+    crate supports_color: bool,
+    /// Whether the test code was wrapped in a `fn main`, i.e. whether it could be parsed and
+    /// doesn't have a `fn main` of its own.
+    crate wrapped_in_main: bool,
+}
+
+/// Like [`make_test`], but also says whether the test code was wrapped in a `fn main`.
+crate fn wrap_test(
+    s: &str,
+    crate_name: Option<&str>,
+    dont_insert_main: bool,
+    opts: &GlobalTestOptions,
+    edition: Edition,
+    test_id: Option<&str>,
+) -> WrappedTest {
     let (crate_attrs, everything_else, crates) = partition_source(s);
     let everything_else = everything_else.trim();
     let mut line_offset = 0;
@@ -622,7 +701,12 @@ fn drop(&mut self) {
         Err(ErrorGuaranteed) => {
             // If the parser panicked due to a fatal error, pass the test code through unchanged.
             // The error will be reported during compilation.
-            return (s.to_owned(), 0, false);
+            return WrappedTest {
+                source: s.to_owned(),
+                line_offset: 0,
+                supports_color: false,
+                wrapped_in_main: false,
+            };
         }
     };
 
@@ -657,7 +741,8 @@ fn drop(&mut self) {
     }
 
     // FIXME: This code cannot yet handle no_std test cases yet
-    if dont_insert_main || already_has_main || prog.contains("![no_std]") {
+    let wrapped_in_main = !(dont_insert_main || already_has_main || prog.contains("![no_std]"));
+    if !wrapped_in_main {
         prog.push_str(everything_else);
     } else {
         let returns_result = everything_else.trim_end().ends_with("(())");
@@ -700,7 +785,7 @@ fn drop(&mut self) {
 
     debug!("final doctest:\n{}", prog);
 
-    (prog, line_offset, supports_color)
+    WrappedTest { source: prog, line_offset, supports_color, wrapped_in_main }
 }
 
 // FIXME(aburka): use a real parser to deal with multiline attributes
@@ -817,6 +902,12 @@ fn register_header(&mut self, _name: &str, _level: u32) {}
     visited_tests: FxHashMap<(String, usize), usize>,
     unused_extern_reports: Arc<Mutex<Vec<UnusedExterns>>>,
     compiling_test_count: AtomicUsize,
+    /// The doctests that are compiled together with `--merge-doctests`, by edition.
+    merged_doctests: FxHashMap<Edition, Arc<MergedDoctests>>,
+    /// The doctests that passed in earlier runs, with `--doctest-cache-dir`.
+    doctest_cache: Option<DoctestCache>,
+    /// The number of doctests which passed in earlier runs, and aren't run again.
+    crate cached_test_count: usize,
 }
 
 impl Collector {
@@ -829,6 +920,10 @@ impl Collector {
         filename: Option<PathBuf>,
         enable_per_target_ignores: bool,
     ) -> Collector {
+        let doctest_cache = rustdoc_options
+            .doctest_cache_dir
+            .clone()
+            .map(|dir| DoctestCache::new(dir, &rustdoc_options));
         Collector {
             tests: Vec::new(),
             names: Vec::new(),
@@ -843,6 +938,9 @@ impl Collector {
             visited_tests: FxHashMap::default(),
             unused_extern_reports: Default::default(),
             compiling_test_count: AtomicUsize::new(0),
+            merged_doctests: FxHashMap::default(),
+            doctest_cache,
+            cached_test_count: 0,
         }
     }
 
@@ -943,17 +1041,67 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
             )
         };
 
+        let ignore = match config.ignore {
+            Ignore::All => true,
+            Ignore::None => false,
+            Ignore::Some(ref ignores) => ignores.iter().any(|s| target_str.contains(s)),
+        };
+        let cache_entry = match &self.doctest_cache {
+            Some(cache) if !ignore => {
+                let (source, _, _) = make_test(
+                    &test,
+                    Some(&crate_name),
+                    config.test_harness,
+                    &opts,
+                    edition,
+                    Some(&test_id),
+                );
+                // Whether the output has colors doesn't change the outcome of the test.
+                let compiler = make_compiler_command(
+                    &rustdoc_options,
+                    &config,
+                    no_run,
+                    &target,
+                    edition,
+                    false,
+                );
+                let key = cache.key(&source, &compiler, &config, no_run, &rustdoc_options);
+                Some((cache.clone(), key))
+            }
+            _ => None,
+        };
+        // Doctests that passed before and haven't changed since don't need to be run again. They
+        // pass right away, under their usual names, so that filtering them works as before.
+        let cached = cache_entry.as_ref().map_or(false, |(cache, key)| cache.contains(key));
+        if cached {
+            self.cached_test_count += 1;
+        }
+        let merged = if rustdoc_options.merge_doctests && !ignore && !cached {
+            let wrapped =
+                wrap_test(&test, Some(&crate_name), false, &opts, edition, Some(&test_id));
+            if merged::is_mergeable(&test, &wrapped, &config, edition) {
+                let doctests = self
+                    .merged_doctests
+                    .entry(edition)
+                    .or_insert_with(|| Arc::new(MergedDoctests::new(edition)));
+                Some((doctests.clone(), doctests.add(wrapped, path.clone(), line)))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
         debug!("creating test {}: {}", name, test);
         self.tests.push(test::TestDescAndFn {
             desc: test::TestDesc {
                 name: test::DynTestName(name),
-                ignore: match config.ignore {
-                    Ignore::All => true,
-                    Ignore::None => false,
-                    Ignore::Some(ref ignores) => ignores.iter().any(|s| target_str.contains(s)),
-                },
+                ignore,
                 #[cfg(not(bootstrap))]
                 ignore_message: None,
+                // dynamically created tests can't be retried
+                #[cfg(not(bootstrap))]
+                retries: None,
                 // compiler failures are test failures
                 should_panic: test::ShouldPanic::No,
                 compile_fail: config.compile_fail,
@@ -961,26 +1109,48 @@ fn add_test(&mut self, test: String, config: LangString, line: usize) {
                 test_type: test::TestType::DocTest,
             },
             testfn: test::DynTestFn(box move || {
+                if cached {
+                    println!("this doctest passed in an earlier run with the same inputs");
+                    return;
+                }
                 let report_unused_externs = |uext| {
                     unused_externs.lock().unwrap().push(uext);
                 };
-                let res = run_test(
-                    &test,
-                    &crate_name,
-                    line,
-                    rustdoc_options,
-                    config,
-                    no_run,
-                    runtool,
-                    runtool_args,
-                    target,
-                    &opts,
-                    edition,
-                    outdir,
-                    path,
-                    &test_id,
-                    report_unused_externs,
-                );
+                let merged_res = merged.and_then(|(doctests, index)| {
+                    doctests.run_test(
+                        index,
+                        &rustdoc_options,
+                        &config,
+                        no_run,
+                        &target,
+                        &report_unused_externs,
+                    )
+                });
+                // Doctests whose merged crate doesn't compile are compiled on their own.
+                let res = merged_res.unwrap_or_else(|| {
+                    run_test(
+                        &test,
+                        &crate_name,
+                        line,
+                        rustdoc_options,
+                        config,
+                        no_run,
+                        runtool,
+                        runtool_args,
+                        target,
+                        &opts,
+                        edition,
+                        outdir,
+                        path,
+                        &test_id,
+                        report_unused_externs,
+                    )
+                });
+
+                if let (Ok(()), Some((cache, key))) = (&res, cache_entry) {
+                    // Failing to record the result only means that the test will be run again.
+                    let _ = cache.insert(&key);
+                }
 
                 if let Err(err) = res {
                     match err {
@@ -0,0 +1,159 @@
+//! Caching of doctest results, enabled with `--doctest-cache-dir`.
+//!
+//! Each doctest that passes is recorded in the cache directory as an empty file, named after a
+//! hash of the inputs which can change its outcome: its final source, the arguments it's compiled
+//! with, including `--cfg`, how it's run, `RUSTFLAGS` and `RUSTDOCFLAGS`, and the size and
+//! modification time of the compiler and of the crates passed with `--extern` or found in `-L`
+//! directories. Other environment variables and files are only included if they're given with
+//! `--doctest-cache-env` and `--doctest-cache-path`, since hashing all of them would change the
+//! keys on every run, e.g. in CI. When a later run finds a doctest with the same hash, it passes
+//! without being compiled and run again.
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::stable_hasher::StableHasher;
+
+use crate::config::Options as RustdocOptions;
+use crate::html::markdown::LangString;
+
+#[derive(Clone)]
+crate struct DoctestCache {
+    dir: PathBuf,
+    /// The hash of what can change the outcome of any doctest of this run, which is computed once
+    /// since it's the same for all of them.
+    environment: Fingerprint,
+}
+
+impl DoctestCache {
+    crate fn new(dir: PathBuf, rustdoc_options: &RustdocOptions) -> DoctestCache {
+        let mut hasher = StableHasher::new();
+        // Doctests are compiled and run with the environment of rustdoc, but most variables, like
+        // the ones with the ID of a CI job, don't change their outcome.
+        let vars = ["RUSTFLAGS", "RUSTDOCFLAGS"].into_iter().map(str::to_owned);
+        for var in vars.chain(rustdoc_options.doctest_cache_env.iter().cloned()) {
+            let value = env::var_os(&var);
+            (var, value).hash(&mut hasher);
+        }
+
+        // Crates in `-L` directories can be used without `--extern`.
+        for search_path in &rustdoc_options.libs {
+            for file in &search_path.files {
+                file.path.hash(&mut hasher);
+                hash_file(&mut hasher, &file.path);
+            }
+        }
+
+        // The directories rustdoc writes to are skipped, since recording a doctest would
+        // invalidate all the others. They are created first, so that creating them doesn't
+        // invalidate the doctests either.
+        let skip = [Some(&dir), rustdoc_options.persist_doctests.as_ref()];
+        let skip = skip
+            .into_iter()
+            .flatten()
+            .filter_map(|d| fs::create_dir_all(d).and_then(|()| d.canonicalize()).ok())
+            .collect::<Vec<_>>();
+        for path in &rustdoc_options.doctest_cache_paths {
+            path.hash(&mut hasher);
+            match path.canonicalize() {
+                Ok(path) if path.is_dir() => hash_dir(&mut hasher, &path, &skip),
+                _ => hash_file(&mut hasher, path),
+            }
+        }
+
+        DoctestCache { dir, environment: hasher.finish() }
+    }
+
+    /// Computes the key of a doctest whose final source is `source`, and which is compiled with
+    /// `compiler`.
+    crate fn key(
+        &self,
+        source: &str,
+        compiler: &Command,
+        lang_string: &LangString,
+        no_run: bool,
+        rustdoc_options: &RustdocOptions,
+    ) -> String {
+        let mut hasher = StableHasher::new();
+        rustc_interface::util::version_str().hash(&mut hasher);
+        self.environment.hash(&mut hasher);
+        source.hash(&mut hasher);
+
+        compiler.get_program().hash(&mut hasher);
+        hash_file(&mut hasher, Path::new(compiler.get_program()));
+        let mut args = compiler.get_args();
+        while let Some(arg) = args.next() {
+            arg.hash(&mut hasher);
+            // The crates a doctest depends on can be rebuilt at the same path.
+            if arg == "--extern" {
+                if let Some(path) = args.next().and_then(OsStr::to_str).and_then(extern_path) {
+                    path.hash(&mut hasher);
+                    hash_file(&mut hasher, Path::new(path));
+                }
+            }
+        }
+
+        // What's expected of the doctest, and how it's run.
+        (lang_string.should_panic, lang_string.compile_fail, &lang_string.error_codes, no_run)
+            .hash(&mut hasher);
+        (&rustdoc_options.runtool, &rustdoc_options.runtool_args).hash(&mut hasher);
+        rustdoc_options.test_run_directory.hash(&mut hasher);
+
+        hasher.finish::<Fingerprint>().to_hex()
+    }
+
+    /// Returns whether the doctest with the given key passed before.
+    crate fn contains(&self, key: &str) -> bool {
+        self.dir.join(key).exists()
+    }
+
+    /// Records that the doctest with the given key passed.
+    crate fn insert(&self, key: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(key), "")
+    }
+}
+
+/// Returns the path of an `--extern` argument, which looks like `name=path`.
+fn extern_path(arg: &str) -> Option<&str> {
+    arg.split_once('=').map(|(_, path)| path)
+}
+
+/// Hashes the size and modification time of the file at `path`, if it exists.
+fn hash_file(hasher: &mut StableHasher, path: &Path) {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return,
+    };
+    metadata.len().hash(hasher);
+    if let Ok(modified) = metadata.modified() {
+        modified.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0).hash(hasher);
+    }
+}
+
+/// Hashes the paths, sizes and modification times of the files in `dir` and its subdirectories,
+/// except for the directories in `skip`. Symbolic links to directories aren't followed.
+fn hash_dir(hasher: &mut StableHasher, dir: &Path, skip: &[PathBuf]) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut entries = entries.filter_map(Result::ok).collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if skip.contains(&path) {
+            continue;
+        }
+        path.hash(hasher);
+        if entry.file_type().map_or(false, |ty| ty.is_dir()) {
+            hash_dir(hasher, &path, skip);
+        } else {
+            hash_file(hasher, &path);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Merged doctests, enabled with `--merge-doctests`.
+//!
+//! Compiling each doctest as its own binary means linking once per doctest, which is most of the
+//! time it takes to run them. Instead, the doctests that don't need a crate of their own are put
+//! in modules of a single crate, which is compiled once with a libtest harness, the first time one
+//! of them is run. Each doctest then runs the harness with a filter that only selects it, so that
+//! it's still reported on its own.
+//!
+//! Doctests are merged per edition. If the merged crate doesn't compile, for example because two
+//! doctests implement the same trait for the same type, its doctests are compiled on their own
+//! instead.
+//!
+//! Each module is written to a file of its own, which is remapped to the file the doctest comes
+//! from, and is padded with empty lines so that the doctest starts on the line it starts on in
+//! that file. This way, the locations of panics point to the documentation like they do for
+//! doctests compiled on their own.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use rustc_span::edition::Edition;
+use rustc_target::spec::TargetTriple;
+use tempfile::Builder as TempFileBuilder;
+
+use super::{
+    make_compiler_command, partition_source, run_binary, DirState, TestFailure, UnusedExterns,
+    WrappedTest,
+};
+use crate::config::Options as RustdocOptions;
+use crate::html::markdown::LangString;
+
+/// Returns whether the doctest `test`, which `wrap_test` turned into `wrapped`, can be put in a
+/// module of the merged crate.
+crate fn is_mergeable(
+    test: &str,
+    wrapped: &WrappedTest,
+    config: &LangString,
+    edition: Edition,
+) -> bool {
+    let (crate_attrs, _, _) = partition_source(test);
+    // In the 2015 edition, `use` paths are relative to the crate root, which isn't the module of
+    // the doctest anymore.
+    edition >= Edition::Edition2018
+        && !config.compile_fail
+        && !config.test_harness
+        && !crate_attrs.lines().any(|line| line.trim_start().starts_with("#!["))
+        // The module has to have the `main` that `wrap_test` adds, which the test calls.
+        && wrapped.wrapped_in_main
+        // `#[macro_use] extern crate` only works at the root of a crate.
+        && !test.contains("#[macro_use]")
+}
+
+/// The doctests of a crate that are merged, for one edition.
+crate struct MergedDoctests {
+    edition: Edition,
+    /// Each doctest, along with the file it comes from and the line it starts after.
+    tests: Mutex<Vec<(WrappedTest, PathBuf, usize)>>,
+    /// The merged crate, once the first doctest has tried to compile it.
+    compiled: Mutex<Option<Option<Arc<Compiled>>>>,
+}
+
+struct Compiled {
+    outdir: DirState,
+    unused_externs: Option<UnusedExterns>,
+}
+
+impl MergedDoctests {
+    crate fn new(edition: Edition) -> MergedDoctests {
+        MergedDoctests { edition, tests: Mutex::new(Vec::new()), compiled: Mutex::new(None) }
+    }
+
+    /// Adds a doctest to the merged crate, and returns its index.
+    crate fn add(&self, test: WrappedTest, path: PathBuf, line: usize) -> usize {
+        let mut tests = self.tests.lock().unwrap();
+        tests.push((test, path, line));
+        tests.len() - 1
+    }
+
+    /// Runs the doctest at `index`, compiling the merged crate first if needed. Returns `None` if
+    /// the merged crate doesn't compile, in which case the doctest has to be compiled on its own.
+    crate fn run_test(
+        &self,
+        index: usize,
+        rustdoc_options: &RustdocOptions,
+        lang_string: &LangString,
+        no_run: bool,
+        target: &TargetTriple,
+        report_unused_externs: impl Fn(UnusedExterns),
+    ) -> Option<Result<(), TestFailure>> {
+        // The other doctests wait for the first one to compile the merged crate, but they're run
+        // in parallel afterwards.
+        let compiled = self
+            .compiled
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| self.compile(rustdoc_options, target).map(Arc::new))
+            .clone()?;
+        if let Some(unused_externs) = &compiled.unused_externs {
+            report_unused_externs(unused_externs.clone());
+        }
+        if no_run {
+            return Some(Ok(()));
+        }
+
+        let mut args = vec![format!("{}::run", module_name(index)), "--exact".to_owned()];
+        if rustdoc_options.nocapture {
+            args.push("--nocapture".to_owned());
+        }
+        Some(run_binary(
+            &compiled.outdir.path().join("rust_out"),
+            &args,
+            rustdoc_options.runtool.clone(),
+            rustdoc_options.runtool_args.clone(),
+            rustdoc_options,
+            lang_string,
+        ))
+    }
+
+    /// Writes the modules of the merged crate to `dir`, and returns the source of its root along
+    /// with the `--remap-path-prefix` arguments that map the modules to the files of their
+    /// doctests.
+    fn write_modules(&self, dir: &Path) -> Result<(String, Vec<String>), String> {
+        // The merged crate is read from stdin, so the paths of its modules have to be absolute.
+        let dir = env::current_dir().map_err(|e| e.to_string())?.join(dir);
+        let mut source = String::new();
+        let mut modules = String::new();
+        let mut remaps = Vec::new();
+        for (index, (test, path, line)) in self.tests.lock().unwrap().iter().enumerate() {
+            let mut lines = test.source.lines().peekable();
+            // The crate attributes are the same for all the doctests, since they can't have their
+            // own.
+            let mut crate_attrs = 0;
+            while let Some(attr) = lines.next_if(|line| line.starts_with("#![")) {
+                if index == 0 {
+                    source.push_str(attr);
+                    source.push('\n');
+                }
+                crate_attrs += 1;
+            }
+
+            // Padding the module makes the test code start after `line`, like in its file.
+            let offset = test.line_offset.saturating_sub(crate_attrs);
+            let mut module = "\n".repeat(line.saturating_sub(offset));
+            for line in lines {
+                module.push_str(line);
+                module.push('\n');
+            }
+            module.push_str("#[test]\nfn run() {\n    main()\n}\n");
+
+            // Remapping the file itself would add a trailing slash, so each module gets a
+            // directory that is remapped to the directory of the doctest's file.
+            let module_dir = dir.join(module_name(index));
+            let file_name = path.file_name().unwrap_or_else(|| "doctest.rs".as_ref());
+            let module_path = module_dir.join(file_name);
+            fs::create_dir_all(&module_dir)
+                .and_then(|()| fs::write(&module_path, module))
+                .map_err(|e| format!("failed to write `{}`: {}", module_path.display(), e))?;
+            let doctest_dir = path.parent().unwrap_or_else(|| Path::new(""));
+            remaps.push(format!("{}={}", module_dir.display(), doctest_dir.display()));
+            modules.push_str(&format!(
+                "#[path = {:?}]\nmod {};\n",
+                module_path.display().to_string(),
+                module_name(index)
+            ));
+        }
+        source.push_str(&modules);
+        Ok((source, remaps))
+    }
+
+    fn compile(&self, rustdoc_options: &RustdocOptions, target: &TargetTriple) -> Option<Compiled> {
+        let outdir = if let Some(mut path) = rustdoc_options.persist_doctests.clone() {
+            path.push(format!("merged_{}", self.edition));
+            std::fs::create_dir_all(&path)
+                .expect("Couldn't create directory for doctest executables");
+            DirState::Perm(path)
+        } else {
+            DirState::Temp(
+                TempFileBuilder::new()
+                    .prefix("rustdoctest")
+                    .tempdir()
+                    .expect("rustdoc needs a tempdir"),
+            )
+        };
+
+        let lang_string = LangString::default();
+        let mut compiler = make_compiler_command(
+            rustdoc_options,
+            &lang_string,
+            false,
+            target,
+            self.edition,
+            false,
+        );
+        let (source, remaps) = match self.write_modules(outdir.path()) {
+            Ok(result) => result,
+            Err(e) => {
+                debug!("{}", e);
+                return None;
+            }
+        };
+        for remap in remaps {
+            compiler.arg("--remap-path-prefix").arg(remap);
+        }
+        compiler.arg("--test");
+        compiler.arg("-o").arg(outdir.path().join("rust_out"));
+        compiler.arg("-");
+        compiler.stdin(Stdio::piped());
+        compiler.stderr(Stdio::piped());
+
+        debug!("merged doctests:\n{}", source);
+        let mut child = compiler.spawn().expect("Failed to spawn rustc process");
+        {
+            let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+            stdin.write_all(source.as_bytes()).expect("could write out test sources");
+        }
+        let output = child.wait_with_output().expect("Failed to read stdout");
+        if !output.status.success() {
+            debug!(
+                "merged doctests failed to compile:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            return None;
+        }
+
+        let unused_externs = str::from_utf8(&output.stderr)
+            .unwrap()
+            .lines()
+            .find_map(|line| serde_json::from_str::<UnusedExterns>(line).ok());
+        Some(Compiled { outdir, unused_externs })
+    }
+}
+
+fn module_name(index: usize) -> String {
+    format!("__doctest_{}", index)
+}
@@ -13,6 +13,7 @@
 use crate::formats::Impl;
 use crate::html::format::join_with_double_colon;
 use crate::html::markdown::short_markdown_summary;
+use crate::html::render::search_index::full_text::item_words;
 use crate::html::render::search_index::get_function_type_for_search;
 use crate::html::render::IndexItem;
 
@@ -92,6 +93,9 @@
     /// [doc_masked]: https://doc.rust-lang.org/nightly/unstable-book/language-features/doc-masked.html
     crate masked_crates: FxHashSet<CrateNum>,
 
+    /// Whether to collect the words in the documentation of items for full-text search.
+    crate full_text_search: bool,
+
     // Private fields only used when initially crawling a crate to build a cache
     stack: Vec<Symbol>,
     parent_stack: Vec<DefId>,
@@ -143,6 +147,7 @@ impl Cache {
         // Crawl the crate to build various caches used for the output
         debug!(?cx.cache.crate_version);
         cx.cache.traits = krate.external_traits.take();
+        cx.cache.full_text_search = cx.render_options.full_text_search;
 
         // Cache where all our extern crates are located
         // FIXME: this part is specific to HTML so it'd be nice to remove it from the common code
@@ -305,6 +310,7 @@ fn fold_item(&mut self, item: clean::Item) -> Option<clean::Item> {
                             parent_idx: None,
                             search_type: get_function_type_for_search(&item, self.tcx, self.cache),
                             aliases: item.attrs.get_doc_aliases(),
+                            doc_words: item_words(&item, self.cache),
                         });
                     }
                 }
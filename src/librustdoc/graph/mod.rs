@@ -0,0 +1,355 @@
+//! Rustdoc's graph backend
+//!
+//! This module renders the structure of a crate as a graph rather than as documentation, e.g. to
+//! draw diagrams of it for design reviews. The nodes of the graph are the modules of the crate and
+//! the types and traits in them, and its edges go from modules to the items they contain or
+//! re-export, and from types to the traits they implement. The graph is written in the DOT format
+//! of GraphViz to `{crate}.dot`, and as JSON to `{crate}.graph.json`.
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_graphviz as dot;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+use serde::Serialize;
+
+use crate::clean;
+use crate::config::RenderOptions;
+use crate::error::Error;
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::formats::{FormatRenderer, Impl};
+use crate::html::format::join_with_double_colon;
+use crate::try_err;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug)]
+crate struct Node {
+    /// The fully qualified path of the item, or its name for primitive types.
+    crate path: String,
+    crate kind: ItemType,
+    /// Whether the item is defined in the documented crate.
+    crate local: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+crate enum EdgeKind {
+    /// From a module to a module, type or trait defined in it.
+    Contains,
+    /// From a type to a trait it implements.
+    Implements,
+    /// From a module to an item it re-exports with `pub use`.
+    Reexports,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+crate struct Edge {
+    /// The index of the node the edge starts from.
+    crate source: usize,
+    /// The index of the node the edge goes to.
+    crate target: usize,
+    crate kind: EdgeKind,
+}
+
+crate struct Graph {
+    /// The name of the documented crate.
+    crate name: String,
+    crate nodes: Vec<Node>,
+    crate edges: Vec<Edge>,
+    /// The edges that are already in `edges`, since an item can be re-exported or implement a
+    /// trait several times.
+    seen_edges: FxHashSet<Edge>,
+}
+
+impl Graph {
+    crate fn new(name: String) -> Graph {
+        Graph { name, nodes: Vec::new(), edges: Vec::new(), seen_edges: FxHashSet::default() }
+    }
+
+    crate fn add_node(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    crate fn add_edge(&mut self, source: usize, target: usize, kind: EdgeKind) {
+        let edge = Edge { source, target, kind };
+        if self.seen_edges.insert(edge) {
+            self.edges.push(edge);
+        }
+    }
+
+    /// Renders the graph in the DOT format of GraphViz.
+    crate fn to_dot(&self) -> String {
+        let mut dot = Vec::new();
+        dot::render(self, &mut dot).unwrap();
+        String::from_utf8(dot).unwrap()
+    }
+
+    crate fn to_json(&self) -> String {
+        #[derive(Serialize)]
+        struct JsonNode<'a> {
+            id: usize,
+            path: &'a str,
+            kind: &'static str,
+            local: bool,
+        }
+
+        #[derive(Serialize)]
+        struct JsonGraph<'a> {
+            #[serde(rename = "crate")]
+            krate: &'a str,
+            nodes: Vec<JsonNode<'a>>,
+            edges: &'a [Edge],
+        }
+
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| JsonNode {
+                id,
+                path: &node.path,
+                kind: node.kind.as_str(),
+                local: node.local,
+            })
+            .collect();
+        serde_json::to_string(&JsonGraph { krate: &self.name, nodes, edges: &self.edges }).unwrap()
+    }
+}
+
+impl<'a> dot::Labeller<'a> for Graph {
+    type Node = usize;
+    type Edge = Edge;
+
+    fn graph_id(&'a self) -> dot::Id<'a> {
+        dot::Id::new(self.name.as_str()).unwrap()
+    }
+
+    fn node_id(&'a self, n: &usize) -> dot::Id<'a> {
+        dot::Id::new(format!("n{}", n)).unwrap()
+    }
+
+    fn node_shape(&'a self, n: &usize) -> Option<dot::LabelText<'a>> {
+        let shape = match self.nodes[*n].kind {
+            ItemType::Module => "folder",
+            ItemType::Trait | ItemType::TraitAlias => "ellipse",
+            _ => "box",
+        };
+        Some(dot::LabelText::label(shape))
+    }
+
+    fn node_label(&'a self, n: &usize) -> dot::LabelText<'a> {
+        let node = &self.nodes[*n];
+        dot::LabelText::label(format!("{} {}", node.kind, node.path))
+    }
+
+    fn node_style(&'a self, n: &usize) -> dot::Style {
+        if self.nodes[*n].local {
+            dot::Style::None
+        } else {
+            dot::Style::Dashed
+        }
+    }
+
+    fn edge_label(&'a self, e: &Edge) -> dot::LabelText<'a> {
+        let label = match e.kind {
+            EdgeKind::Contains => "",
+            EdgeKind::Implements => "impl",
+            EdgeKind::Reexports => "pub use",
+        };
+        dot::LabelText::label(label)
+    }
+
+    fn edge_style(&'a self, e: &Edge) -> dot::Style {
+        match e.kind {
+            EdgeKind::Contains => dot::Style::None,
+            EdgeKind::Implements => dot::Style::Dashed,
+            EdgeKind::Reexports => dot::Style::Dotted,
+        }
+    }
+}
+
+impl<'a> dot::GraphWalk<'a> for Graph {
+    type Node = usize;
+    type Edge = Edge;
+
+    fn nodes(&'a self) -> dot::Nodes<'a, usize> {
+        (0..self.nodes.len()).collect()
+    }
+
+    fn edges(&'a self) -> dot::Edges<'a, Edge> {
+        Cow::Borrowed(&self.edges)
+    }
+
+    fn source(&'a self, e: &Edge) -> usize {
+        e.source
+    }
+
+    fn target(&'a self, e: &Edge) -> usize {
+        e.target
+    }
+}
+
+/// Builds the graph of a crate from its module tree and the impls collected in the [`Cache`].
+struct GraphBuilder<'a> {
+    cache: &'a Cache,
+    graph: Graph,
+    /// The item of each node.
+    items: Vec<DefId>,
+    /// The node of each item.
+    nodes: FxHashMap<DefId, usize>,
+}
+
+impl GraphBuilder<'_> {
+    /// Returns the node of the item `did`, adding it to the graph if needed. Returns `None` for
+    /// the items whose path isn't known, which aren't documented.
+    fn node(&mut self, did: DefId) -> Option<usize> {
+        if let Some(&node) = self.nodes.get(&did) {
+            return Some(node);
+        }
+        let &(ref fqp, kind) =
+            self.cache.paths.get(&did).or_else(|| self.cache.external_paths.get(&did))?;
+        let path = match kind {
+            ItemType::Primitive => fqp.last()?.to_string(),
+            _ => join_with_double_colon(fqp),
+        };
+        let node = self.graph.add_node(Node { path, kind, local: did.is_local() });
+        self.items.push(did);
+        self.nodes.insert(did, node);
+        Some(node)
+    }
+
+    /// Adds the modules, types and traits in the module `m`, whose node is `parent`, and the
+    /// items it re-exports.
+    fn add_module(&mut self, parent: usize, m: &clean::Module) {
+        for item in m.items.iter().filter(|item| !item.is_stripped()) {
+            match *item.kind {
+                clean::ModuleItem(_)
+                | clean::StructItem(_)
+                | clean::EnumItem(_)
+                | clean::UnionItem(_)
+                | clean::TraitItem(_)
+                | clean::TraitAliasItem(_)
+                | clean::TypedefItem(..)
+                | clean::ForeignTypeItem
+                | clean::PrimitiveItem(_) => {
+                    let node = match item.def_id.as_def_id().and_then(|did| self.node(did)) {
+                        Some(node) => node,
+                        None => continue,
+                    };
+                    self.graph.add_edge(parent, node, EdgeKind::Contains);
+                    if let clean::ModuleItem(ref m) = *item.kind {
+                        self.add_module(node, m);
+                    }
+                }
+                clean::ImportItem(ref import) if import.should_be_displayed => {
+                    if let Some(node) = import.source.did.and_then(|did| self.node(did)) {
+                        self.graph.add_edge(parent, node, EdgeKind::Reexports);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Adds the traits implemented by the local types of the graph, and the types implementing
+    /// its local traits. Auto traits and blanket impls are left out, since nearly every type has
+    /// them.
+    fn add_impls(&mut self) {
+        let cache = self.cache;
+        let is_shown = |i: &&Impl| {
+            let kind = &i.inner_impl().kind;
+            !kind.is_auto() && !kind.is_blanket()
+        };
+        let local_items: Vec<_> = self.items.iter().copied().filter(|did| did.is_local()).collect();
+        for did in local_items {
+            let node = self.nodes[&did];
+            for i in cache.impls.get(&did).into_iter().flatten().filter(is_shown) {
+                if let Some(trait_) = i.trait_did().and_then(|did| self.node(did)) {
+                    self.graph.add_edge(node, trait_, EdgeKind::Implements);
+                }
+            }
+            for i in cache.implementors.get(&did).into_iter().flatten().filter(is_shown) {
+                let for_ = i.inner_impl().for_.def_id(cache).and_then(|did| self.node(did));
+                if let Some(for_) = for_ {
+                    self.graph.add_edge(for_, node, EdgeKind::Implements);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+crate struct GraphRenderer {
+    out_path: PathBuf,
+    name: Symbol,
+    graph: Rc<Graph>,
+    cache: Rc<Cache>,
+}
+
+impl<'tcx> FormatRenderer<'tcx> for GraphRenderer {
+    fn descr() -> &'static str {
+        "graph"
+    }
+
+    const RUN_ON_MODULE: bool = false;
+
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        debug!("Initializing graph renderer");
+        let name = krate.name(tcx);
+        let mut builder = GraphBuilder {
+            cache: &cache,
+            graph: Graph::new(name.to_string()),
+            items: Vec::new(),
+            nodes: FxHashMap::default(),
+        };
+        let root = builder.node(krate.module.def_id.expect_def_id());
+        if let (Some(root), clean::ModuleItem(m)) = (root, &*krate.module.kind) {
+            builder.add_module(root, m);
+        }
+        builder.add_impls();
+        let graph = Rc::new(builder.graph);
+        Ok((GraphRenderer { out_path: options.output, name, graph, cache: Rc::new(cache) }, krate))
+    }
+
+    fn make_child_renderer(&self) -> Self {
+        self.clone()
+    }
+
+    fn item(&mut self, _item: clean::Item) -> Result<(), Error> {
+        // The graph was built from the whole crate in `init`.
+        Ok(())
+    }
+
+    fn mod_item_in(&mut self, _item: &clean::Item) -> Result<(), Error> {
+        unreachable!("RUN_ON_MODULE = false should never call mod_item_in")
+    }
+
+    fn after_krate(&mut self) -> Result<(), Error> {
+        debug!("Writing the graph of {}", self.name);
+        let out_dir = &self.out_path;
+        try_err!(fs::create_dir_all(out_dir), out_dir);
+        let dst = out_dir.join(format!("{}.dot", self.name));
+        try_err!(fs::write(&dst, self.graph.to_dot()), &dst);
+        let dst = out_dir.join(format!("{}.graph.json", self.name));
+        try_err!(fs::write(&dst, self.graph.to_json()), &dst);
+        Ok(())
+    }
+
+    fn cache(&self) -> &Cache {
+        &self.cache
+    }
+}
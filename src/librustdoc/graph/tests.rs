@@ -0,0 +1,46 @@
+use super::{EdgeKind, Graph, Node};
+use crate::formats::item_type::ItemType;
+
+fn graph() -> Graph {
+    let mut graph = Graph::new("foo".to_owned());
+    let krate =
+        graph.add_node(Node { path: "foo".to_owned(), kind: ItemType::Module, local: true });
+    let ty =
+        graph.add_node(Node { path: "foo::Foo".to_owned(), kind: ItemType::Struct, local: true });
+    let trait_ = graph.add_node(Node {
+        path: "core::clone::Clone".to_owned(),
+        kind: ItemType::Trait,
+        local: false,
+    });
+    graph.add_edge(krate, ty, EdgeKind::Contains);
+    graph.add_edge(ty, trait_, EdgeKind::Implements);
+    // Added again for another impl of the trait.
+    graph.add_edge(ty, trait_, EdgeKind::Implements);
+    graph
+}
+
+#[test]
+fn test_to_dot() {
+    let expected = r#"digraph foo {
+    n0[label="mod foo"][shape="folder"];
+    n1[label="struct foo::Foo"][shape="box"];
+    n2[label="trait core::clone::Clone"][style="dashed"][shape="ellipse"];
+    n0 -> n1[label=""];
+    n1 -> n2[label="impl"][style="dashed"];
+}
+"#;
+    assert_eq!(graph().to_dot(), expected);
+}
+
+#[test]
+fn test_to_json() {
+    let expected = concat!(
+        r#"{"crate":"foo","nodes":["#,
+        r#"{"id":0,"path":"foo","kind":"mod","local":true},"#,
+        r#"{"id":1,"path":"foo::Foo","kind":"struct","local":true},"#,
+        r#"{"id":2,"path":"core::clone::Clone","kind":"trait","local":false}],"#,
+        r#""edges":[{"source":0,"target":1,"kind":"contains"},"#,
+        r#"{"source":1,"target":2,"kind":"implements"}]}"#,
+    );
+    assert_eq!(graph().to_json(), expected);
+}
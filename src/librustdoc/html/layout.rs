@@ -21,6 +21,9 @@
     crate css_file_extension: Option<PathBuf>,
     /// If true, then scrape-examples.js will be included in the output HTML file
     crate scrape_examples_extension: bool,
+    /// The version of the documentation, if several versions are documented in the same site, in
+    /// which case `versions.js` is loaded from the root of the site.
+    crate doc_version: Option<String>,
 }
 
 crate struct Page<'a> {
@@ -146,13 +146,13 @@ impl ErrorCodes {
 /// Controls whether a line will be hidden or shown in HTML output.
 ///
 /// All lines are used in documentation tests.
-enum Line<'a> {
+crate enum Line<'a> {
     Hidden(&'a str),
     Shown(Cow<'a, str>),
 }
 
 impl<'a> Line<'a> {
-    fn for_html(self) -> Option<Cow<'a, str>> {
+    crate fn for_html(self) -> Option<Cow<'a, str>> {
         match self {
             Line::Shown(l) => Some(l),
             Line::Hidden(_) => None,
@@ -172,7 +172,7 @@ fn for_code(self) -> Cow<'a, str> {
 // is done in the single # case. This inconsistency seems okay, if non-ideal. In
 // order to fix it we'd have to iterate to find the first non-# character, and
 // then reallocate to remove it; which would make us return a String.
-fn map_line(s: &str) -> Line<'_> {
+crate fn map_line(s: &str) -> Line<'_> {
     let trimmed = s.trim();
     if trimmed.starts_with("##") {
         Line::Shown(Cow::Owned(s.replacen("##", "#", 1)))
@@ -872,7 +872,7 @@ fn default() -> Self {
 }
 
 impl LangString {
-    fn parse_without_check(
+    crate fn parse_without_check(
         string: &str,
         allow_error_code_check: ErrorCodes,
         enable_per_target_ignores: bool,
@@ -1250,6 +1250,31 @@ fn markdown_summary_with_limit(
     s
 }
 
+/// Renders the provided markdown as plain text, for the full-text search index.
+///
+/// - Headings, links, and formatting are stripped.
+/// - Inline code is rendered as-is.
+/// - HTML and code blocks are ignored.
+crate fn plain_text(md: &str) -> String {
+    let mut s = String::with_capacity(md.len());
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(md, main_body_opts()) {
+        match &event {
+            Event::Text(text) if !in_code_block => s.push_str(text),
+            Event::Code(code) => s.push_str(code),
+            Event::HardBreak | Event::SoftBreak => s.push(' '),
+            Event::Start(Tag::CodeBlock(..)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(..)) => in_code_block = false,
+            // Keep the words of consecutive blocks apart.
+            Event::End(_) => s.push(' '),
+            _ => (),
+        }
+    }
+
+    s
+}
+
 #[derive(Debug)]
 crate struct MarkdownLink {
     pub kind: LinkType,
@@ -1323,6 +1348,34 @@ fn markdown_summary_with_limit(
     links.into_inner()
 }
 
+/// Returns the inline code spans of the markdown that aren't part of a link, along with their
+/// range in the markdown, which includes the backticks.
+crate fn markdown_code_spans(md: &str) -> Vec<(String, Range<usize>)> {
+    let mut spans = vec![];
+    if md.is_empty() {
+        return spans;
+    }
+
+    // Broken links like [`Foo`] are reported by `broken_intra_doc_links`, so they are turned into
+    // links to be skipped.
+    let mut replacer = |broken_link: BrokenLink<'_>| {
+        Some((broken_link.reference.into_string().into(), "".into()))
+    };
+    let p = Parser::new_with_broken_link_callback(md, main_body_opts(), Some(&mut replacer))
+        .into_offset_iter();
+
+    let mut link_depth = 0;
+    for (event, range) in p {
+        match event {
+            Event::Start(Tag::Link(..)) => link_depth += 1,
+            Event::End(Tag::Link(..)) => link_depth -= 1,
+            Event::Code(code) if link_depth == 0 => spans.push((code.into_string(), range)),
+            _ => {}
+        }
+    }
+    spans
+}
+
 #[derive(Debug)]
 crate struct RustCodeBlock {
     /// The range in the markdown that the code block occupies. Note that this includes the fences
@@ -1,4 +1,4 @@
-use super::{find_testable_code, plain_text_summary, short_markdown_summary};
+use super::{find_testable_code, markdown_code_spans, plain_text_summary, short_markdown_summary};
 use super::{ErrorCodes, HeadingOffset, IdMap, Ignore, LangString, Markdown, MarkdownHtml};
 use rustc_span::edition::{Edition, DEFAULT_EDITION};
 
@@ -310,3 +310,17 @@ fn add_test(&mut self, _test: String, _config: LangString, line: usize) {
     t("```rust\n```\n```rust\n```", &[1, 3]);
     t("```rust\n```\n ```rust\n```", &[1, 3]);
 }
+
+#[test]
+fn test_markdown_code_spans() {
+    fn t(input: &str, expect: &[(&str, std::ops::Range<usize>)]) {
+        let spans = markdown_code_spans(input);
+        let spans: Vec<_> = spans.iter().map(|(code, range)| (&**code, range.clone())).collect();
+        assert_eq!(spans, expect);
+    }
+
+    t("", &[]);
+    t("Calls `Foo::bar`.", &[("Foo::bar", 6..16)]);
+    t("[`Foo`] and [`Bar`](Bar) aren't code spans, `baz` is.", &[("baz", 44..49)]);
+    t("```\nlet x = `y`;\n```", &[]);
+}
@@ -15,6 +15,8 @@
 
 use super::print_item::{full_path, item_path, print_item};
 use super::search_index::build_index;
+use super::search_index::full_text::build_full_text_index;
+use super::versions::VersionHistory;
 use super::write_shared::write_shared;
 use super::{
     collect_spans_and_sources, print_sidebar, settings, AllTypes, LinkFromSrc, NameDoc, StylePath,
@@ -124,6 +126,10 @@
     crate cache: Cache,
 
     crate call_locations: AllCallLocations,
+    /// `None` by default, depends on the `doc-version` option. If this field is set, the
+    /// signatures of the items are recorded, and the pages of the items say which version they
+    /// were added in.
+    pub(super) versions: Option<VersionHistory>,
 }
 
 impl SharedContext<'_> {
@@ -406,6 +412,7 @@ fn init(
             generate_link_to_definition,
             call_locations,
             no_emit_shared,
+            doc_version,
             ..
         } = options;
 
@@ -430,6 +437,7 @@ fn init(
             krate: krate.name(tcx).to_string(),
             css_file_extension: extension_css,
             scrape_examples_extension: !call_locations.is_empty(),
+            doc_version: doc_version.clone(),
         };
         let mut issue_tracker_base_url = None;
         let mut include_sources = true;
@@ -468,6 +476,15 @@ fn init(
             generate_link_to_definition,
         );
 
+        // With `--doc-version`, `output` is the directory of the version, in the root of the site.
+        let versions = match doc_version {
+            Some(version) => {
+                let root = output.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+                Some(VersionHistory::new(root, version, krate.name(tcx).as_str())?)
+            }
+            None => None,
+        };
+
         let (sender, receiver) = channel();
         let mut scx = SharedContext {
             tcx,
@@ -490,6 +507,7 @@ fn init(
             span_correspondance_map: matches,
             cache,
             call_locations,
+            versions,
         };
 
         // Add the default themes to the `Vec` of stylepaths
@@ -526,6 +544,15 @@ fn init(
             // Build our search index
             let index = build_index(&krate, &mut Rc::get_mut(&mut cx.shared).unwrap().cache, tcx);
 
+            // The full-text index refers to items by their position in the search index, so it
+            // has to be built after it.
+            if emit_crate && cx.shared.cache.full_text_search {
+                let full_text_index = build_full_text_index(&krate, &cx.shared.cache, tcx);
+                let dst = cx.dst.join(krate.name(tcx).as_str());
+                cx.shared.ensure_dir(&dst)?;
+                cx.shared.fs.write(dst.join("search-fulltext.js"), full_text_index)?;
+            }
+
             // Write shared runs within a flock; disable thread dispatching of IO temporarily.
             Rc::get_mut(&mut cx.shared).unwrap().fs.set_sync_only(true);
             write_shared(&cx, &krate, index, &md_opts)?;
@@ -616,6 +643,12 @@ fn after_krate(&mut self) -> Result<(), Error> {
             }
         }
 
+        if let Some(ref versions) = self.shared.versions {
+            let crate_dir = self.dst.join(crate_name.as_str());
+            self.shared.ensure_dir(&crate_dir)?;
+            versions.write_signatures(&crate_dir, &self.shared.fs)?;
+        }
+
         // Flush pending errors.
         Rc::get_mut(&mut self.shared).unwrap().fs.close();
         let nb_errors =
@@ -31,6 +31,7 @@
 mod context;
 mod print_item;
 mod span_map;
+mod versions;
 mod write_shared;
 
 crate use self::context::*;
@@ -103,6 +104,8 @@
     crate parent_idx: Option<usize>,
     crate search_type: Option<IndexItemFunctionType>,
     crate aliases: Box<[Symbol]>,
+    /// The words in the documentation of the item, for the full-text search index.
+    crate doc_words: Vec<String>,
 }
 
 /// A type used for the search index.
@@ -2381,7 +2384,7 @@ fn sidebar_enum(cx: &Context<'_>, buf: &mut Buffer, it: &clean::Item, e: &clean:
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum ItemSection {
+crate enum ItemSection {
     Reexports,
     PrimitiveTypes,
     Modules,
@@ -2410,7 +2413,7 @@ enum ItemSection {
 }
 
 impl ItemSection {
-    const ALL: &'static [Self] = {
+    crate const ALL: &'static [Self] = {
         use ItemSection::*;
         // NOTE: The order here affects the order in the UI.
         &[
@@ -2472,7 +2475,7 @@ fn id(self) -> &'static str {
         }
     }
 
-    fn name(self) -> &'static str {
+    crate fn name(self) -> &'static str {
         match self {
             Self::Reexports => "Re-exports",
             Self::Modules => "Modules",
@@ -2503,7 +2506,7 @@ fn name(self) -> &'static str {
     }
 }
 
-fn item_ty_to_section(ty: ItemType) -> ItemSection {
+crate fn item_ty_to_section(ty: ItemType) -> ItemSection {
     match ty {
         ItemType::ExternCrate | ItemType::Import => ItemSection::Reexports,
         ItemType::Module => ItemSection::Modules,
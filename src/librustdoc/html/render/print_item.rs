@@ -57,6 +57,7 @@ struct ItemVars<'a> {
     item_type: &'a str,
     path_components: Vec<PathComponent>,
     stability_since_raw: &'a str,
+    version_history: &'a str,
     src_href: Option<&'a str>,
 }
 
@@ -128,6 +129,46 @@ pub(super) fn print_item(cx: &Context<'_>, item: &clean::Item, buf: &mut Buffer,
             .collect()
     };
 
+    // The heading is written after the content, since it says when the declaration in the content
+    // changed.
+    let mut content = Buffer::empty_from(buf);
+    match *item.kind {
+        clean::ModuleItem(ref m) => item_module(&mut content, cx, item, &m.items),
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+            item_function(&mut content, cx, item, f)
+        }
+        clean::TraitItem(ref t) => item_trait(&mut content, cx, item, t),
+        clean::StructItem(ref s) => item_struct(&mut content, cx, item, s),
+        clean::UnionItem(ref s) => item_union(&mut content, cx, item, s),
+        clean::EnumItem(ref e) => item_enum(&mut content, cx, item, e),
+        clean::TypedefItem(ref t, is_associated) => {
+            item_typedef(&mut content, cx, item, t, is_associated)
+        }
+        clean::MacroItem(ref m) => item_macro(&mut content, cx, item, m),
+        clean::ProcMacroItem(ref m) => item_proc_macro(&mut content, cx, item, m),
+        clean::PrimitiveItem(_) => item_primitive(&mut content, cx, item),
+        clean::StaticItem(ref i) | clean::ForeignStaticItem(ref i) => {
+            item_static(&mut content, cx, item, i)
+        }
+        clean::ConstantItem(ref c) => item_constant(&mut content, cx, item, c),
+        clean::ForeignTypeItem => item_foreign_type(&mut content, cx, item),
+        clean::KeywordItem(_) => item_keyword(&mut content, cx, item),
+        clean::OpaqueTyItem(ref e) => item_opaque_ty(&mut content, cx, item, e),
+        clean::TraitAliasItem(ref ta) => item_trait_alias(&mut content, cx, item, ta),
+        _ => {
+            // We don't generate pages for any other type.
+            unreachable!();
+        }
+    }
+    let content = content.into_inner();
+
+    let version_history = match cx.shared.versions {
+        Some(ref versions) => {
+            versions.annotate(&page_path(cx, item), declaration(&content).as_deref())
+        }
+        None => String::new(),
+    };
+
     let item_vars = ItemVars {
         page,
         static_root_path: page.get_static_root_path(),
@@ -136,35 +177,12 @@ pub(super) fn print_item(cx: &Context<'_>, item: &clean::Item, buf: &mut Buffer,
         item_type: &item.type_().to_string(),
         path_components,
         stability_since_raw: &stability_since_raw,
+        version_history: &version_history,
         src_href: src_href.as_deref(),
     };
 
     item_vars.render_into(buf).unwrap();
-
-    match *item.kind {
-        clean::ModuleItem(ref m) => item_module(buf, cx, item, &m.items),
-        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
-            item_function(buf, cx, item, f)
-        }
-        clean::TraitItem(ref t) => item_trait(buf, cx, item, t),
-        clean::StructItem(ref s) => item_struct(buf, cx, item, s),
-        clean::UnionItem(ref s) => item_union(buf, cx, item, s),
-        clean::EnumItem(ref e) => item_enum(buf, cx, item, e),
-        clean::TypedefItem(ref t, is_associated) => item_typedef(buf, cx, item, t, is_associated),
-        clean::MacroItem(ref m) => item_macro(buf, cx, item, m),
-        clean::ProcMacroItem(ref m) => item_proc_macro(buf, cx, item, m),
-        clean::PrimitiveItem(_) => item_primitive(buf, cx, item),
-        clean::StaticItem(ref i) | clean::ForeignStaticItem(ref i) => item_static(buf, cx, item, i),
-        clean::ConstantItem(ref c) => item_constant(buf, cx, item, c),
-        clean::ForeignTypeItem => item_foreign_type(buf, cx, item),
-        clean::KeywordItem(_) => item_keyword(buf, cx, item),
-        clean::OpaqueTyItem(ref e) => item_opaque_ty(buf, cx, item, e),
-        clean::TraitAliasItem(ref ta) => item_trait_alias(buf, cx, item, ta),
-        _ => {
-            // We don't generate pages for any other type.
-            unreachable!();
-        }
-    }
+    buf.push_str(&content);
 }
 
 /// For large structs, enums, unions, etc, determine whether to hide their fields
@@ -1412,6 +1430,35 @@ pub(super) fn full_path(cx: &Context<'_>, item: &clean::Item) -> String {
     s
 }
 
+/// Returns the path of the page of `item`, relative to the root of the documentation.
+fn page_path(cx: &Context<'_>, item: &clean::Item) -> String {
+    let mut path: String = cx.current.iter().map(|name| format!("{}/", name)).collect();
+    if item.is_mod() {
+        path.push_str("index.html");
+    } else {
+        path.push_str(&item_path(item.type_(), item.name.unwrap().as_str()));
+    }
+    path
+}
+
+/// Returns the text of the declaration in the `content` of the page of an item, without its HTML
+/// tags, if it has one.
+fn declaration(content: &str) -> Option<String> {
+    let start = content.find("<div class=\"docblock item-decl\">")?;
+    let end = start + content[start..].find("</pre>")?;
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in content[start..end].chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    Some(text)
+}
+
 pub(super) fn item_path(ty: ItemType, name: &str) -> String {
     match ty {
         ItemType::Module => format!("{}index.html", ensure_trailing_slash(name)),
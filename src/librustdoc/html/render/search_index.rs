@@ -14,6 +14,8 @@
 use crate::html::markdown::short_markdown_summary;
 use crate::html::render::{IndexItem, IndexItemFunctionType, RenderType, TypeWithKind};
 
+crate mod full_text;
+
 /// Builds the search index from the collected metadata
 crate fn build_index<'tcx>(krate: &clean::Crate, cache: &mut Cache, tcx: TyCtxt<'tcx>) -> String {
     let mut defid_to_pathid = FxHashMap::default();
@@ -35,6 +37,7 @@
                 parent_idx: None,
                 search_type: get_function_type_for_search(item, tcx, &cache),
                 aliases: item.attrs.get_doc_aliases(),
+                doc_words: full_text::item_words(item, cache),
             });
         }
     }
@@ -44,7 +47,7 @@
         .doc_value()
         .map_or_else(String::new, |s| short_markdown_summary(&s, &krate.module.link_names(cache)));
 
-    let Cache { ref mut search_index, ref paths, .. } = *cache;
+    let Cache { ref mut search_index, ref paths, full_text_search, .. } = *cache;
 
     // Aliases added through `#[doc(alias = "...")]`. Since a few items can have the same alias,
     // we need the alias element to have an array of items.
@@ -108,6 +111,8 @@ struct CrateData<'a> {
         //
         // To be noted: the `usize` elements are indexes to `items`.
         aliases: &'a BTreeMap<String, Vec<usize>>,
+        // Whether the crate has a full-text index, which is loaded separately.
+        has_full_text: bool,
     }
 
     impl<'a> Serialize for CrateData<'a> {
@@ -116,8 +121,8 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             S: Serializer,
         {
             let has_aliases = !self.aliases.is_empty();
-            let mut crate_data =
-                serializer.serialize_struct("CrateData", if has_aliases { 9 } else { 8 })?;
+            let len = 8 + has_aliases as usize + self.has_full_text as usize;
+            let mut crate_data = serializer.serialize_struct("CrateData", len)?;
             crate_data.serialize_field("doc", &self.doc)?;
             crate_data.serialize_field(
                 "t",
@@ -162,6 +167,9 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             if has_aliases {
                 crate_data.serialize_field("a", &self.aliases)?;
             }
+            if self.has_full_text {
+                crate_data.serialize_field("ft", &1)?;
+            }
             crate_data.end()
         }
     }
@@ -175,6 +183,7 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
             items: crate_items,
             paths: crate_paths,
             aliases: &aliases,
+            has_full_text: full_text_search,
         })
         .expect("failed serde conversion")
         // All these `replace` calls are because we have to go through JS string for JSON content.
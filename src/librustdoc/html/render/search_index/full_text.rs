@@ -0,0 +1,134 @@
+//! The full-text search index, enabled with `--full-text-search`.
+//!
+//! Unlike the main search index, which only contains the names and signatures of items, this one
+//! maps the words in the documentation of items to the items. Each crate gets its own index in
+//! `{crate}/search-fulltext.js`, which `search.js` only loads once a search is made, since it is
+//! much bigger than the main index.
+//!
+//! Words are lowercased and reduced to a stem with a few simple rules, so that `parses`, `parsed`
+//! and `parsing` all match each other. `search.js` has to process queries in exactly the same
+//! way, so any change here has to be made there too.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rustc_middle::ty::TyCtxt;
+use serde::Serialize;
+
+use crate::clean;
+use crate::formats::cache::Cache;
+use crate::html::markdown::plain_text;
+
+#[cfg(test)]
+mod tests;
+
+/// Words that are so common that searching for them isn't useful.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "if", "in", "is", "it", "of",
+    "on", "or", "that", "the", "this", "to", "was", "with",
+];
+
+/// Returns the stems of the words in the documentation of `item`, if full-text search is enabled.
+crate fn item_words(item: &clean::Item, cache: &Cache) -> Vec<String> {
+    if !cache.full_text_search {
+        return Vec::new();
+    }
+    item.doc_value().map_or_else(Vec::new, |doc| words(&doc))
+}
+
+/// Returns the distinct stems of the words in the Markdown `md`, sorted.
+crate fn words(md: &str) -> Vec<String> {
+    let words: BTreeSet<_> = plain_text(md)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| stem(&word))
+        .collect();
+    words.into_iter().collect()
+}
+
+/// Strips the most common English suffixes from `word`. Only ASCII words are changed.
+fn stem(word: &str) -> String {
+    if word.len() <= 3 || !word.is_ascii() {
+        return word.to_owned();
+    }
+    let mut stem = word.to_owned();
+    // Plurals and the third person.
+    if let Some(s) = word.strip_suffix("ies") {
+        stem = format!("{}y", s);
+    } else if word.ends_with("sses") {
+        stem.truncate(word.len() - 2);
+    } else if word.ends_with('s') && !["ss", "us", "is"].iter().any(|s| word.ends_with(s)) {
+        stem.pop();
+    }
+
+    if let Some(s) = stem.strip_suffix("ation").filter(|s| s.len() >= 3) {
+        // `allocation` becomes `allocate`, which loses its `e` below.
+        stem = format!("{}ate", s);
+    } else if let Some(s) = stem.strip_suffix("ied").filter(|s| s.len() >= 2) {
+        stem = format!("{}y", s);
+    } else if let Some(s) =
+        stem.strip_suffix("ing").or_else(|| stem.strip_suffix("ed")).filter(|s| s.len() >= 3)
+    {
+        let mut s = s.to_owned();
+        // `running` and `stopped` double their last consonant.
+        let bytes = s.as_bytes();
+        let last = bytes[bytes.len() - 1];
+        if last == bytes[bytes.len() - 2] && !b"aeioulsz".contains(&last) {
+            s.pop();
+        }
+        stem = s;
+    } else if let Some(s) = stem.strip_suffix("ly").filter(|s| s.len() >= 3) {
+        stem = s.to_owned();
+    }
+
+    // `parse` and `pars(ing)` need to end up the same.
+    if stem.len() > 3 && stem.ends_with('e') {
+        stem.pop();
+    }
+    stem
+}
+
+/// Builds the full-text index of the crate, once the search index has been sorted by
+/// `build_index`. Items are referred to by their position in the search index of the crate, where
+/// the crate itself comes first.
+crate fn build_full_text_index<'tcx>(
+    krate: &clean::Crate,
+    cache: &Cache,
+    tcx: TyCtxt<'tcx>,
+) -> String {
+    let crate_words = krate.module.doc_value().map_or_else(Vec::new, |doc| words(&doc));
+    let mut postings: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for word in &crate_words {
+        postings.entry(word).or_default().push(0);
+    }
+    for (i, item) in cache.search_index.iter().enumerate() {
+        for word in &item.doc_words {
+            postings.entry(word).or_default().push(i + 1);
+        }
+    }
+
+    #[derive(Serialize)]
+    struct FullTextIndex<'a> {
+        /// The words, sorted.
+        w: Vec<&'a str>,
+        /// The items whose documentation contains each word.
+        i: Vec<Vec<usize>>,
+    }
+
+    let (words, items) = postings.into_iter().unzip();
+    let index = serde_json::to_string(&FullTextIndex { w: words, i: items })
+        .expect("failed serde conversion")
+        // All these `replace` calls are because we have to go through JS string for JSON content.
+        .replace(r#"\"#, r"\\")
+        .replace(r#"'"#, r"\'")
+        // We need to escape double quotes for the JSON.
+        .replace("\\\"", "\\\\\"");
+    format!(
+        "if (window.registerFullTextIndex) {{\
+            window.registerFullTextIndex(\"{}\", JSON.parse('{}'))\
+        }};",
+        krate.name(tcx),
+        index
+    )
+}
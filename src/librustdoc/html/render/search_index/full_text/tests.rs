@@ -0,0 +1,37 @@
+use super::{stem, words};
+
+#[test]
+fn test_stem() {
+    fn t(word: &str, expected: &str) {
+        assert_eq!(stem(word), expected, "stem of {:?}", word);
+    }
+
+    for word in ["parse", "parses", "parsed", "parsing"] {
+        t(word, "pars");
+    }
+    for word in ["allocate", "allocates", "allocation", "allocations", "allocated"] {
+        t(word, "allocat");
+    }
+    t("libraries", "library");
+    t("library", "library");
+    t("copied", "copy");
+    t("running", "run");
+    t("stopped", "stop");
+    t("quickly", "quick");
+    t("status", "status");
+    t("classes", "class");
+    t("fn", "fn");
+    t("écrites", "écrites");
+}
+
+#[test]
+fn test_words() {
+    assert_eq!(
+        words(
+            "Parses the `config_file`.\n\n```\nlet x = parse();\n```\n\n# Errors\n\n\
+             Fails on I/O errors."
+        ),
+        ["config", "error", "fail", "fil", "i", "o", "pars"]
+    );
+    assert_eq!(words(""), Vec::<String>::new());
+}
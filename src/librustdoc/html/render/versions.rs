@@ -0,0 +1,196 @@
+//! Sites with the documentation of several versions of crates, enabled with `--doc-version`.
+//!
+//! Each version is documented in a subdirectory of the site named after it, exactly as it would be
+//! on its own, so every version has its own search index and list of crates. The versions are
+//! listed in `versions.js` at the root of the site, which `main.js` uses to add a version switcher
+//! to the sidebar.
+//!
+//! The declaration of every item is hashed into `{crate}/signatures.json` in the directory of its
+//! version, so that the pages of later versions can say which version an item was added in, and
+//! which version its declaration last changed in.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::flock;
+use rustc_data_structures::stable_hasher::StableHasher;
+
+use crate::docfs::{DocFS, PathError};
+use crate::error::Error;
+use crate::html::escape::Escape;
+use crate::try_err;
+
+#[cfg(test)]
+mod tests;
+
+const VERSIONS_FILE: &str = "versions.js";
+const SIGNATURES_FILE: &str = "signatures.json";
+
+/// The hashes of the declarations of the items of a crate, by the path of their page. Items
+/// without a declaration, like modules, have no hash.
+type Signatures = BTreeMap<String, Option<String>>;
+
+crate struct VersionHistory {
+    /// The root of the site, which contains the directory of each version.
+    root: PathBuf,
+    /// The version being documented.
+    version: String,
+    /// The signatures of the items of the crate in the earlier versions of the site, from the most
+    /// recent one.
+    earlier: Vec<(String, Signatures)>,
+    /// The signatures of the items of the crate in this version.
+    signatures: RefCell<Signatures>,
+}
+
+impl VersionHistory {
+    /// Loads the signatures of the items of `krate` in the versions of the site at `root` that are
+    /// earlier than `version`.
+    crate fn new(root: PathBuf, version: String, krate: &str) -> Result<VersionHistory, Error> {
+        let mut earlier = Vec::new();
+        for other in read_versions(&root)?.into_iter().rev() {
+            if compare_versions(&other, &version) != Ordering::Less {
+                continue;
+            }
+            let crate_dir = root.join(&other).join(krate);
+            let path = crate_dir.join(SIGNATURES_FILE);
+            let signatures = if path.exists() {
+                let contents = try_err!(fs::read_to_string(&path), &path);
+                try_err!(serde_json::from_str(&contents), &path)
+            } else if crate_dir.exists() {
+                // The crate was documented without `--doc-version`, so we know nothing about it.
+                continue;
+            } else {
+                // The crate didn't exist yet.
+                Signatures::new()
+            };
+            earlier.push((other, signatures));
+        }
+        Ok(VersionHistory { root, version, earlier, signatures: Default::default() })
+    }
+
+    /// Records the declaration of the item whose page is at `path`, relative to the directory of
+    /// the version. Returns the HTML saying which version the item was added in and which version
+    /// its declaration last changed in, or an empty string if they aren't known.
+    crate fn annotate(&self, path: &str, declaration: Option<&str>) -> String {
+        let signature = declaration.map(|declaration| {
+            let mut hasher = StableHasher::new();
+            declaration.hash(&mut hasher);
+            hasher.finish::<Fingerprint>().to_hex()
+        });
+        let (added, changed) = history(&self.version, &signature, path, &self.earlier);
+        self.signatures.borrow_mut().insert(path.to_owned(), signature);
+
+        let mut parts = Vec::new();
+        if let Some(added) = added {
+            parts.push(format!("Added in {}", Escape(added)));
+        }
+        if let Some(changed) = changed {
+            let verb = if parts.is_empty() { "Changed" } else { "changed" };
+            parts.push(format!("{} in {}", verb, Escape(changed)));
+        }
+        if parts.is_empty() {
+            return String::new();
+        }
+        format!(
+            "<span class=\"since version-history\" title=\"The version this item was added in, \
+             and the last version its declaration changed in\">{}</span>",
+            parts.join(", ")
+        )
+    }
+
+    /// Writes the signatures of the items of this version of the crate to `crate_dir`.
+    crate fn write_signatures(&self, crate_dir: &Path, fs: &DocFS) -> Result<(), Error> {
+        let signatures = serde_json::to_string(&*self.signatures.borrow()).unwrap();
+        fs.write(crate_dir.join(SIGNATURES_FILE), signatures)
+    }
+
+    /// Adds this version to the list of versions at the root of the site.
+    crate fn write_version_list(&self, fs: &DocFS) -> Result<(), Error> {
+        // Other versions can be documented at the same time.
+        let lock_file = self.root.join(".lock");
+        let _lock = try_err!(flock::Lock::new(&lock_file, true, true, true), &lock_file);
+
+        let mut versions = read_versions(&self.root)?;
+        if !versions.contains(&self.version) {
+            versions.push(self.version.clone());
+            versions.sort_by(|a, b| compare_versions(a, b));
+        }
+        let versions = serde_json::to_string(&versions).unwrap();
+        fs.write(self.root.join(VERSIONS_FILE), format!("window.ALL_VERSIONS = {};", versions))
+    }
+}
+
+/// Reads the versions of the site at `root`, sorted from the earliest one.
+fn read_versions(root: &Path) -> Result<Vec<String>, Error> {
+    let path = root.join(VERSIONS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = try_err!(fs::read_to_string(&path), &path);
+    let versions = contents
+        .trim()
+        .strip_prefix("window.ALL_VERSIONS = ")
+        .and_then(|versions| versions.strip_suffix(';'))
+        .unwrap_or("[]");
+    Ok(try_err!(serde_json::from_str(versions), &path))
+}
+
+/// Returns the version the item at `path` was added in, unless it was already in the earliest
+/// version, and the last version its signature changed in.
+fn history<'a>(
+    version: &'a str,
+    signature: &Option<String>,
+    path: &str,
+    earlier: &'a [(String, Signatures)],
+) -> (Option<&'a str>, Option<&'a str>) {
+    let mut changed = None;
+    let (mut newer_version, mut newer_signature) = (version, signature);
+    for (earlier_version, signatures) in earlier {
+        let Some(earlier_signature) = signatures.get(path) else {
+            return (Some(newer_version), changed);
+        };
+        if changed.is_none() && earlier_signature != newer_signature {
+            changed = Some(newer_version);
+        }
+        newer_version = earlier_version.as_str();
+        newer_signature = earlier_signature;
+    }
+    (None, changed)
+}
+
+/// Compares versions like `1.10.0` and `1.9.1-beta.2` component by component, comparing the
+/// components that are numbers as numbers. Like in semver, pre-releases come before the release.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn compare_components(a: &str, b: &str) -> Ordering {
+        let mut a = a.split('.');
+        let mut b = b.split('.');
+        loop {
+            let ordering = match (a.next(), b.next()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    _ => a.cmp(b),
+                },
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+    }
+
+    let (a, a_pre) = a.split_once('-').map_or((a, None), |(a, pre)| (a, Some(pre)));
+    let (b, b_pre) = b.split_once('-').map_or((b, None), |(b, pre)| (b, Some(pre)));
+    compare_components(a, b).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_components(a, b),
+    })
+}
@@ -0,0 +1,35 @@
+use std::cmp::Ordering;
+
+use super::{compare_versions, history, Signatures};
+
+#[test]
+fn test_compare_versions() {
+    assert_eq!(compare_versions("1.9.1", "1.10.0"), Ordering::Less);
+    assert_eq!(compare_versions("1.10.0", "1.10.0"), Ordering::Equal);
+    assert_eq!(compare_versions("2.0", "1.99.99"), Ordering::Greater);
+    assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+    assert_eq!(compare_versions("1.0.0-beta.2", "1.0.0-beta.10"), Ordering::Less);
+    assert_eq!(compare_versions("1.0.0-beta", "1.0.0"), Ordering::Less);
+    assert_eq!(compare_versions("nightly", "beta"), Ordering::Greater);
+}
+
+#[test]
+fn test_history() {
+    fn signatures(items: &[(&str, &str)]) -> Signatures {
+        items.iter().map(|&(path, hash)| (path.to_owned(), Some(hash.to_owned()))).collect()
+    }
+    let earlier = vec![
+        ("1.2.0".to_owned(), signatures(&[("foo/fn.a.html", "a2"), ("foo/fn.b.html", "b")])),
+        ("1.1.0".to_owned(), signatures(&[("foo/fn.a.html", "a1"), ("foo/fn.b.html", "b")])),
+        ("1.0.0".to_owned(), signatures(&[("foo/fn.a.html", "a1")])),
+    ];
+    let t =
+        |path: &str, signature: &str| history("1.3.0", &Some(signature.to_owned()), path, &earlier);
+
+    assert_eq!(t("foo/fn.a.html", "a2"), (None, Some("1.2.0")));
+    assert_eq!(t("foo/fn.a.html", "a3"), (None, Some("1.3.0")));
+    assert_eq!(t("foo/fn.b.html", "b"), (Some("1.1.0"), None));
+    assert_eq!(t("foo/fn.b.html", "b2"), (Some("1.1.0"), Some("1.3.0")));
+    assert_eq!(t("foo/fn.c.html", "c"), (Some("1.3.0"), None));
+    assert_eq!(history("1.0.0", &None, "foo/index.html", &[]), (None, None));
+}
@@ -457,6 +457,15 @@ fn to_json_string(&self) -> String {
         Ok(format!("window.ALL_CRATES = [{}];", krates).into_bytes())
     })?;
 
+    // The list of versions is at the root of the site rather than in `cx.dst`, but it changes
+    // with the invocations like the list of crates does.
+    let versions_js = SharedResource::InvocationSpecific { basename: "versions.js" };
+    if let Some(ref versions) = cx.shared.versions {
+        if versions_js.should_emit(&options.emit) {
+            versions.write_version_list(&cx.shared.fs)?;
+        }
+    }
+
     if options.enable_index_page {
         if let Some(index_page) = options.index_page.clone() {
             let mut md_opts = options.clone();
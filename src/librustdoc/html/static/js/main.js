@@ -196,6 +196,7 @@ function hideThemeButtonState() {
         // 0 for "In Names"
         // 1 for "In Parameters"
         // 2 for "In Return Types"
+        // 3 for "In Documentation", with `--full-text-search`
         currentTab: 0,
         // tab and back preserves the element that was focused.
         focusedByTab: [null, null, null],
@@ -969,8 +970,48 @@ function hideThemeButtonState() {
         buildHelperPopup = function() {};
     };
 
+    /**
+     * When the documentation of several versions is in the same site (`--doc-version`), add a
+     * menu to the sidebar to go to the same page in another version.
+     */
+    function addVersionSwitcher() {
+        var currentVersion = getVar("doc-version");
+        var sidebar = document.querySelector(".sidebar");
+        if (!currentVersion || !window.ALL_VERSIONS || !sidebar) {
+            return;
+        }
+        var select = document.createElement("select");
+        select.id = "version-switcher";
+        select.setAttribute("aria-label", "Version");
+        onEach(window.ALL_VERSIONS.slice().reverse(), function(version) {
+            var option = document.createElement("option");
+            option.value = version;
+            option.textContent = version;
+            option.selected = version === currentVersion;
+            select.appendChild(option);
+        });
+        select.addEventListener("change", function() {
+            // The path of the current page, relative to the directory of the version.
+            var versionRoot = new URL(window.rootPath, window.location.href).pathname;
+            var page = window.location.pathname.substring(versionRoot.length);
+            window.location.href = window.rootPath + "../" +
+                encodeURIComponent(select.value) + "/" + page + window.location.hash;
+        });
+
+        var div = document.createElement("div");
+        div.className = "block version-switcher";
+        div.appendChild(select);
+        var sidebarLocation = sidebar.querySelector(".location");
+        if (sidebarLocation) {
+            sidebar.insertBefore(div, sidebarLocation);
+        } else {
+            sidebar.appendChild(div);
+        }
+    }
+
     onHashChange(null);
     window.addEventListener("hashchange", onHashChange);
+    addVersionSwitcher();
     searchState.setup();
 }());
 
@@ -39,7 +39,7 @@ var TY_KEYWORD = itemTypes.indexOf("keyword");
 
 // In the search display, allows to switch between tabs.
 function printTab(nb) {
-    if (nb === 0 || nb === 1 || nb === 2) {
+    if (nb === 0 || nb === 1 || nb === 2 || nb === 3) {
         searchState.currentTab = nb;
     }
     var nb_copy = nb;
@@ -123,6 +123,16 @@ window.initSearch = function(rawSearchIndex) {
     var searchWords;
     var currentResults;
     var ALIASES = {};
+    /**
+     * The full-text indexes that have been loaded, by crate name. Each one is an object like
+     * `{offset, words, items}`, where `offset` is the position of the crate in `searchIndex`.
+     */
+    var fullTextIndexes = {};
+    var fullTextRequested = {};
+    var FULL_TEXT_STOP_WORDS = [
+        "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "if", "in", "is", "it",
+        "of", "on", "or", "that", "the", "this", "to", "was", "with",
+    ];
     var params = searchState.getQueryStringParams();
 
     // Populate search bar with query string search term when provided,
@@ -938,7 +948,8 @@ window.initSearch = function(rawSearchIndex) {
     }
 
     function nextTab(direction) {
-        var next = (searchState.currentTab + direction + 3) % searchState.focusedByTab.length;
+        var nbTabs = searchState.focusedByTab.length;
+        var next = (searchState.currentTab + direction + nbTabs) % nbTabs;
         searchState.focusedByTab[searchState.currentTab] = document.activeElement;
         printTab(next);
         focusSearchResult();
@@ -1021,6 +1032,222 @@ window.initSearch = function(rawSearchIndex) {
         return tmp;
     }
 
+    /**
+     * Called by the `search-fulltext.js` file of a crate once it has been loaded.
+     *
+     * @param {string} crate
+     * @param {{w: Array<string>, i: Array<Array<Number>>}} data
+     */
+    window.registerFullTextIndex = function(crate, data) {
+        var offset = -1;
+        for (var i = 0, len = searchIndex.length; i < len; ++i) {
+            if (searchIndex[i].crate === crate) {
+                offset = i;
+                break;
+            }
+        }
+        if (offset === -1) {
+            return;
+        }
+        fullTextIndexes[crate] = {offset: offset, words: data.w, items: data.i};
+        // Update the results that are displayed, if any.
+        if (searchState.input.value.trim() !== "" &&
+            !hasClass(searchState.outputElement(), "hidden"))
+        {
+            search(undefined, true);
+        }
+    };
+
+    /**
+     * Returns true if at least one of the crates has a full-text index.
+     *
+     * @return {boolean}
+     */
+    function hasFullTextSearch() {
+        for (var crate in rawSearchIndex) {
+            if (hasOwnPropertyRustdoc(rawSearchIndex, crate) && rawSearchIndex[crate].ft) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    /**
+     * Starts loading the full-text indexes that are needed to search in `filterCrates`. They're
+     * much bigger than the search index, so they're only loaded once they're needed.
+     *
+     * @param {string|null} filterCrates
+     */
+    function loadFullTextIndexes(filterCrates) {
+        for (var crate in rawSearchIndex) {
+            if (!hasOwnPropertyRustdoc(rawSearchIndex, crate) ||
+                !rawSearchIndex[crate].ft ||
+                (filterCrates !== null && crate !== filterCrates) ||
+                fullTextRequested[crate])
+            {
+                continue;
+            }
+            fullTextRequested[crate] = true;
+            var script = document.createElement("script");
+            script.src = window.rootPath + crate + "/search-fulltext.js";
+            document.head.append(script);
+        }
+    }
+
+    /**
+     * Splits `text` into lowercase words, without the stop words. This has to match the way
+     * rustdoc splits the documentation into words (see `search_index/full_text.rs`).
+     *
+     * @param {string} text
+     * @return {Array<string>}
+     */
+    function fullTextWords(text) {
+        return text.toLowerCase().split(/[\s!-\/:-@\[-`{-~]+/).filter(function(word) {
+            return word !== "" && FULL_TEXT_STOP_WORDS.indexOf(word) === -1;
+        });
+    }
+
+    /**
+     * Strips the most common English suffixes from `word`, exactly like rustdoc does when it
+     * builds the full-text index.
+     *
+     * @param {string} word
+     * @return {string}
+     */
+    function fullTextStem(word) {
+        if (word.length <= 3 || !/^[\x00-\x7f]*$/.test(word)) {
+            return word;
+        }
+        var stem = word;
+        if (word.endsWith("ies")) {
+            stem = word.slice(0, -3) + "y";
+        } else if (word.endsWith("sses")) {
+            stem = word.slice(0, -2);
+        } else if (word.endsWith("s") && !word.endsWith("ss") && !word.endsWith("us") &&
+                   !word.endsWith("is")) {
+            stem = word.slice(0, -1);
+        }
+
+        var suffix = stem.endsWith("ing") ? 3 : (stem.endsWith("ed") ? 2 : 0);
+        if (stem.endsWith("ation") && stem.length - 5 >= 3) {
+            stem = stem.slice(0, -5) + "ate";
+        } else if (stem.endsWith("ied") && stem.length - 3 >= 2) {
+            stem = stem.slice(0, -3) + "y";
+        } else if (suffix > 0 && stem.length - suffix >= 3) {
+            stem = stem.slice(0, -suffix);
+            var last = stem[stem.length - 1];
+            if (last === stem[stem.length - 2] && "aeioulsz".indexOf(last) === -1) {
+                stem = stem.slice(0, -1);
+            }
+        } else if (stem.endsWith("ly") && stem.length - 2 >= 3) {
+            stem = stem.slice(0, -2);
+        }
+
+        if (stem.length > 3 && stem.endsWith("e")) {
+            stem = stem.slice(0, -1);
+        }
+        return stem;
+    }
+
+    /**
+     * Returns the positions of the words of `index` that are equal to `word`, or that start
+     * with it if `prefix` is true.
+     *
+     * @param {{words: Array<string>}} index
+     * @param {string}                 word
+     * @param {boolean}                prefix
+     * @return {Array<Number>}
+     */
+    function findFullTextWords(index, word, prefix) {
+        var words = index.words;
+        var low = 0;
+        var high = words.length;
+        while (low < high) {
+            var mid = (low + high) >> 1;
+            if (words[mid] < word) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        var found = [];
+        for (var i = low; i < words.length; ++i) {
+            if (words[i] === word || (prefix && words[i].startsWith(word))) {
+                found.push(i);
+            } else {
+                break;
+            }
+        }
+        return found;
+    }
+
+    /**
+     * Searches for the items whose documentation contains the words of `query`. Items that
+     * contain more of the words come first, then the ones that contain the rarest words. The
+     * last word also matches the words that start with it, since it may not be finished yet.
+     *
+     * @param {string}      query
+     * @param {string|null} filterCrates
+     * @return {Array<?>}
+     */
+    function searchFullText(query, filterCrates) {
+        var words = fullTextWords(query);
+        var scores = {};
+        var matches = [];
+        for (var crate in fullTextIndexes) {
+            if (!hasOwnPropertyRustdoc(fullTextIndexes, crate) ||
+                (filterCrates !== null && crate !== filterCrates))
+            {
+                continue;
+            }
+            var index = fullTextIndexes[crate];
+            for (var i = 0, len = words.length; i < len; ++i) {
+                var isLast = i === len - 1;
+                var positions = findFullTextWords(index, fullTextStem(words[i]), false);
+                if (isLast) {
+                    positions = positions.concat(findFullTextWords(index, words[i], true));
+                }
+                // An item is only counted once for each word of the query.
+                var items = {};
+                positions.forEach(function(pos) {
+                    index.items[pos].forEach(function(item) {
+                        items[index.offset + item] = true;
+                    });
+                });
+                var ids = Object.keys(items);
+                var idf = Math.log(searchIndex.length / ids.length) + 1;
+                ids.forEach(function(id) {
+                    if (scores[id] === undefined) {
+                        scores[id] = {id: id, words: 0, idf: 0};
+                        matches.push(scores[id]);
+                    }
+                    scores[id].words += 1;
+                    scores[id].idf += idf;
+                });
+            }
+        }
+        matches.sort(function(a, b) {
+            if (a.words !== b.words) {
+                return b.words - a.words;
+            }
+            return b.idf - a.idf;
+        });
+
+        var out = [];
+        for (i = 0, len = matches.length; i < len && out.length < MAX_RESULTS; ++i) {
+            var obj = searchIndex[matches[i].id];
+            var res = buildHrefAndPath(obj);
+            out.push({
+                ty: obj.ty,
+                name: obj.name,
+                desc: obj.desc,
+                displayPath: pathSplitter(res[0]),
+                href: res[1],
+            });
+        }
+        return out;
+    }
+
     /**
      * Render a set of search results for a single tab.
      * @param {Array<?>}    array   - The search results for this tab
@@ -1140,19 +1367,28 @@ window.initSearch = function(rawSearchIndex) {
         var ret_in_args = addTab(results.in_args, query, false);
         var ret_returned = addTab(results.returned, query, false);
 
+        // The "In Documentation" tab is only there if full-text search is enabled.
+        var ret_docs = results.docs !== undefined ? addTab(results.docs, query, false) : null;
+
         // Navigate to the relevant tab if the current tab is empty, like in case users search
         // for "-> String". If they had selected another tab previously, they have to click on
         // it again.
         var currentTab = searchState.currentTab;
+        if (currentTab === 3 && ret_docs === null) {
+            currentTab = 0;
+        }
         if ((currentTab === 0 && ret_others[1] === 0) ||
                 (currentTab === 1 && ret_in_args[1] === 0) ||
-                (currentTab === 2 && ret_returned[1] === 0)) {
+                (currentTab === 2 && ret_returned[1] === 0) ||
+                (currentTab === 3 && ret_docs[1] === 0)) {
             if (ret_others[1] !== 0) {
                 currentTab = 0;
             } else if (ret_in_args[1] !== 0) {
                 currentTab = 1;
             } else if (ret_returned[1] !== 0) {
                 currentTab = 2;
+            } else if (ret_docs !== null && ret_docs[1] !== 0) {
+                currentTab = 3;
             }
         }
 
@@ -1168,10 +1404,11 @@ window.initSearch = function(rawSearchIndex) {
             <h1 class="search-results-title">Results for ${escape(query.query)} ` +
             (query.type ? " (type: " + escape(query.type) + ")" : "") + "</h1>" +
             crates +
-            `</div><div id="titles">` +
+            `</div><div id="titles"` + (ret_docs !== null ? ` class="full-text">` : ">") +
             makeTabHeader(0, "In Names", ret_others[1]) +
             makeTabHeader(1, "In Parameters", ret_in_args[1]) +
             makeTabHeader(2, "In Return Types", ret_returned[1]) +
+            (ret_docs !== null ? makeTabHeader(3, "In Documentation", ret_docs[1]) : "") +
             "</div>";
 
         var resultsElem = document.createElement("div");
@@ -1179,6 +1416,9 @@ window.initSearch = function(rawSearchIndex) {
         resultsElem.appendChild(ret_others[0]);
         resultsElem.appendChild(ret_in_args[0]);
         resultsElem.appendChild(ret_returned[0]);
+        if (ret_docs !== null) {
+            resultsElem.appendChild(ret_docs[0]);
+        }
 
         search.innerHTML = output;
         let crateSearch = document.getElementById("crate-search");
@@ -1188,11 +1428,17 @@ window.initSearch = function(rawSearchIndex) {
         search.appendChild(resultsElem);
         // Reset focused elements.
         searchState.focusedByTab = [null, null, null];
+        if (ret_docs !== null) {
+            searchState.focusedByTab.push(null);
+        }
         searchState.showResults(search);
         var elems = document.getElementById("titles").childNodes;
         elems[0].onclick = function() { printTab(0); };
         elems[1].onclick = function() { printTab(1); };
         elems[2].onclick = function() { printTab(2); };
+        if (ret_docs !== null) {
+            elems[3].onclick = function() { printTab(3); };
+        }
         printTab(currentTab);
     }
 
@@ -1265,8 +1511,13 @@ window.initSearch = function(rawSearchIndex) {
             }
         }
 
-        showResults(execSearch(query, searchWords, filterCrates),
-            params["go_to_first"], filterCrates);
+        var results = execSearch(query, searchWords, filterCrates);
+        if (hasFullTextSearch()) {
+            // The results are updated once the indexes are loaded.
+            loadFullTextIndexes(filterCrates);
+            results.docs = searchFullText(query.raw, filterCrates);
+        }
+        showResults(results, params["go_to_first"], filterCrates);
     }
 
     function buildIndex(rawSearchIndex) {
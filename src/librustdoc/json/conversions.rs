@@ -11,6 +11,7 @@
 use rustc_hir::{def::CtorKind, def_id::DefId};
 use rustc_middle::ty::{self, TyCtxt};
 use rustc_span::def_id::CRATE_DEF_INDEX;
+use rustc_span::symbol::sym;
 use rustc_span::Pos;
 use rustc_target::spec::abi::Abi as RustcAbi;
 
@@ -39,6 +40,14 @@ pub(super) fn convert_item(&self, item: clean::Item) -> Option<Item> {
             .iter()
             .map(rustc_ast_pretty::pprust::attribute_to_string)
             .collect();
+        let stability = item.stability(self.tcx);
+        let other_attrs = &item.attrs.other_attrs;
+        let must_use = other_attrs
+            .iter()
+            .find(|attr| attr.has_name(sym::must_use))
+            .map(|attr| MustUse { reason: attr.value_str().map(|s| s.to_string()) });
+        let track_caller = other_attrs.iter().any(|attr| attr.has_name(sym::track_caller));
+        let cfg = item.cfg.as_deref().map(from_cfg);
         let span = item.span(self.tcx);
         let clean::Item { name, attrs: _, kind: _, visibility, def_id, cfg: _ } = item;
         let inner = match *item.kind {
@@ -54,6 +63,10 @@ pub(super) fn convert_item(&self, item: clean::Item) -> Option<Item> {
             docs,
             attrs,
             deprecation: deprecation.map(from_deprecation),
+            stability: stability.map(from_stability),
+            must_use,
+            track_caller,
+            cfg,
             inner,
             links,
         })
@@ -115,6 +128,35 @@ fn into_tcx(self, tcx: TyCtxt<'_>) -> U {
     Deprecation { since: since.map(|s| s.to_string()), note: note.map(|s| s.to_string()) }
 }
 
+crate fn from_stability(stability: rustc_attr::Stability) -> Stability {
+    let rustc_attr::Stability { level, feature } = stability;
+    let level = match level {
+        rustc_attr::StabilityLevel::Stable { since } => {
+            StabilityLevel::Stable { since: since.to_string() }
+        }
+        rustc_attr::StabilityLevel::Unstable { reason, issue, is_soft: _ } => {
+            StabilityLevel::Unstable {
+                reason: reason.map(|s| s.to_string()),
+                issue: issue.map(|issue| issue.get()),
+            }
+        }
+    };
+    Stability { level, feature: feature.to_string() }
+}
+
+crate fn from_cfg(cfg: &clean::cfg::Cfg) -> Cfg {
+    match cfg {
+        clean::cfg::Cfg::True => Cfg::True,
+        clean::cfg::Cfg::False => Cfg::False,
+        clean::cfg::Cfg::Cfg(name, value) => {
+            Cfg::Cfg { name: name.to_string(), value: value.map(|s| s.to_string()) }
+        }
+        clean::cfg::Cfg::Not(cfg) => Cfg::Not(Box::new(from_cfg(cfg))),
+        clean::cfg::Cfg::Any(cfgs) => Cfg::Any(cfgs.iter().map(from_cfg).collect()),
+        clean::cfg::Cfg::All(cfgs) => Cfg::All(cfgs.iter().map(from_cfg).collect()),
+    }
+}
+
 impl FromWithTcx<clean::GenericArgs> for GenericArgs {
     fn from_tcx(args: clean::GenericArgs, tcx: TyCtxt<'_>) -> Self {
         use clean::GenericArgs::*;
@@ -0,0 +1,896 @@
+//! Comparison of the public API of two versions of a crate, as described by their JSON output.
+//!
+//! Items are matched by the paths they can be reached by from the root of the crate, since the
+//! IDs of items change between versions. Every public path of an item is part of the API, so
+//! removing a re-export is reported just like removing the item itself.
+//!
+//! Whether a change breaks code using the crate is only a guess: it follows the usual semver rules
+//! for Rust, but can't know about sealed traits or about how the items are actually used.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustdoc_json_types::{
+    Abi, Crate, FnDecl, GenericArg, GenericArgs, GenericBound, GenericParamDefKind, Generics,
+    Header, Id, Impl, Item, ItemEnum, MacroKind, StructType, Term, TraitBoundModifier, Type,
+    TypeBindingKind, Variant, Visibility, WherePredicate, FORMAT_VERSION,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A difference between the public APIs of two versions of a crate.
+#[derive(Debug, PartialEq)]
+crate struct Change {
+    /// The path of the item that changed.
+    crate path: String,
+    /// The kind of the item, like `struct` or `method`.
+    crate kind: &'static str,
+    crate change: ChangeKind,
+    /// Whether the change is likely to break code that uses the crate.
+    crate breaking: bool,
+}
+
+#[derive(Debug, PartialEq)]
+crate enum ChangeKind {
+    Added,
+    Removed,
+    /// The item is now a different kind of item.
+    Kind {
+        old: &'static str,
+    },
+    Signature {
+        old: String,
+        new: String,
+    },
+    /// The value of a constant changed.
+    Value {
+        old: String,
+        new: String,
+    },
+    /// The definition of a macro changed.
+    Definition,
+    /// A function gained or lost `const`.
+    Const(bool),
+    Deprecated,
+    NonExhaustive(bool),
+    /// A struct whose fields were all public gained private fields.
+    PrivateFields,
+    /// An item of a trait lost or gained its default.
+    Required(bool),
+    ImplAdded(String),
+    ImplRemoved(String),
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, path) = (self.kind, &self.path);
+        match self.change {
+            ChangeKind::Added => write!(f, "added {} `{}`", kind, path),
+            ChangeKind::Removed => write!(f, "removed {} `{}`", kind, path),
+            ChangeKind::Kind { old } => write!(f, "changed `{}` from {} to {}", path, old, kind),
+            ChangeKind::Signature { ref old, ref new } => {
+                write!(f, "changed signature of {} `{}`", kind, path)?;
+                write!(f, "\n    old: {}\n    new: {}", old, new)
+            }
+            ChangeKind::Value { ref old, ref new } => {
+                write!(f, "changed value of {} `{}`", kind, path)?;
+                write!(f, "\n    old: {}\n    new: {}", old, new)
+            }
+            ChangeKind::Definition => write!(f, "changed definition of {} `{}`", kind, path),
+            ChangeKind::Const(true) => write!(f, "made {} `{}` const", kind, path),
+            ChangeKind::Const(false) => write!(f, "{} `{}` is no longer const", kind, path),
+            ChangeKind::Deprecated => write!(f, "deprecated {} `{}`", kind, path),
+            ChangeKind::NonExhaustive(true) => {
+                write!(f, "made {} `{}` non-exhaustive", kind, path)
+            }
+            ChangeKind::NonExhaustive(false) => {
+                write!(f, "{} `{}` is no longer non-exhaustive", kind, path)
+            }
+            ChangeKind::PrivateFields => write!(f, "{} `{}` now has private fields", kind, path),
+            ChangeKind::Required(true) => write!(f, "{} `{}` no longer has a default", kind, path),
+            ChangeKind::Required(false) => write!(f, "{} `{}` now has a default", kind, path),
+            ChangeKind::ImplAdded(ref impl_) => write!(f, "added `{}`", impl_),
+            ChangeKind::ImplRemoved(ref impl_) => write!(f, "removed `{}`", impl_),
+        }
+    }
+}
+
+/// An item that is part of the public API.
+struct ApiItem<'a> {
+    item: &'a Item,
+    kind: &'static str,
+    /// The path of the item this one is a member of, like the struct of a field.
+    parent: Option<String>,
+}
+
+/// The public API of a crate.
+struct Api<'a> {
+    krate: &'a Crate,
+    /// All public items, by path.
+    items: BTreeMap<String, ApiItem<'a>>,
+    /// The trait implementations of all public types, by the path of the type.
+    impls: BTreeMap<String, Vec<&'a Impl>>,
+    /// The first public path found for every item, used to print types that refer to them.
+    paths: FxHashMap<&'a Id, String>,
+    /// The modules that have been walked already, since glob imports can form cycles.
+    visited: FxHashSet<&'a Id>,
+}
+
+impl<'a> Api<'a> {
+    fn new(krate: &'a Crate) -> Api<'a> {
+        let mut api = Api {
+            krate,
+            items: BTreeMap::new(),
+            impls: BTreeMap::new(),
+            paths: FxHashMap::default(),
+            visited: FxHashSet::default(),
+        };
+        if let Some(root) = krate.index.get(&krate.root) {
+            let name = root.name.clone().unwrap_or_default();
+            api.paths.insert(&krate.root, name.clone());
+            api.add_module(&krate.root, &name);
+        }
+        api
+    }
+
+    /// Adds the public items of the module `id` as if they were in the module at `path`.
+    fn add_module(&mut self, id: &'a Id, path: &str) {
+        let module = match self.krate.index.get(id).map(|item| &item.inner) {
+            Some(ItemEnum::Module(module)) => module,
+            _ => return,
+        };
+        if !self.visited.insert(id) {
+            return;
+        }
+        for id in &module.items {
+            let item = match self.krate.index.get(id) {
+                Some(item) => item,
+                None => continue,
+            };
+            // `macro_rules!` macros are public if they're exported, whatever their visibility.
+            if item.visibility != Visibility::Public && !matches!(item.inner, ItemEnum::Macro(_)) {
+                continue;
+            }
+            match item.inner {
+                ItemEnum::Import(ref import) => {
+                    let target =
+                        import.id.as_ref().and_then(|id| Some((id, self.krate.index.get(id)?)));
+                    match target {
+                        Some((id, _)) if import.glob => self.add_module(id, path),
+                        Some((id, target)) => {
+                            self.add_item(id, target, format!("{}::{}", path, import.name), None)
+                        }
+                        // Re-exports of items from other crates are only compared by their source.
+                        None if !import.glob => {
+                            let path = format!("{}::{}", path, import.name);
+                            self.insert(path, item, None);
+                        }
+                        None => {}
+                    }
+                }
+                ItemEnum::ExternCrate { ref name, ref rename } => {
+                    let path = format!("{}::{}", path, rename.as_ref().unwrap_or(name));
+                    self.insert(path, item, None);
+                }
+                _ => {
+                    if let Some(name) = &item.name {
+                        self.add_item(id, item, format!("{}::{}", path, name), None);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds `item` and its members at `path`.
+    fn add_item(&mut self, id: &'a Id, item: &'a Item, path: String, parent: Option<&str>) {
+        if self.items.contains_key(&path) {
+            return;
+        }
+        self.insert(path.clone(), item, parent);
+        if self.paths.contains_key(id) {
+            // The members of re-exported items are only added at their first path.
+            if let ItemEnum::Module(_) = item.inner {
+                self.add_module(id, &path);
+            }
+            return;
+        }
+        self.paths.insert(id, path.clone());
+
+        match item.inner {
+            ItemEnum::Module(_) => self.add_module(id, &path),
+            ItemEnum::Struct(ref s) => {
+                self.add_members(&s.fields, &path, true);
+                self.add_impls(&s.impls, &path);
+            }
+            ItemEnum::Union(ref u) => {
+                self.add_members(&u.fields, &path, true);
+                self.add_impls(&u.impls, &path);
+            }
+            ItemEnum::Enum(ref e) => {
+                self.add_members(&e.variants, &path, false);
+                self.add_impls(&e.impls, &path);
+            }
+            ItemEnum::Variant(Variant::Struct(ref fields)) => {
+                self.add_members(fields, &path, false);
+            }
+            ItemEnum::Trait(ref t) => self.add_members(&t.items, &path, false),
+            _ => {}
+        }
+    }
+
+    /// Adds the members `ids` of the item at `path`. If `public_only` is set, members that
+    /// aren't `pub` are skipped; trait items and variants are public without being marked `pub`.
+    fn add_members(&mut self, ids: &'a [Id], path: &str, public_only: bool) {
+        for id in ids {
+            if let Some(item) = self.krate.index.get(id) {
+                if public_only && item.visibility != Visibility::Public {
+                    continue;
+                }
+                if let Some(name) = &item.name {
+                    self.add_item(id, item, format!("{}::{}", path, name), Some(path));
+                }
+            }
+        }
+    }
+
+    /// Adds the items of the inherent impls of the type at `path`, and remembers its trait impls.
+    fn add_impls(&mut self, ids: &'a [Id], path: &str) {
+        for id in ids {
+            let impl_ = match self.krate.index.get(id).map(|item| &item.inner) {
+                Some(ItemEnum::Impl(impl_)) => impl_,
+                _ => continue,
+            };
+            if impl_.trait_.is_some() {
+                // Blanket impls follow from other impls, so they don't need to be compared.
+                if impl_.blanket_impl.is_none() {
+                    self.impls.entry(path.to_owned()).or_default().push(impl_);
+                }
+            } else {
+                self.add_members(&impl_.items, path, true);
+            }
+        }
+    }
+
+    fn insert(&mut self, path: String, item: &'a Item, parent: Option<&str>) {
+        let kind = kind(item);
+        self.items.insert(path, ApiItem { item, kind, parent: parent.map(str::to_owned) });
+    }
+
+    /// Returns whether `member`, a member of `parent`, can't be added without breaking code.
+    fn is_required(&self, parent: &ApiItem<'_>, member: &ApiItem<'_>) -> bool {
+        match (&parent.item.inner, &member.item.inner) {
+            // Adding a field breaks struct expressions and patterns, unless there are private
+            // fields already.
+            (ItemEnum::Struct(s), ItemEnum::StructField(_)) => {
+                !s.fields_stripped && !is_non_exhaustive(parent.item)
+            }
+            (ItemEnum::Variant(_), ItemEnum::StructField(_)) => {
+                let enum_ = parent.parent.as_ref().and_then(|path| self.items.get(path));
+                !is_non_exhaustive(parent.item)
+                    && !enum_.map_or(false, |e| is_non_exhaustive(e.item))
+            }
+            // Adding a variant breaks exhaustive matches.
+            (ItemEnum::Enum(_), ItemEnum::Variant(_)) => !is_non_exhaustive(parent.item),
+            // Adding an item without a default breaks implementations of the trait.
+            (ItemEnum::Trait(_), _) => has_no_default(member.item),
+            _ => false,
+        }
+    }
+
+    fn is_trait_item(&self, item: &ApiItem<'_>) -> bool {
+        let parent = item.parent.as_ref().and_then(|path| self.items.get(path));
+        matches!(parent.map(|p| &p.item.inner), Some(ItemEnum::Trait(_)))
+    }
+
+    /// Prints the declaration of `item` without its body, or returns `None` if it doesn't have
+    /// one that can be compared.
+    fn signature(&self, item: &Item) -> Option<String> {
+        let name = item.name.as_deref().unwrap_or("_");
+        Some(match item.inner {
+            ItemEnum::Module(_) | ItemEnum::PrimitiveType(_) | ItemEnum::Impl(_) => return None,
+            ItemEnum::ExternCrate { ref name, ref rename } => match rename {
+                Some(rename) => format!("extern crate {} as {}", name, rename),
+                None => format!("extern crate {}", name),
+            },
+            ItemEnum::Import(ref import) => {
+                if import.source.rsplit("::").next() == Some(&import.name[..]) {
+                    format!("use {}", import.source)
+                } else {
+                    format!("use {} as {}", import.source, import.name)
+                }
+            }
+            ItemEnum::Struct(ref s) => {
+                let generics = self.generics(&s.generics);
+                let where_clause = self.where_clause(&s.generics);
+                match s.struct_type {
+                    StructType::Plain => {
+                        format!("struct {}{}{} {{ .. }}", name, generics, where_clause)
+                    }
+                    StructType::Tuple => format!("struct {}{}(..){}", name, generics, where_clause),
+                    StructType::Unit => format!("struct {}{}{}", name, generics, where_clause),
+                }
+            }
+            ItemEnum::Union(ref u) => {
+                format!(
+                    "union {}{}{}",
+                    name,
+                    self.generics(&u.generics),
+                    self.where_clause(&u.generics)
+                )
+            }
+            ItemEnum::Enum(ref e) => {
+                format!(
+                    "enum {}{}{}",
+                    name,
+                    self.generics(&e.generics),
+                    self.where_clause(&e.generics)
+                )
+            }
+            ItemEnum::Variant(ref v) => match v {
+                Variant::Plain => name.to_owned(),
+                Variant::Tuple(types) => format!("{}({})", name, self.types(types)),
+                Variant::Struct(_) => format!("{} {{ .. }}", name),
+            },
+            ItemEnum::StructField(ref ty) => format!("{}: {}", name, self.ty(ty)),
+            ItemEnum::Function(ref f) => self.function(name, &f.header, &f.generics, &f.decl),
+            ItemEnum::Method(ref m) => self.function(name, &m.header, &m.generics, &m.decl),
+            ItemEnum::Trait(ref t) => format!(
+                "{}{}trait {}{}{}{}",
+                if t.is_unsafe { "unsafe " } else { "" },
+                if t.is_auto { "auto " } else { "" },
+                name,
+                self.generics(&t.generics),
+                if t.bounds.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", self.bounds(&t.bounds))
+                },
+                self.where_clause(&t.generics),
+            ),
+            ItemEnum::TraitAlias(ref t) => format!(
+                "trait {}{}{} = {}",
+                name,
+                self.generics(&t.generics),
+                self.where_clause(&t.generics),
+                self.bounds(&t.params),
+            ),
+            ItemEnum::Typedef(ref t) => format!(
+                "type {}{}{} = {}",
+                name,
+                self.generics(&t.generics),
+                self.where_clause(&t.generics),
+                self.ty(&t.type_),
+            ),
+            ItemEnum::OpaqueTy(ref t) => format!(
+                "type {}{}{} = impl {}",
+                name,
+                self.generics(&t.generics),
+                self.where_clause(&t.generics),
+                self.bounds(&t.bounds),
+            ),
+            ItemEnum::Constant(ref c) => format!("const {}: {}", name, self.ty(&c.type_)),
+            ItemEnum::Static(ref s) => format!(
+                "static {}{}: {}",
+                if s.mutable { "mut " } else { "" },
+                name,
+                self.ty(&s.type_),
+            ),
+            ItemEnum::ForeignType => format!("type {}", name),
+            ItemEnum::Macro(_) => format!("macro_rules! {}", name),
+            ItemEnum::ProcMacro(ref m) => match m.kind {
+                MacroKind::Bang => format!("#[proc_macro] {}", name),
+                MacroKind::Attr => format!("#[proc_macro_attribute] {}", name),
+                MacroKind::Derive if m.helpers.is_empty() => {
+                    format!("#[proc_macro_derive({})]", name)
+                }
+                MacroKind::Derive => {
+                    format!("#[proc_macro_derive({}, attributes({}))]", name, m.helpers.join(", "))
+                }
+            },
+            ItemEnum::AssocConst { ref type_, .. } => format!("const {}: {}", name, self.ty(type_)),
+            ItemEnum::AssocType { ref bounds, .. } if bounds.is_empty() => format!("type {}", name),
+            ItemEnum::AssocType { ref bounds, .. } => {
+                format!("type {}: {}", name, self.bounds(bounds))
+            }
+        })
+    }
+
+    /// Prints a function or method. Whether it's `const` isn't included, since it's compared
+    /// separately.
+    fn function(&self, name: &str, header: &Header, generics: &Generics, decl: &FnDecl) -> String {
+        let Header { const_: _, unsafe_, async_, ref abi } = *header;
+        format!(
+            "{}{}{}fn {}{}{}{}",
+            if async_ { "async " } else { "" },
+            if unsafe_ { "unsafe " } else { "" },
+            print_abi(abi),
+            name,
+            self.generics(generics),
+            self.fn_decl(decl),
+            self.where_clause(generics),
+        )
+    }
+
+    fn fn_decl(&self, decl: &FnDecl) -> String {
+        let mut inputs: Vec<_> = decl
+            .inputs
+            .iter()
+            .map(|(name, ty)| match ty {
+                Type::Generic(s) if name == "self" && s == "Self" => "self".to_owned(),
+                Type::BorrowedRef { lifetime, mutable, type_ }
+                    if name == "self" && **type_ == Type::Generic("Self".to_owned()) =>
+                {
+                    format!(
+                        "&{}{}self",
+                        print_lifetime(lifetime),
+                        if *mutable { "mut " } else { "" }
+                    )
+                }
+                _ => format!("{}: {}", name, self.ty(ty)),
+            })
+            .collect();
+        if decl.c_variadic {
+            inputs.push("...".to_owned());
+        }
+        match decl.output {
+            Some(ref output) => format!("({}) -> {}", inputs.join(", "), self.ty(output)),
+            None => format!("({})", inputs.join(", ")),
+        }
+    }
+
+    fn generics(&self, generics: &Generics) -> String {
+        let params: Vec<_> = generics
+            .params
+            .iter()
+            // Skip the parameters that are created for `impl Trait` arguments.
+            .filter(|param| !param.name.starts_with("impl "))
+            .map(|param| match param.kind {
+                GenericParamDefKind::Lifetime { ref outlives } if outlives.is_empty() => {
+                    param.name.clone()
+                }
+                GenericParamDefKind::Lifetime { ref outlives } => {
+                    format!("{}: {}", param.name, outlives.join(" + "))
+                }
+                GenericParamDefKind::Type { ref bounds, ref default } => {
+                    let mut s = param.name.clone();
+                    if !bounds.is_empty() {
+                        s = format!("{}: {}", s, self.bounds(bounds));
+                    }
+                    if let Some(default) = default {
+                        s = format!("{} = {}", s, self.ty(default));
+                    }
+                    s
+                }
+                GenericParamDefKind::Const { ref ty, ref default } => match default {
+                    Some(default) => format!("const {}: {} = {}", param.name, self.ty(ty), default),
+                    None => format!("const {}: {}", param.name, self.ty(ty)),
+                },
+            })
+            .collect();
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        }
+    }
+
+    fn where_clause(&self, generics: &Generics) -> String {
+        if generics.where_predicates.is_empty() {
+            return String::new();
+        }
+        let predicates: Vec<_> = generics
+            .where_predicates
+            .iter()
+            .map(|pred| match pred {
+                WherePredicate::BoundPredicate { ty, bounds } => {
+                    format!("{}: {}", self.ty(ty), self.bounds(bounds))
+                }
+                WherePredicate::RegionPredicate { lifetime, bounds } => {
+                    format!("{}: {}", lifetime, self.bounds(bounds))
+                }
+                WherePredicate::EqPredicate { lhs, rhs } => {
+                    format!("{} = {}", self.ty(lhs), self.term(rhs))
+                }
+            })
+            .collect();
+        format!(" where {}", predicates.join(", "))
+    }
+
+    fn bounds(&self, bounds: &[GenericBound]) -> String {
+        bounds.iter().map(|bound| self.bound(bound)).collect::<Vec<_>>().join(" + ")
+    }
+
+    fn bound(&self, bound: &GenericBound) -> String {
+        match bound {
+            GenericBound::TraitBound { trait_, generic_params, modifier } => {
+                let modifier = match modifier {
+                    TraitBoundModifier::None => "",
+                    TraitBoundModifier::Maybe => "?",
+                    TraitBoundModifier::MaybeConst => "~const ",
+                };
+                let params: Vec<_> = generic_params.iter().map(|p| p.name.as_str()).collect();
+                let for_prefix = if params.is_empty() {
+                    String::new()
+                } else {
+                    format!("for<{}> ", params.join(", "))
+                };
+                format!("{}{}{}", for_prefix, modifier, self.path(trait_))
+            }
+            GenericBound::Outlives(lifetime) => lifetime.clone(),
+        }
+    }
+
+    /// Prints a path to an item. Paths to items of the crate are printed as the path the item was
+    /// first found at, so that moving an item without changing its public path isn't a change.
+    fn path(&self, ty: &Type) -> String {
+        match ty {
+            Type::ResolvedPath { name, id, args, .. } => {
+                let path = match self.paths.get(id) {
+                    Some(path) => path.clone(),
+                    None => match self.krate.paths.get(id) {
+                        Some(summary) => summary.path.join("::"),
+                        None => name.clone(),
+                    },
+                };
+                match args {
+                    Some(args) => format!("{}{}", path, self.generic_args(args)),
+                    None => path,
+                }
+            }
+            _ => self.ty(ty),
+        }
+    }
+
+    fn generic_args(&self, args: &GenericArgs) -> String {
+        match args {
+            GenericArgs::AngleBracketed { args, bindings } => {
+                let args = args.iter().map(|arg| match arg {
+                    GenericArg::Lifetime(lifetime) => lifetime.clone(),
+                    GenericArg::Type(ty) => self.ty(ty),
+                    GenericArg::Const(c) => c.expr.clone(),
+                    GenericArg::Infer => "_".to_owned(),
+                });
+                let bindings = bindings.iter().map(|binding| match binding.binding {
+                    TypeBindingKind::Equality(ref term) => {
+                        format!("{} = {}", binding.name, self.term(term))
+                    }
+                    TypeBindingKind::Constraint(ref bounds) => {
+                        format!("{}: {}", binding.name, self.bounds(bounds))
+                    }
+                });
+                let args: Vec<_> = args.chain(bindings).collect();
+                if args.is_empty() {
+                    String::new()
+                } else {
+                    format!("<{}>", args.join(", "))
+                }
+            }
+            GenericArgs::Parenthesized { inputs, output } => match output {
+                Some(output) => format!("({}) -> {}", self.types(inputs), self.ty(output)),
+                None => format!("({})", self.types(inputs)),
+            },
+        }
+    }
+
+    fn term(&self, term: &Term) -> String {
+        match term {
+            Term::Type(ty) => self.ty(ty),
+            Term::Constant(c) => c.expr.clone(),
+        }
+    }
+
+    fn types(&self, types: &[Type]) -> String {
+        types.iter().map(|ty| self.ty(ty)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn ty(&self, ty: &Type) -> String {
+        match ty {
+            Type::ResolvedPath { id, param_names, .. } => {
+                // Trait objects are paths to traits, with any other bounds in `param_names`.
+                let is_trait = matches!(
+                    self.krate.paths.get(id).map(|summary| &summary.kind),
+                    Some(rustdoc_json_types::ItemKind::Trait)
+                );
+                if !is_trait {
+                    return self.path(ty);
+                }
+                let mut s = format!("dyn {}", self.path(ty));
+                for bound in param_names {
+                    s.push_str(" + ");
+                    s.push_str(&self.bound(bound));
+                }
+                s
+            }
+            Type::Generic(name) | Type::Primitive(name) => name.clone(),
+            Type::FunctionPointer(f) => {
+                let params: Vec<_> = f.generic_params.iter().map(|p| p.name.as_str()).collect();
+                format!(
+                    "{}{}{}fn{}",
+                    if params.is_empty() {
+                        String::new()
+                    } else {
+                        format!("for<{}> ", params.join(", "))
+                    },
+                    if f.header.unsafe_ { "unsafe " } else { "" },
+                    print_abi(&f.header.abi),
+                    self.fn_decl(&f.decl),
+                )
+            }
+            Type::Tuple(types) if types.len() == 1 => format!("({},)", self.ty(&types[0])),
+            Type::Tuple(types) => format!("({})", self.types(types)),
+            Type::Slice(ty) => format!("[{}]", self.ty(ty)),
+            Type::Array { type_, len } => format!("[{}; {}]", self.ty(type_), len),
+            Type::ImplTrait(bounds) => format!("impl {}", self.bounds(bounds)),
+            Type::Infer => "_".to_owned(),
+            Type::RawPointer { mutable, type_ } => {
+                format!("*{} {}", if *mutable { "mut" } else { "const" }, self.ty(type_))
+            }
+            Type::BorrowedRef { lifetime, mutable, type_ } => format!(
+                "&{}{}{}",
+                print_lifetime(lifetime),
+                if *mutable { "mut " } else { "" },
+                self.ty(type_)
+            ),
+            Type::QualifiedPath { name, self_type, trait_ } => {
+                format!("<{} as {}>::{}", self.ty(self_type), self.path(trait_), name)
+            }
+        }
+    }
+
+    fn impl_(&self, impl_: &Impl) -> String {
+        format!(
+            "{}impl{} {}{} for {}{}",
+            if impl_.is_unsafe { "unsafe " } else { "" },
+            self.generics(&impl_.generics),
+            if impl_.negative { "!" } else { "" },
+            impl_.trait_.as_ref().map_or(String::new(), |trait_| self.path(trait_)),
+            self.ty(&impl_.for_),
+            self.where_clause(&impl_.generics),
+        )
+    }
+}
+
+fn print_abi(abi: &Abi) -> String {
+    let (name, unwind) = match *abi {
+        Abi::Rust => return String::new(),
+        Abi::C { unwind } => ("C", unwind),
+        Abi::Cdecl { unwind } => ("cdecl", unwind),
+        Abi::Stdcall { unwind } => ("stdcall", unwind),
+        Abi::Fastcall { unwind } => ("fastcall", unwind),
+        Abi::Aapcs { unwind } => ("aapcs", unwind),
+        Abi::Win64 { unwind } => ("win64", unwind),
+        Abi::SysV64 { unwind } => ("sysv64", unwind),
+        Abi::System { unwind } => ("system", unwind),
+        Abi::Other(ref name) => (&name[..], false),
+    };
+    format!("extern \"{}{}\" ", name, if unwind { "-unwind" } else { "" })
+}
+
+fn print_lifetime(lifetime: &Option<String>) -> String {
+    lifetime.as_ref().map_or(String::new(), |lifetime| format!("{} ", lifetime))
+}
+
+fn kind(item: &Item) -> &'static str {
+    match item.inner {
+        ItemEnum::Module(_) => "module",
+        ItemEnum::ExternCrate { .. } => "extern crate",
+        ItemEnum::Import(_) => "re-export",
+        ItemEnum::Union(_) => "union",
+        ItemEnum::Struct(_) => "struct",
+        ItemEnum::StructField(_) => "field",
+        ItemEnum::Enum(_) => "enum",
+        ItemEnum::Variant(_) => "variant",
+        ItemEnum::Function(_) => "function",
+        ItemEnum::Trait(_) => "trait",
+        ItemEnum::TraitAlias(_) => "trait alias",
+        ItemEnum::Method(_) => "method",
+        ItemEnum::Impl(_) => "impl",
+        ItemEnum::Typedef(_) => "type alias",
+        ItemEnum::OpaqueTy(_) => "opaque type",
+        ItemEnum::Constant(_) => "constant",
+        ItemEnum::Static(_) => "static",
+        ItemEnum::ForeignType => "foreign type",
+        ItemEnum::Macro(_) => "macro",
+        ItemEnum::ProcMacro(ref m) => match m.kind {
+            MacroKind::Bang => "macro",
+            MacroKind::Attr => "attribute macro",
+            MacroKind::Derive => "derive macro",
+        },
+        ItemEnum::PrimitiveType(_) => "primitive",
+        ItemEnum::AssocConst { .. } => "associated constant",
+        ItemEnum::AssocType { .. } => "associated type",
+    }
+}
+
+fn is_non_exhaustive(item: &Item) -> bool {
+    item.attrs.iter().any(|attr| attr == "#[non_exhaustive]")
+}
+
+fn has_no_default(item: &Item) -> bool {
+    match item.inner {
+        ItemEnum::Method(ref m) => !m.has_body,
+        ItemEnum::AssocConst { ref default, .. } => default.is_none(),
+        ItemEnum::AssocType { ref default, .. } => default.is_none(),
+        _ => false,
+    }
+}
+
+fn is_const(item: &Item) -> Option<bool> {
+    match item.inner {
+        ItemEnum::Function(ref f) => Some(f.header.const_),
+        ItemEnum::Method(ref m) => Some(m.header.const_),
+        _ => None,
+    }
+}
+
+/// Compares the public APIs of two versions of a crate.
+crate fn diff(old: &Crate, new: &Crate) -> Vec<Change> {
+    let old = Api::new(old);
+    let new = Api::new(new);
+    let mut changes = Vec::new();
+    let mut push = |path: &str, kind, change, breaking| {
+        changes.push(Change { path: path.to_owned(), kind, change, breaking })
+    };
+
+    for (path, old_item) in &old.items {
+        let new_item = match new.items.get(path) {
+            Some(new_item) => new_item,
+            None => {
+                // Members that are removed with the item they belong to aren't reported.
+                if old_item.parent.as_ref().map_or(true, |parent| new.items.contains_key(parent)) {
+                    push(path, old_item.kind, ChangeKind::Removed, true);
+                }
+                continue;
+            }
+        };
+        let kind = new_item.kind;
+        if old_item.kind != kind {
+            push(path, kind, ChangeKind::Kind { old: old_item.kind }, true);
+            continue;
+        }
+        let (old_item, new_item, is_trait_item) =
+            (old_item.item, new_item.item, new.is_trait_item(new_item));
+
+        if let (Some(old), Some(new)) = (old.signature(old_item), new.signature(new_item)) {
+            if old != new {
+                push(path, kind, ChangeKind::Signature { old, new }, true);
+            }
+        }
+        if let (Some(old), Some(new)) = (is_const(old_item), is_const(new_item)) {
+            if old != new {
+                push(path, kind, ChangeKind::Const(new), !new);
+            }
+        }
+        match (&old_item.inner, &new_item.inner) {
+            (ItemEnum::Constant(old), ItemEnum::Constant(new)) if old.expr != new.expr => {
+                let change = ChangeKind::Value { old: old.expr.clone(), new: new.expr.clone() };
+                push(path, kind, change, false);
+            }
+            (ItemEnum::Macro(old), ItemEnum::Macro(new)) if old != new => {
+                push(path, kind, ChangeKind::Definition, false);
+            }
+            (ItemEnum::Struct(old), ItemEnum::Struct(new))
+                if !old.fields_stripped && new.fields_stripped =>
+            {
+                push(path, kind, ChangeKind::PrivateFields, true);
+            }
+            _ => {}
+        }
+        if old_item.deprecation.is_none() && new_item.deprecation.is_some() {
+            push(path, kind, ChangeKind::Deprecated, false);
+        }
+        let non_exhaustive = is_non_exhaustive(new_item);
+        if is_non_exhaustive(old_item) != non_exhaustive {
+            push(path, kind, ChangeKind::NonExhaustive(non_exhaustive), non_exhaustive);
+        }
+        let required = has_no_default(new_item);
+        if is_trait_item && has_no_default(old_item) != required {
+            push(path, kind, ChangeKind::Required(required), required);
+        }
+    }
+
+    for (path, new_item) in &new.items {
+        if old.items.contains_key(path) {
+            continue;
+        }
+        let breaking = match &new_item.parent {
+            Some(parent) => match old.items.get(parent) {
+                Some(parent) => old.is_required(parent, new_item),
+                // Members that are added with the item they belong to aren't reported.
+                None => continue,
+            },
+            None => false,
+        };
+        push(path, new_item.kind, ChangeKind::Added, breaking);
+    }
+
+    for (path, old_impls) in &old.impls {
+        let (old_item, new_item) = match (old.items.get(path), new.items.get(path)) {
+            (Some(old_item), Some(new_item)) if old_item.kind == new_item.kind => {
+                (old_item, new_item)
+            }
+            _ => continue,
+        };
+        let old_impls: BTreeSet<_> = old_impls.iter().map(|impl_| old.impl_(impl_)).collect();
+        let new_impls: BTreeSet<_> =
+            new.impls.get(path).into_iter().flatten().map(|impl_| new.impl_(impl_)).collect();
+        for impl_ in old_impls.difference(&new_impls) {
+            push(path, old_item.kind, ChangeKind::ImplRemoved(impl_.clone()), true);
+        }
+        for impl_ in new_impls.difference(&old_impls) {
+            push(path, new_item.kind, ChangeKind::ImplAdded(impl_.clone()), false);
+        }
+    }
+    for (path, new_impls) in &new.impls {
+        if old.impls.contains_key(path) || !old.items.contains_key(path) {
+            continue;
+        }
+        let kind = new.items[path].kind;
+        for impl_ in new_impls.iter().map(|impl_| new.impl_(impl_)).collect::<BTreeSet<_>>() {
+            push(path, kind, ChangeKind::ImplAdded(impl_), false);
+        }
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+fn load(path: &Path) -> Result<Crate, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read `{}`: {}", path.display(), e))?;
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("failed to parse `{}`: {}", path.display(), e))?;
+    // Check the version first, since other versions are unlikely to deserialize.
+    match json.get("format_version").and_then(serde_json::Value::as_u64) {
+        Some(version) if version == FORMAT_VERSION as u64 => {}
+        Some(version) => {
+            return Err(format!(
+                "`{}` uses version {} of the JSON format, but only version {} is supported",
+                path.display(),
+                version,
+                FORMAT_VERSION
+            ));
+        }
+        None => return Err(format!("`{}` isn't JSON output of rustdoc", path.display())),
+    }
+    serde_json::from_value(json).map_err(|e| format!("failed to parse `{}`: {}", path.display(), e))
+}
+
+/// Compares the JSON output of two versions of a crate and returns a report of the changes to its
+/// public API.
+crate fn run(old: &Path, new: &Path) -> Result<String, String> {
+    let (old, new) = (load(old)?, load(new)?);
+    let changes = diff(&old, &new);
+
+    let name = new.index.get(&new.root).and_then(|root| root.name.as_deref()).unwrap_or("crate");
+    let mut report = match (&old.crate_version, &new.crate_version) {
+        (Some(old), Some(new)) => format!("API changes to `{}` from {} to {}\n", name, old, new),
+        _ => format!("API changes to `{}`\n", name),
+    };
+    for (breaking, title) in [(true, "Breaking changes"), (false, "Other changes")] {
+        let changes: Vec<_> = changes.iter().filter(|c| c.breaking == breaking).collect();
+        if changes.is_empty() {
+            continue;
+        }
+        report.push_str(&format!("\n{}:\n", title));
+        for change in changes {
+            report.push_str(&format!("    {}\n", change.to_string().replace('\n', "\n    ")));
+        }
+    }
+
+    let breaking = changes.iter().filter(|c| c.breaking).count();
+    let other = changes.len() - breaking;
+    let plural = |n| if n == 1 { "" } else { "s" };
+    report.push_str(&format!(
+        "\n{} breaking change{}, {} other change{}\n",
+        breaking,
+        plural(breaking),
+        other,
+        plural(other)
+    ));
+    Ok(report)
+}
@@ -0,0 +1,241 @@
+use rustdoc_json_types::*;
+
+use super::{diff, Change, ChangeKind};
+
+fn item(id: &str, name: &str, inner: ItemEnum) -> Item {
+    Item {
+        id: Id(id.to_owned()),
+        crate_id: 0,
+        name: Some(name.to_owned()),
+        span: None,
+        visibility: Visibility::Public,
+        docs: None,
+        links: Default::default(),
+        attrs: Vec::new(),
+        deprecation: None,
+        stability: None,
+        must_use: None,
+        track_caller: false,
+        cfg: None,
+        inner,
+    }
+}
+
+fn ids(ids: &[&str]) -> Vec<Id> {
+    ids.iter().map(|id| Id(id.to_string())).collect()
+}
+
+/// Creates a crate named `foo` whose root module contains `root_items`.
+fn krate(root_items: &[&str], items: Vec<Item>) -> Crate {
+    let root =
+        item("0:0", "foo", ItemEnum::Module(Module { is_crate: true, items: ids(root_items) }));
+    let clone = Id("1:1".to_owned());
+    let clone_summary = ItemSummary {
+        crate_id: 1,
+        path: vec!["core".into(), "clone".into(), "Clone".into()],
+        kind: ItemKind::Trait,
+    };
+    Crate {
+        root: root.id.clone(),
+        crate_version: None,
+        includes_private: false,
+        index: items.into_iter().chain(Some(root)).map(|item| (item.id.clone(), item)).collect(),
+        paths: Some((clone, clone_summary)).into_iter().collect(),
+        external_crates: Default::default(),
+        format_version: FORMAT_VERSION,
+    }
+}
+
+fn function(id: &str, name: &str, inputs: Vec<(&str, Type)>) -> Item {
+    let decl = FnDecl {
+        inputs: inputs.into_iter().map(|(name, ty)| (name.to_owned(), ty)).collect(),
+        output: None,
+        c_variadic: false,
+    };
+    let header = Header { const_: false, unsafe_: false, async_: false, abi: Abi::Rust };
+    item(id, name, ItemEnum::Function(Function { decl, generics: Generics::default(), header }))
+}
+
+fn method(id: &str, name: &str, has_body: bool) -> Item {
+    let decl = FnDecl { inputs: Vec::new(), output: None, c_variadic: false };
+    let header = Header { const_: false, unsafe_: false, async_: false, abi: Abi::Rust };
+    let method = Method { decl, generics: Generics::default(), header, has_body };
+    Item { visibility: Visibility::Default, ..item(id, name, ItemEnum::Method(method)) }
+}
+
+fn enum_(id: &str, name: &str, variants: &[&str]) -> Item {
+    let e = Enum {
+        generics: Generics::default(),
+        variants_stripped: false,
+        variants: ids(variants),
+        impls: Vec::new(),
+    };
+    item(id, name, ItemEnum::Enum(e))
+}
+
+fn variant(id: &str, name: &str) -> Item {
+    Item { visibility: Visibility::Default, ..item(id, name, ItemEnum::Variant(Variant::Plain)) }
+}
+
+fn struct_(id: &str, name: &str, impls: &[&str]) -> Item {
+    let s = Struct {
+        struct_type: StructType::Unit,
+        generics: Generics::default(),
+        fields_stripped: false,
+        fields: Vec::new(),
+        impls: ids(impls),
+    };
+    item(id, name, ItemEnum::Struct(s))
+}
+
+fn change(path: &str, kind: &'static str, change: ChangeKind, breaking: bool) -> Change {
+    Change { path: path.to_owned(), kind, change, breaking }
+}
+
+#[test]
+fn test_added_and_removed() {
+    let old =
+        krate(&["0:1", "0:2"], vec![function("0:1", "a", vec![]), function("0:2", "b", vec![])]);
+    let new =
+        krate(&["0:1", "0:2"], vec![function("0:1", "b", vec![]), function("0:2", "c", vec![])]);
+    assert_eq!(
+        diff(&old, &new),
+        vec![
+            change("foo::a", "function", ChangeKind::Removed, true),
+            change("foo::c", "function", ChangeKind::Added, false),
+        ]
+    );
+}
+
+#[test]
+fn test_signature() {
+    let old =
+        krate(&["0:1"], vec![function("0:1", "f", vec![("x", Type::Primitive("u32".into()))])]);
+    let new =
+        krate(&["0:1"], vec![function("0:1", "f", vec![("x", Type::Primitive("u64".into()))])]);
+    let signature =
+        ChangeKind::Signature { old: "fn f(x: u32)".to_owned(), new: "fn f(x: u64)".to_owned() };
+    assert_eq!(diff(&old, &new), vec![change("foo::f", "function", signature, true)]);
+    assert_eq!(diff(&old, &old), vec![]);
+}
+
+#[test]
+fn test_reexport() {
+    let module = |items| item("0:1", "inner", ItemEnum::Module(Module { is_crate: false, items }));
+    let import = item(
+        "0:3",
+        "S",
+        ItemEnum::Import(Import {
+            source: "inner::S".to_owned(),
+            name: "S".to_owned(),
+            id: Some(Id("0:2".to_owned())),
+            glob: false,
+        }),
+    );
+    let old = krate(
+        &["0:1", "0:3"],
+        vec![module(ids(&["0:2"])), struct_("0:2", "S", &[]), import.clone()],
+    );
+    let new = krate(&["0:1"], vec![module(ids(&["0:2"])), struct_("0:2", "S", &[])]);
+    assert_eq!(diff(&old, &new), vec![change("foo::S", "struct", ChangeKind::Removed, true)]);
+}
+
+#[test]
+fn test_variants() {
+    let old = krate(&["0:1"], vec![enum_("0:1", "E", &["0:2"]), variant("0:2", "A")]);
+    let new = krate(
+        &["0:1"],
+        vec![enum_("0:1", "E", &["0:2", "0:3"]), variant("0:2", "A"), variant("0:3", "B")],
+    );
+    assert_eq!(diff(&old, &new), vec![change("foo::E::B", "variant", ChangeKind::Added, true)]);
+
+    let non_exhaustive = |mut item: Item| {
+        item.attrs.push("#[non_exhaustive]".to_owned());
+        item
+    };
+    let old =
+        krate(&["0:1"], vec![non_exhaustive(enum_("0:1", "E", &["0:2"])), variant("0:2", "A")]);
+    let new = krate(
+        &["0:1"],
+        vec![
+            non_exhaustive(enum_("0:1", "E", &["0:2", "0:3"])),
+            variant("0:2", "A"),
+            variant("0:3", "B"),
+        ],
+    );
+    assert_eq!(diff(&old, &new), vec![change("foo::E::B", "variant", ChangeKind::Added, false)]);
+}
+
+#[test]
+fn test_trait_items() {
+    let trait_ = |items| {
+        let t = Trait {
+            is_auto: false,
+            is_unsafe: false,
+            items: ids(items),
+            generics: Generics::default(),
+            bounds: Vec::new(),
+            implementors: Vec::new(),
+        };
+        item("0:1", "T", ItemEnum::Trait(t))
+    };
+    let old = krate(&["0:1"], vec![trait_(&["0:2"]), method("0:2", "a", true)]);
+    let new = krate(
+        &["0:1"],
+        vec![
+            trait_(&["0:2", "0:3", "0:4"]),
+            method("0:2", "a", false),
+            method("0:3", "b", true),
+            method("0:4", "c", false),
+        ],
+    );
+    assert_eq!(
+        diff(&old, &new),
+        vec![
+            change("foo::T::a", "method", ChangeKind::Required(true), true),
+            change("foo::T::b", "method", ChangeKind::Added, false),
+            change("foo::T::c", "method", ChangeKind::Added, true),
+        ]
+    );
+}
+
+#[test]
+fn test_impls() {
+    let clone = Type::ResolvedPath {
+        name: "Clone".to_owned(),
+        id: Id("1:1".to_owned()),
+        args: None,
+        param_names: Vec::new(),
+    };
+    let for_ = Type::ResolvedPath {
+        name: "S".to_owned(),
+        id: Id("0:1".to_owned()),
+        args: None,
+        param_names: Vec::new(),
+    };
+    let impl_ = Impl {
+        is_unsafe: false,
+        generics: Generics::default(),
+        provided_trait_methods: Vec::new(),
+        trait_: Some(clone),
+        for_,
+        items: Vec::new(),
+        negative: false,
+        synthetic: false,
+        blanket_impl: None,
+    };
+    let impl_ = Item { name: None, ..item("0:2", "", ItemEnum::Impl(impl_)) };
+    let old = krate(&["0:1"], vec![struct_("0:1", "S", &["0:2"]), impl_]);
+    let new = krate(&["0:1"], vec![struct_("0:1", "S", &[])]);
+    let removed = ChangeKind::ImplRemoved("impl core::clone::Clone for foo::S".to_owned());
+    assert_eq!(diff(&old, &new), vec![change("foo::S", "struct", removed, true)]);
+    assert_eq!(
+        diff(&new, &old),
+        vec![change(
+            "foo::S",
+            "struct",
+            ChangeKind::ImplAdded("impl core::clone::Clone for foo::S".to_owned()),
+            false
+        )]
+    );
+}
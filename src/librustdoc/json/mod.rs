@@ -5,6 +5,7 @@
 //! docs for usage and details.
 
 mod conversions;
+crate mod diff;
 
 use std::cell::RefCell;
 use std::fs::{create_dir_all, File};
@@ -130,6 +131,10 @@ fn get_trait_items(&mut self) -> Vec<(types::Id, types::Item)> {
                             links: Default::default(),
                             attrs: Default::default(),
                             deprecation: Default::default(),
+                            stability: Default::default(),
+                            must_use: Default::default(),
+                            track_caller: Default::default(),
+                            cfg: Default::default(),
                         },
                     ))
                 } else {
@@ -45,6 +45,7 @@
 extern crate rustc_errors;
 extern crate rustc_expand;
 extern crate rustc_feature;
+extern crate rustc_graphviz;
 extern crate rustc_hir;
 extern crate rustc_hir_pretty;
 extern crate rustc_index;
@@ -117,11 +118,13 @@ macro_rules! map {
 mod externalfiles;
 mod fold;
 mod formats;
+mod graph;
 // used by the error-index generator, so it needs to be public
 pub mod html;
 mod json;
 crate mod lint;
 mod markdown;
+mod md;
 mod passes;
 mod scrape_examples;
 mod theme;
@@ -161,6 +164,7 @@ pub fn main() {
         }
     }
 
+    rustc_metadata::proc_macro_worker::run_if_requested();
     rustc_driver::set_sigpipe_handler();
     rustc_driver::install_ice_hook();
 
@@ -517,6 +521,30 @@ fn opts() -> Vec<RustcOptGroup> {
                 "PATH",
             )
         }),
+        unstable("doctest-cache-dir", |o| {
+            o.optopt(
+                "",
+                "doctest-cache-dir",
+                "Directory to record passing doctests in, to skip them while they are unchanged",
+                "PATH",
+            )
+        }),
+        unstable("doctest-cache-env", |o| {
+            o.optmulti(
+                "",
+                "doctest-cache-env",
+                "Environment variable which doctests depend on, for --doctest-cache-dir",
+                "VAR",
+            )
+        }),
+        unstable("doctest-cache-path", |o| {
+            o.optmulti(
+                "",
+                "doctest-cache-path",
+                "File or directory which doctests depend on, for --doctest-cache-dir",
+                "PATH",
+            )
+        }),
         unstable("show-coverage", |o| {
             o.optflagmulti(
                 "",
@@ -524,6 +552,31 @@ fn opts() -> Vec<RustcOptGroup> {
                 "calculate percentage of public items with documentation",
             )
         }),
+        unstable("coverage-details", |o| {
+            o.optflagmulti(
+                "",
+                "coverage-details",
+                "list the public items without documentation or examples with --show-coverage",
+            )
+        }),
+        unstable("coverage-threshold", |o| {
+            o.optopt(
+                "",
+                "coverage-threshold",
+                "fail if less than this percentage of public items is documented with \
+                 --show-coverage",
+                "PERCENT",
+            )
+        }),
+        unstable("api-diff", |o| {
+            o.optopt(
+                "",
+                "api-diff",
+                "compare the public API described by the JSON output of an older version of a \
+                 crate with the one given as input",
+                "PATH",
+            )
+        }),
         unstable("enable-per-target-ignores", |o| {
             o.optflagmulti(
                 "",
@@ -569,9 +622,32 @@ fn opts() -> Vec<RustcOptGroup> {
         unstable("no-run", |o| {
             o.optflagmulti("", "no-run", "Compile doctests without running them")
         }),
+        unstable("merge-doctests", |o| {
+            o.optflagmulti(
+                "",
+                "merge-doctests",
+                "Compile the doctests that allow it into a single crate, to run them faster",
+            )
+        }),
         unstable("show-type-layout", |o| {
             o.optflagmulti("", "show-type-layout", "Include the memory layout of types in the docs")
         }),
+        unstable("full-text-search", |o| {
+            o.optflagmulti(
+                "",
+                "full-text-search",
+                "Allow searching for items by the words in their documentation",
+            )
+        }),
+        unstable("doc-version", |o| {
+            o.optopt(
+                "",
+                "doc-version",
+                "Document this version of the crates in a subdirectory of the output directory, \
+                 next to the other versions",
+                "VERSION",
+            )
+        }),
         unstable("nocapture", |o| {
             o.optflag("", "nocapture", "Don't capture stdout and stderr of tests")
         }),
@@ -733,6 +809,11 @@ fn run_renderer<'tcx, T: formats::FormatRenderer<'tcx>>(
 fn main_options(options: config::Options) -> MainResult {
     let diag = core::new_handler(options.error_format, None, &options.debugging_opts);
 
+    if let Some(old) = &options.api_diff {
+        let report = json::diff::run(old, &options.input);
+        return wrap_return(&diag, report.map(|report| print!("{}", report)));
+    }
+
     match (options.should_test, options.markdown_input()) {
         (true, true) => return wrap_return(&diag, markdown::test(options)),
         (true, false) => return doctest::run(options),
@@ -760,6 +841,7 @@ fn main_options(options: config::Options) -> MainResult {
     let crate_version = options.crate_version.clone();
 
     let output_format = options.output_format;
+    let coverage_options = options.coverage_options;
     // FIXME: fix this clone (especially render_options)
     let externs = options.externs.clone();
     let render_options = options.render_options.clone();
@@ -804,6 +886,7 @@ fn main_options(options: config::Options) -> MainResult {
                         show_coverage,
                         render_options,
                         output_format,
+                        coverage_options,
                     )
                 });
                 info!("finished with rustc");
@@ -831,6 +914,12 @@ fn main_options(options: config::Options) -> MainResult {
                     config::OutputFormat::Json => sess.time("render_json", || {
                         run_renderer::<json::JsonRenderer<'_>>(krate, render_opts, cache, tcx)
                     }),
+                    config::OutputFormat::Markdown => sess.time("render_markdown", || {
+                        run_renderer::<md::MarkdownRenderer<'_>>(krate, render_opts, cache, tcx)
+                    }),
+                    config::OutputFormat::Graph => sess.time("render_graph", || {
+                        run_renderer::<graph::GraphRenderer>(krate, render_opts, cache, tcx)
+                    }),
                 }
             })
         })
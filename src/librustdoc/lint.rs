@@ -169,6 +169,17 @@ macro_rules! declare_rustdoc_lint {
    "codeblock could not be parsed as valid Rust or is empty"
 }
 
+declare_rustdoc_lint! {
+    /// The `stale_code_references` lint detects paths in inline code in
+    /// documentation, like `` `Foo::bar` ``, that don't resolve to an item.
+    /// This is a `rustdoc` only lint, see the documentation in the [rustdoc book].
+    ///
+    /// [rustdoc book]: ../../../rustdoc/lints.html#stale_code_references
+    STALE_CODE_REFERENCES,
+    Allow,
+    "detects paths in inline code that don't resolve to an item"
+}
+
 crate static RUSTDOC_LINTS: Lazy<Vec<&'static Lint>> = Lazy::new(|| {
     vec![
         BROKEN_INTRA_DOC_LINKS,
@@ -180,6 +191,7 @@ macro_rules! declare_rustdoc_lint {
         INVALID_HTML_TAGS,
         BARE_URLS,
         MISSING_CRATE_LEVEL_DOCS,
+        STALE_CODE_REFERENCES,
     ]
 });
 
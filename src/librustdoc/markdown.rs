@@ -145,6 +145,11 @@ fn extract_leading_metadata(s: &str) -> (Vec<&str>, &str) {
 
     find_testable_code(&input_str, &mut collector, codes, options.enable_per_target_ignores, None);
 
-    crate::doctest::run_tests(options.test_args, options.nocapture, collector.tests);
+    crate::doctest::run_tests(
+        options.test_args,
+        options.nocapture,
+        collector.tests,
+        collector.cached_test_count,
+    );
     Ok(())
 }
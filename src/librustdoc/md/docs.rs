@@ -0,0 +1,197 @@
+//! Rewriting of doc comments for the Markdown backend.
+//!
+//! Doc comments are already Markdown, so they're written out mostly as they are. Intra-doc links
+//! are replaced with regular links to the pages of the items they point to, headings are nested
+//! below the heading of the item they document and hidden lines are removed from Rust code blocks.
+
+use std::ops::Range;
+
+use pulldown_cmark::{BrokenLink, CodeBlockKind, Event, LinkType, Parser, Tag};
+
+use crate::clean::RenderedLink;
+use crate::html::markdown::{main_body_opts, map_line, ErrorCodes, LangString};
+
+#[cfg(test)]
+mod tests;
+
+/// A link that is being rewritten.
+struct PendingLink<'a> {
+    range: Range<usize>,
+    href: &'a str,
+    /// The text to display instead of the original text, for links without a disambiguator.
+    new_text: Option<&'a str>,
+    /// The source of the link text.
+    inner: Option<Range<usize>>,
+    /// Whether the link text is a single code span, like in ``[`Vec`]``.
+    is_code: bool,
+}
+
+/// Rewrites the Markdown in `md`, replacing intra-doc links with the `href` of the corresponding
+/// entry in `links` and adding `heading_offset` levels to its headings.
+crate fn rewrite(md: &str, links: &[RenderedLink], heading_offset: usize) -> String {
+    let mut replacer = |broken_link: BrokenLink<'_>| {
+        links
+            .iter()
+            .find(|link| link.original_text.as_str() == &*broken_link.reference)
+            .map(|link| (link.href.as_str().into(), link.new_text.as_str().into()))
+    };
+    let p = Parser::new_with_broken_link_callback(md, main_body_opts(), Some(&mut replacer));
+
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    // The number of lists, block quotes and footnotes the parser is in. Code blocks in them are
+    // indented in the source, which makes it impossible to replace them.
+    let mut containers = 0;
+    let mut code_block: Option<String> = None;
+    let mut link: Option<PendingLink<'_>> = None;
+    for (event, range) in p.into_offset_iter() {
+        if let Some(link) = &mut link {
+            if !matches!(event, Event::End(Tag::Link(..))) {
+                link.is_code = link.inner.is_none() && matches!(event, Event::Code(_));
+                link.inner = Some(match link.inner.take() {
+                    Some(inner) => inner.start.min(range.start)..inner.end.max(range.end),
+                    None => range,
+                });
+                continue;
+            }
+        }
+        match event {
+            Event::Start(Tag::Heading(..)) if heading_offset > 0 => {
+                let source = &md[range.clone()];
+                let trimmed = source.trim_start();
+                // Setext headings can't be nested deeper than two levels, so they're left alone.
+                if trimmed.starts_with('#') {
+                    let level = trimmed.chars().take_while(|&c| c == '#').count();
+                    let extra = (level + heading_offset).min(6) - level;
+                    let start = range.start + source.len() - trimmed.len();
+                    edits.push((start..start, "#".repeat(extra)));
+                }
+            }
+            Event::Start(Tag::List(_) | Tag::BlockQuote | Tag::FootnoteDefinition(_)) => {
+                containers += 1;
+            }
+            Event::End(Tag::List(_) | Tag::BlockQuote | Tag::FootnoteDefinition(_)) => {
+                containers -= 1;
+            }
+            Event::Start(Tag::CodeBlock(kind)) if containers == 0 => {
+                let is_rust = match kind {
+                    CodeBlockKind::Fenced(ref lang) => {
+                        LangString::parse_without_check(lang, ErrorCodes::No, false).rust
+                    }
+                    CodeBlockKind::Indented => true,
+                };
+                if is_rust {
+                    code_block = Some(String::new());
+                }
+            }
+            Event::Text(text) => {
+                if let Some(code) = &mut code_block {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                if let Some(code) = code_block.take() {
+                    let mut block = String::new();
+                    for line in code.lines().filter_map(|l| map_line(l).for_html()) {
+                        block.push_str(&line);
+                        block.push('\n');
+                    }
+                    let mut fence = "```".to_owned();
+                    while block.contains(&fence) {
+                        fence.push('`');
+                    }
+                    let newline = if md[range.clone()].ends_with('\n') { "\n" } else { "" };
+                    // Remove the indentation of the block as well, which would make the fence
+                    // part of an indented code block otherwise.
+                    let line_start = md[..range.start].rfind('\n').map_or(0, |i| i + 1);
+                    let start = if md[line_start..range.start].trim().is_empty() {
+                        line_start
+                    } else {
+                        range.start
+                    };
+                    let block = format!("{0}rust\n{1}{0}{2}", fence, block, newline);
+                    edits.push((start..range.end, block));
+                }
+            }
+            Event::Start(Tag::Link(kind, dest, _)) => {
+                let found = match kind {
+                    // These were resolved by the `broken_link_callback`, so the URL is already
+                    // the `href`.
+                    LinkType::ShortcutUnknown
+                    | LinkType::CollapsedUnknown
+                    | LinkType::ReferenceUnknown => {
+                        links.iter().find(|link| *link.href == *dest).map(|link| {
+                            // `[fn@f]` is displayed as `f`, but `[the function][fn@f]` keeps
+                            // its text.
+                            let new_text = (kind != LinkType::ReferenceUnknown)
+                                .then(|| link.new_text.as_str());
+                            (link.href.as_str(), new_text)
+                        })
+                    }
+                    _ => links
+                        .iter()
+                        .find(|link| *link.original_text == *dest)
+                        .map(|link| (link.href.as_str(), None)),
+                };
+                if let Some((href, new_text)) = found {
+                    let mut range = range;
+                    // The range of collapsed links doesn't include their trailing `[]`.
+                    if matches!(kind, LinkType::Collapsed | LinkType::CollapsedUnknown)
+                        && md[range.end..].starts_with("[]")
+                    {
+                        range.end += 2;
+                    }
+                    link = Some(PendingLink { range, href, new_text, inner: None, is_code: false });
+                }
+            }
+            Event::End(Tag::Link(..)) => {
+                if let Some(link) = link.take() {
+                    let text = match link.new_text {
+                        Some(text) if link.is_code => format!("`{}`", text),
+                        Some(text) => text.to_owned(),
+                        None => link.inner.map_or(String::new(), |inner| md[inner].to_owned()),
+                    };
+                    let href = if link
+                        .href
+                        .contains(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                    {
+                        format!("<{}>", link.href)
+                    } else {
+                        link.href.to_owned()
+                    };
+                    edits.push((link.range, format!("[{}]({})", text, href)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = String::with_capacity(md.len());
+    let mut pos = 0;
+    edits.sort_by_key(|(range, _)| range.start);
+    for (range, replacement) in edits {
+        if range.start < pos {
+            continue;
+        }
+        out.push_str(&md[pos..range.start]);
+        out.push_str(&replacement);
+        pos = range.end;
+    }
+    out.push_str(&md[pos..]);
+    out
+}
+
+/// Returns the first paragraph of `md` on a single line, for use in the list of items in a module.
+crate fn summary(md: &str) -> Option<String> {
+    let mut start = None;
+    for (event, range) in Parser::new_ext(md, main_body_opts()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Paragraph) => start = Some(range.start),
+            Event::End(Tag::Paragraph) => {
+                let paragraph = &md[start?..range.end];
+                return Some(paragraph.split_whitespace().collect::<Vec<_>>().join(" "));
+            }
+            _ => {}
+        }
+    }
+    None
+}
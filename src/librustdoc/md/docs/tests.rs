@@ -0,0 +1,78 @@
+use super::{rewrite, summary};
+use crate::clean::RenderedLink;
+
+fn link(original_text: &str, new_text: &str, href: &str) -> RenderedLink {
+    RenderedLink {
+        original_text: original_text.to_owned(),
+        new_text: new_text.to_owned(),
+        href: href.to_owned(),
+    }
+}
+
+#[test]
+fn test_rewrite_links() {
+    fn t(input: &str, expected: &str) {
+        let links = [
+            link("Vec", "Vec", "../alloc/vec/struct.Vec.md"),
+            link("`Vec`", "`Vec`", "../alloc/vec/struct.Vec.md"),
+            link("fn@f", "f", "fn.f.md"),
+            link("`fn@f`", "`f`", "fn.f.md"),
+            link("S::method", "S::method", "struct.S.md#method.method"),
+        ];
+        assert_eq!(rewrite(input, &links, 0), expected);
+    }
+
+    t("See [Vec].", "See [Vec](../alloc/vec/struct.Vec.md).");
+    t("See [`Vec`].", "See [`Vec`](../alloc/vec/struct.Vec.md).");
+    t("See [Vec][].", "See [Vec](../alloc/vec/struct.Vec.md).");
+    t("Call [fn@f] or [`fn@f`].", "Call [f](fn.f.md) or [`f`](fn.f.md).");
+    t("A [vector](Vec) of *them*.", "A [vector](../alloc/vec/struct.Vec.md) of *them*.");
+    t("A [*vector*][Vec].", "A [*vector*](../alloc/vec/struct.Vec.md).");
+    t("A [vector][v].\n\n[v]: Vec", "A [vector](../alloc/vec/struct.Vec.md).\n\n[v]: Vec");
+    t("Use [the method][S::method].", "Use [the method](struct.S.md#method.method).");
+    // Links that aren't intra-doc links are left alone.
+    t("[Unknown] and [a link](https://example.com)", "[Unknown] and [a link](https://example.com)");
+    t("`[Vec]`", "`[Vec]`");
+}
+
+#[test]
+fn test_rewrite_headings() {
+    fn t(input: &str, heading_offset: usize, expected: &str) {
+        assert_eq!(rewrite(input, &[], heading_offset), expected);
+    }
+
+    t("# Examples\n\ntext", 1, "## Examples\n\ntext");
+    t("  ## Panics ##", 2, "  #### Panics ##");
+    t("##### Deep", 3, "###### Deep");
+    t("Setext\n======", 1, "Setext\n======");
+    t("# Examples", 0, "# Examples");
+}
+
+#[test]
+fn test_rewrite_code_blocks() {
+    fn t(input: &str, expected: &str) {
+        assert_eq!(rewrite(input, &[], 0), expected);
+    }
+
+    t(
+        "```\n# use std::vec::Vec;\nlet v = Vec::new();\n```\n",
+        "```rust\nlet v = Vec::new();\n```\n",
+    );
+    t("```should_panic\n# fn main() {\npanic!();\n# }\n```", "```rust\npanic!();\n```");
+    t("```text\n# not hidden\n```\n", "```text\n# not hidden\n```\n");
+    t("Code:\n\n    # hidden\n    shown\n", "Code:\n\n```rust\nshown\n```\n");
+    t("```\n## shown\nlet s = \"```\";\n```\n", "````rust\n# shown\nlet s = \"```\";\n````\n");
+    // Code blocks in lists can't be rewritten without knowing their indentation.
+    t("- item\n\n  ```\n  # hidden\n  ```\n", "- item\n\n  ```\n  # hidden\n  ```\n");
+}
+
+#[test]
+fn test_summary() {
+    assert_eq!(summary("First\nline.\n\nSecond paragraph.").as_deref(), Some("First line."));
+    assert_eq!(
+        summary("# Heading\n\nText [with](link.md).").as_deref(),
+        Some("Text [with](link.md).")
+    );
+    assert_eq!(summary("```\ncode\n```"), None);
+    assert_eq!(summary(""), None);
+}
@@ -0,0 +1,467 @@
+//! Rustdoc's Markdown backend
+//!
+//! This module contains the logic for rendering a crate as a tree of Markdown files rather than
+//! the normal static HTML output, e.g. to read documentation in a terminal or to import it into a
+//! wiki. Every item that has an HTML page gets a Markdown file at the same place, with `.md`
+//! instead of `.html`, and intra-doc links are rewritten to relative links between these files.
+
+mod docs;
+mod print;
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{self, DefIdTree, TyCtxt};
+use rustc_span::hygiene::MacroKind;
+use rustc_span::Symbol;
+
+use crate::clean::{self, ExternalLocation, ItemLink, RenderedLink};
+use crate::config::RenderOptions;
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::formats::{FormatRenderer, Impl};
+use crate::html::format::{href_relative_parts, join_with_double_colon};
+use crate::html::render::{item_ty_to_section, ItemSection};
+use crate::md::print::Printer;
+use crate::try_err;
+
+#[derive(Clone)]
+crate struct MarkdownRenderer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The path of the module being rendered, starting with the name of the crate.
+    current: Vec<Symbol>,
+    /// The directory that the module being rendered is written to.
+    dst: PathBuf,
+    /// Whether the module being rendered was stripped, in which case its items are rendered
+    /// by the modules that re-export them, if any.
+    in_stripped_module: bool,
+    cache: Rc<Cache>,
+}
+
+impl<'tcx> MarkdownRenderer<'tcx> {
+    fn printer(&self) -> Printer<'tcx> {
+        Printer { tcx: self.tcx }
+    }
+
+    /// Returns the path to the page documenting `did` from the directory of the module
+    /// `relative_to`. Items of crates that aren't documented next to this one link to their HTML
+    /// documentation if its location is known.
+    fn href(&self, did: DefId, relative_to: &[Symbol]) -> Option<String> {
+        let did = match self.tcx.def_kind(did) {
+            DefKind::AssocTy | DefKind::AssocFn | DefKind::AssocConst => {
+                // Documented on the page of their trait, or of the type they are implemented for,
+                // since impls don't have pages.
+                let parent = self.tcx.parent(did)?;
+                if self.tcx.def_kind(parent) != DefKind::Impl {
+                    parent
+                } else {
+                    match *self.tcx.type_of(parent).kind() {
+                        ty::Adt(def, _) => def.did,
+                        ty::Foreign(did) => did,
+                        _ => return None,
+                    }
+                }
+            }
+            // documented on their parent's page
+            DefKind::Variant => self.tcx.parent(did)?,
+            _ => did,
+        };
+        let cache = &self.cache;
+        if !did.is_local()
+            && !cache.access_levels.is_public(did)
+            && !cache.document_private
+            && !cache.primitive_locations.values().any(|&id| id == did)
+        {
+            return None;
+        }
+
+        let (fqp, shortty, remote) = match cache.paths.get(&did) {
+            Some(&(ref fqp, shortty)) => (fqp, shortty, None),
+            None => {
+                let &(ref fqp, shortty) = cache.external_paths.get(&did)?;
+                match cache.extern_locations[&did.krate] {
+                    ExternalLocation::Remote(ref s) => (fqp, shortty, Some(s)),
+                    ExternalLocation::Local => (fqp, shortty, None),
+                    ExternalLocation::Unknown => return None,
+                }
+            }
+        };
+        let module_fqp = if shortty == ItemType::Module { &fqp[..] } else { &fqp[..fqp.len() - 1] };
+        let mut parts: Vec<String> = match remote {
+            Some(root) => std::iter::once(root.trim_end_matches('/').to_owned())
+                .chain(module_fqp.iter().map(|s| s.to_string()))
+                .collect(),
+            None => href_relative_parts(module_fqp, relative_to).map(|s| s.to_string()).collect(),
+        };
+        let ext = if remote.is_some() { "html" } else { "md" };
+        parts.push(match shortty {
+            ItemType::Module => format!("index.{}", ext),
+            _ => format!("{}.{}.{}", shortty, fqp.last().unwrap(), ext),
+        });
+        Some(parts.join("/"))
+    }
+
+    /// Finds the intra-doc links in the documentation of `item`, which is rendered on a page in
+    /// the directory of the module `relative_to`.
+    fn links(&self, item: &clean::Item, relative_to: &[Symbol]) -> Vec<RenderedLink> {
+        self.cache
+            .intra_doc_links
+            .get(&item.def_id)
+            .map_or(&[][..], |v| v.as_slice())
+            .iter()
+            .filter_map(|ItemLink { link, link_text, did, fragment }| {
+                let mut href = self.href(*did, relative_to)?;
+                if let Some(fragment) = fragment {
+                    fragment.render(&mut href, self.tcx).unwrap();
+                }
+                Some(RenderedLink {
+                    original_text: link.clone(),
+                    new_text: link_text.clone(),
+                    href,
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the documentation of `item`, whose headings become nested below the heading of
+    /// level `heading_level`.
+    fn document(&self, w: &mut String, item: &clean::Item, heading_level: usize) {
+        if let Some(doc) = item.collapsed_doc_value() {
+            let doc = docs::rewrite(&doc, &self.links(item, &self.current), heading_level);
+            w.push_str(doc.trim_end());
+            w.push_str("\n\n");
+        }
+    }
+
+    fn render_item(&self, item: &clean::Item) -> String {
+        let mut w = String::new();
+        let name = item.name.unwrap();
+        let path = if item.is_primitive() || item.is_keyword() {
+            name.to_string()
+        } else if item.is_mod() {
+            join_with_double_colon(&self.current)
+        } else {
+            format!("{}::{}", join_with_double_colon(&self.current), name)
+        };
+        writeln!(w, "# {} `{}`\n", item_title(item), path).unwrap();
+        if let Some(decl) = self.printer().item(item) {
+            writeln!(w, "```rust\n{}\n```\n", decl).unwrap();
+        }
+        self.document(&mut w, item, 1);
+
+        match *item.kind {
+            clean::ModuleItem(ref m) => self.render_module_items(&mut w, m),
+            clean::StructItem(clean::Struct { ref fields, .. })
+            | clean::UnionItem(clean::Union { ref fields, .. }) => {
+                self.render_members(&mut w, "Fields", fields, 3);
+            }
+            clean::EnumItem(ref e) => {
+                let variants: Vec<_> = e.variants.iter().collect();
+                if !variants.iter().all(|v| v.is_stripped()) {
+                    w.push_str("## Variants\n\n");
+                }
+                for variant in variants.into_iter().filter(|v| !v.is_stripped()) {
+                    self.render_member(&mut w, variant, 3);
+                    if let clean::VariantItem(clean::Variant::Struct(ref s)) = *variant.kind {
+                        for field in s.fields.iter().filter(|f| !f.is_stripped()) {
+                            self.render_member(&mut w, field, 4);
+                        }
+                    }
+                }
+            }
+            clean::TraitItem(ref t) => {
+                for (title, ty) in [
+                    ("Associated Types", ItemType::AssocType),
+                    ("Associated Constants", ItemType::AssocConst),
+                    ("Required Methods", ItemType::TyMethod),
+                    ("Provided Methods", ItemType::Method),
+                ] {
+                    let items: Vec<_> =
+                        t.items.iter().filter(|i| i.type_() == ty).cloned().collect();
+                    self.render_members(&mut w, title, &items, 3);
+                }
+                let implementors = item
+                    .def_id
+                    .as_def_id()
+                    .and_then(|did| self.cache.implementors.get(&did))
+                    .map_or(&[][..], |v| v.as_slice());
+                self.render_impl_list(&mut w, "Implementors", implementors.iter(), |i| {
+                    i.inner_impl().for_.def_id(&self.cache)
+                });
+            }
+            _ => {}
+        }
+
+        if matches!(
+            item.type_(),
+            ItemType::Struct
+                | ItemType::Enum
+                | ItemType::Union
+                | ItemType::Primitive
+                | ItemType::ForeignType
+        ) {
+            if let Some(impls) = item.def_id.as_def_id().and_then(|did| self.cache.impls.get(&did))
+            {
+                self.render_impls(&mut w, impls);
+            }
+        }
+
+        w.truncate(w.trim_end().len());
+        w.push('\n');
+        w
+    }
+
+    /// Lists the items of a module, grouped like in the HTML output.
+    fn render_module_items(&self, w: &mut String, m: &clean::Module) {
+        let mut items: Vec<_> = m
+            .items
+            .iter()
+            .filter(|i| !i.is_stripped())
+            .filter(|i| match *i.kind {
+                clean::ImportItem(ref import) => import.should_be_displayed,
+                clean::ExternCrateItem { .. } => false,
+                _ => true,
+            })
+            .collect();
+        items.sort_by_cached_key(|i| (i.name.map(|n| n.to_string()), i.type_()));
+
+        for section in ItemSection::ALL {
+            let items: Vec<_> =
+                items.iter().filter(|i| item_ty_to_section(i.type_()) == *section).collect();
+            if items.is_empty() {
+                continue;
+            }
+            writeln!(w, "## {}\n", section.name()).unwrap();
+            for item in items {
+                if let clean::ImportItem(ref import) = *item.kind {
+                    let vis =
+                        item.visibility.to_src_with_space(self.tcx, item.def_id.expect_def_id());
+                    writeln!(w, "- `{}{}`", vis, self.printer().import(import)).unwrap();
+                    continue;
+                }
+                let name = item.name.unwrap();
+                let href = match item.type_() {
+                    ItemType::Module => format!("{}/index.md", name),
+                    ty => format!("{}.{}.md", ty, name),
+                };
+                write!(w, "- [`{}`]({})", name, href).unwrap();
+                let summary = item.collapsed_doc_value().and_then(|doc| {
+                    docs::summary(&docs::rewrite(&doc, &self.links(item, &self.current), 0))
+                });
+                if let Some(summary) = summary {
+                    write!(w, ": {}", summary).unwrap();
+                }
+                w.push('\n');
+            }
+            w.push('\n');
+        }
+    }
+
+    /// Renders a section for the members of an item, like fields or methods.
+    fn render_members(&self, w: &mut String, title: &str, members: &[clean::Item], level: usize) {
+        let members: Vec<_> = members.iter().filter(|m| !m.is_stripped()).collect();
+        if members.is_empty() {
+            return;
+        }
+        writeln!(w, "{} {}\n", "#".repeat(level - 1), title).unwrap();
+        for member in members {
+            self.render_member(w, member, level);
+        }
+    }
+
+    /// Renders a member of an item with an anchor for links to it, which has the same name as in
+    /// the HTML output.
+    fn render_member(&self, w: &mut String, member: &clean::Item, level: usize) {
+        let name = match member.name {
+            Some(name) => name,
+            None => return,
+        };
+        let parent = member.def_id.as_def_id().and_then(|did| self.tcx.parent(did));
+        let anchor = match parent {
+            Some(variant)
+                if matches!(*member.kind, clean::StructFieldItem(_))
+                    && self.tcx.def_kind(variant) == DefKind::Variant =>
+            {
+                format!("variant.{}.field.{}", self.tcx.item_name(variant), name)
+            }
+            _ => format!("{}.{}", member.type_(), name),
+        };
+        let decl = self.printer().item(member).unwrap_or_else(|| name.to_string());
+        let decl = decl.split_whitespace().collect::<Vec<_>>().join(" ");
+        writeln!(w, "<a id=\"{}\"></a>\n{} `{}`\n", anchor, "#".repeat(level), decl).unwrap();
+        self.document(w, member, level);
+    }
+
+    fn render_impls(&self, w: &mut String, impls: &[Impl]) {
+        let (inherent, traits): (Vec<_>, Vec<_>) =
+            impls.iter().partition(|i| i.inner_impl().trait_.is_none());
+        if !inherent.is_empty() {
+            w.push_str("## Implementations\n\n");
+        }
+        for i in inherent {
+            writeln!(w, "### `{}`\n", self.printer().impl_(i.inner_impl())).unwrap();
+            self.document(w, &i.impl_item, 3);
+            for item in i.inner_impl().items.iter().filter(|i| !i.is_stripped()) {
+                self.render_member(w, item, 4);
+            }
+        }
+
+        let (auto, traits): (Vec<_>, Vec<_>) =
+            traits.into_iter().partition(|i| i.inner_impl().kind.is_auto());
+        let (blanket, traits): (Vec<_>, Vec<_>) =
+            traits.into_iter().partition(|i| i.inner_impl().kind.is_blanket());
+        let trait_did = |i: &Impl| i.trait_did();
+        self.render_impl_list(w, "Trait Implementations", traits.into_iter(), trait_did);
+        self.render_impl_list(w, "Auto Trait Implementations", auto.into_iter(), trait_did);
+        self.render_impl_list(w, "Blanket Implementations", blanket.into_iter(), trait_did);
+    }
+
+    /// Lists the headers of `impls`, each linking to the page of the item returned by `link_to`.
+    fn render_impl_list<'a>(
+        &self,
+        w: &mut String,
+        title: &str,
+        impls: impl Iterator<Item = &'a Impl>,
+        link_to: impl Fn(&Impl) -> Option<DefId>,
+    ) {
+        let mut entries: Vec<_> = impls
+            .map(|i| {
+                let header = format!("`{}`", self.printer().impl_(i.inner_impl()));
+                match link_to(i).and_then(|did| self.href(did, &self.current)) {
+                    Some(href) => format!("[{}]({})", header.replace('\n', " "), href),
+                    None => header.replace('\n', " "),
+                }
+            })
+            .collect();
+        if entries.is_empty() {
+            return;
+        }
+        entries.sort();
+        entries.dedup();
+        writeln!(w, "## {}\n", title).unwrap();
+        for entry in entries {
+            writeln!(w, "- {}", entry).unwrap();
+        }
+        w.push('\n');
+    }
+}
+
+/// The kind of item documented on a page, as used in its title.
+fn item_title(item: &clean::Item) -> &'static str {
+    match *item.kind {
+        clean::ModuleItem(_) if item.is_crate() => "Crate",
+        clean::ModuleItem(_) => "Module",
+        clean::FunctionItem(..) | clean::ForeignFunctionItem(..) => "Function",
+        clean::TraitItem(..) => "Trait",
+        clean::StructItem(..) => "Struct",
+        clean::UnionItem(..) => "Union",
+        clean::EnumItem(..) => "Enum",
+        clean::TypedefItem(..) => "Type Definition",
+        clean::MacroItem(..) => "Macro",
+        clean::ProcMacroItem(ref mac) => match mac.kind {
+            MacroKind::Bang => "Macro",
+            MacroKind::Attr => "Attribute Macro",
+            MacroKind::Derive => "Derive Macro",
+        },
+        clean::PrimitiveItem(..) => "Primitive Type",
+        clean::StaticItem(..) | clean::ForeignStaticItem(..) => "Static",
+        clean::ConstantItem(..) => "Constant",
+        clean::ForeignTypeItem => "Foreign Type",
+        clean::KeywordItem(..) => "Keyword",
+        clean::OpaqueTyItem(..) => "Opaque Type",
+        clean::TraitAliasItem(..) => "Trait Alias",
+        _ => unreachable!("no page is generated for {:?}", item.type_()),
+    }
+}
+
+impl<'tcx> FormatRenderer<'tcx> for MarkdownRenderer<'tcx> {
+    fn descr() -> &'static str {
+        "markdown"
+    }
+
+    const RUN_ON_MODULE: bool = true;
+
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        debug!("Initializing markdown renderer");
+        Ok((
+            MarkdownRenderer {
+                tcx,
+                current: Vec::new(),
+                dst: options.output,
+                in_stripped_module: false,
+                cache: Rc::new(cache),
+            },
+            krate,
+        ))
+    }
+
+    fn make_child_renderer(&self) -> Self {
+        self.clone()
+    }
+
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        if self.in_stripped_module || item.is_stripped() || !has_page(&item) {
+            return Ok(());
+        }
+        let page = self.render_item(&item);
+        try_err!(fs::create_dir_all(&self.dst), &self.dst);
+        let dst = self.dst.join(format!("{}.{}.md", item.type_(), item.name.unwrap()));
+        try_err!(fs::write(&dst, page), &dst);
+        Ok(())
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
+        let name = item.name.unwrap();
+        self.dst.push(name.as_str());
+        self.current.push(name);
+        self.in_stripped_module |= item.is_stripped();
+        if !self.in_stripped_module {
+            let page = self.render_item(item);
+            try_err!(fs::create_dir_all(&self.dst), &self.dst);
+            let dst = self.dst.join("index.md");
+            try_err!(fs::write(&dst, page), &dst);
+        }
+        Ok(())
+    }
+
+    fn mod_item_out(&mut self) -> Result<(), Error> {
+        self.dst.pop();
+        self.current.pop();
+        Ok(())
+    }
+
+    fn after_krate(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn cache(&self) -> &Cache {
+        &self.cache
+    }
+}
+
+/// Whether `item` gets its own page, rather than being documented on the page of its parent.
+fn has_page(item: &clean::Item) -> bool {
+    !matches!(
+        *item.kind,
+        clean::ImportItem(_)
+            | clean::ExternCrateItem { .. }
+            | clean::ImplItem(_)
+            | clean::TyMethodItem(_)
+            | clean::MethodItem(..)
+            | clean::StructFieldItem(_)
+            | clean::VariantItem(_)
+            | clean::AssocConstItem(..)
+            | clean::AssocTypeItem(..)
+    )
+}
@@ -0,0 +1,562 @@
+//! Printing of declarations as plain Rust source, for use in Markdown code blocks.
+//!
+//! This mirrors the alternate (`{:#}`) formatting in [`crate::html::format`], which can't be used
+//! here as it needs an HTML [`Context`](crate::html::render::Context) to resolve links.
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir as hir;
+use rustc_hir::def::CtorKind;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::hygiene::MacroKind;
+use rustc_span::Symbol;
+
+use crate::clean::{self, ItemId};
+use crate::html::format::{print_abi_with_space, print_constness_with_space, PrintWithSpace};
+
+crate struct Printer<'tcx> {
+    crate tcx: TyCtxt<'tcx>,
+}
+
+impl<'tcx> Printer<'tcx> {
+    /// Prints the declaration of `item`, without its body or docs. Returns `None` for items that
+    /// don't have a declaration, like modules.
+    crate fn item(&self, item: &clean::Item) -> Option<String> {
+        let name = item.name?;
+        let vis = self.visibility(item.visibility, item.def_id);
+        let decl = match *item.kind {
+            clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+                self.function(&vis, name, f, item)
+            }
+            clean::TyMethodItem(ref f) => format!("{};", self.function(&vis, name, f, item)),
+            clean::MethodItem(ref f, _) => self.function(&vis, name, f, item),
+            clean::StructItem(ref s) => {
+                let mut decl = format!("{}struct {}{}", vis, name, self.generics(&s.generics));
+                decl.push_str(&self.fields(s.struct_type, &s.fields, Some(&s.generics)));
+                if s.struct_type != CtorKind::Fictive {
+                    decl.push(';');
+                }
+                decl
+            }
+            clean::UnionItem(ref u) => {
+                let mut decl = format!("{}union {}{}", vis, name, self.generics(&u.generics));
+                decl.push_str(&self.fields(CtorKind::Fictive, &u.fields, Some(&u.generics)));
+                decl
+            }
+            clean::EnumItem(ref e) => {
+                let mut decl = format!(
+                    "{}enum {}{}{}",
+                    vis,
+                    name,
+                    self.generics(&e.generics),
+                    self.where_clause(&e.generics, true),
+                );
+                if e.variants.is_empty() && !e.variants_stripped {
+                    decl.push_str(" {}");
+                } else {
+                    decl.push_str(" {\n");
+                    for variant in e.variants.iter().filter(|v| !v.is_stripped()) {
+                        if let Some(v) = self.item(variant) {
+                            decl.push_str(&format!("    {},\n", v));
+                        }
+                    }
+                    if e.variants_stripped {
+                        decl.push_str("    // some variants omitted\n");
+                    }
+                    decl.push('}');
+                }
+                decl
+            }
+            clean::VariantItem(ref v) => match v {
+                clean::Variant::CLike => name.to_string(),
+                clean::Variant::Tuple(fields) => {
+                    format!("{}{}", name, self.fields(CtorKind::Fn, fields, None))
+                }
+                clean::Variant::Struct(s) => {
+                    format!("{}{}", name, self.fields(s.struct_type, &s.fields, None))
+                }
+            },
+            clean::StructFieldItem(ref ty) => format!("{}{}: {}", vis, name, self.ty(ty)),
+            clean::TraitItem(ref t) => {
+                let mut decl = format!(
+                    "{}{}{}trait {}{}",
+                    vis,
+                    t.unsafety.print_with_space(),
+                    if t.is_auto { "auto " } else { "" },
+                    name,
+                    self.generics(&t.generics),
+                );
+                if !t.bounds.is_empty() {
+                    decl.push_str(&format!(": {}", self.bounds(&t.bounds)));
+                }
+                decl.push_str(&self.where_clause(&t.generics, true));
+                let items: Vec<_> = t
+                    .items
+                    .iter()
+                    .filter(|i| !i.is_stripped())
+                    .filter_map(|i| {
+                        let decl = self.item(i)?;
+                        Some(if i.is_method() { format!("{} {{ ... }}", decl) } else { decl })
+                    })
+                    .collect();
+                if items.is_empty() {
+                    decl.push_str(" {}");
+                } else {
+                    decl.push_str(" {\n");
+                    for item in items {
+                        decl.push_str(&format!("    {}\n", item.replace('\n', "\n    ")));
+                    }
+                    decl.push('}');
+                }
+                decl
+            }
+            clean::TraitAliasItem(ref t) => format!(
+                "trait {}{}{} = {};",
+                name,
+                self.generics(&t.generics),
+                self.where_clause(&t.generics, false),
+                self.bounds(&t.bounds),
+            ),
+            clean::TypedefItem(ref t, _) => format!(
+                "{}type {}{}{} = {};",
+                vis,
+                name,
+                self.generics(&t.generics),
+                self.where_clause(&t.generics, false),
+                self.ty(&t.type_),
+            ),
+            clean::OpaqueTyItem(ref t) => format!(
+                "type {}{}{} = impl {};",
+                name,
+                self.generics(&t.generics),
+                self.where_clause(&t.generics, false),
+                self.bounds(&t.bounds),
+            ),
+            clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => format!(
+                "{}static {}{}: {};",
+                vis,
+                s.mutability.print_with_space(),
+                name,
+                self.ty(&s.type_),
+            ),
+            clean::ConstantItem(ref c) => {
+                format!("{}const {}: {} = {};", vis, name, self.ty(&c.type_), c.expr(self.tcx))
+            }
+            clean::AssocConstItem(ref ty, ref default) => {
+                let mut decl = format!("{}const {}: {}", vis, name, self.ty(ty));
+                if let Some(default) = default {
+                    decl.push_str(&format!(" = {}", default.expr(self.tcx)));
+                }
+                decl.push(';');
+                decl
+            }
+            clean::AssocTypeItem(ref bounds, ref default) => {
+                let mut decl = format!("{}type {}", vis, name);
+                if !bounds.is_empty() {
+                    decl.push_str(&format!(": {}", self.bounds(bounds)));
+                }
+                if let Some(default) = default {
+                    decl.push_str(&format!(" = {}", self.ty(default)));
+                }
+                decl.push(';');
+                decl
+            }
+            clean::ForeignTypeItem => format!("{}type {};", vis, name),
+            clean::MacroItem(ref m) => m.source.clone(),
+            clean::ProcMacroItem(ref m) => {
+                let mut decl = match m.kind {
+                    MacroKind::Bang => format!("{}!() {{ /* proc-macro */ }}", name),
+                    MacroKind::Attr => format!("#[{}]", name),
+                    MacroKind::Derive => format!("#[derive({})]", name),
+                };
+                if !m.helpers.is_empty() {
+                    decl.push_str("\n\n// Helper attributes:");
+                    for helper in &m.helpers {
+                        decl.push_str(&format!("\n#[{}]", helper));
+                    }
+                }
+                decl
+            }
+            _ => return None,
+        };
+        Some(decl)
+    }
+
+    fn function(&self, vis: &str, name: Symbol, f: &clean::Function, item: &clean::Item) -> String {
+        let header = &f.header;
+        format!(
+            "{}{}{}{}{:#}fn {}{}{}{}",
+            vis,
+            print_constness_with_space(&header.constness, item.const_stability(self.tcx)),
+            header.asyncness.print_with_space(),
+            header.unsafety.print_with_space(),
+            print_abi_with_space(header.abi),
+            name,
+            self.generics(&f.generics),
+            self.fn_decl(&f.decl, header.asyncness),
+            self.where_clause(&f.generics, false),
+        )
+    }
+
+    /// Prints the fields of a struct, union or variant, starting with the where clause of the
+    /// struct's `generics`.
+    fn fields(
+        &self,
+        kind: CtorKind,
+        fields: &[clean::Item],
+        g: Option<&clean::Generics>,
+    ) -> String {
+        let where_clause = |end_newline| g.map(|g| self.where_clause(g, end_newline));
+        match kind {
+            CtorKind::Fictive => {
+                let mut s = where_clause(true).unwrap_or_default();
+                let visible: Vec<_> = fields.iter().filter_map(|f| self.item(f)).collect();
+                let has_stripped_fields = visible.len() < fields.len();
+                if visible.is_empty() {
+                    s.push_str(if has_stripped_fields {
+                        " { /* private fields */ }"
+                    } else {
+                        " {}"
+                    });
+                    return s;
+                }
+                // Variants are printed inline in their enum, so their fields are indented by
+                // another level.
+                let indent = if g.is_some() { "    " } else { "        " };
+                s.push_str(" {\n");
+                for field in visible {
+                    s.push_str(&format!("{}{},\n", indent, field));
+                }
+                if has_stripped_fields {
+                    s.push_str(&format!("{}/* private fields */\n", indent));
+                }
+                s.push_str(&indent[4..]);
+                s.push('}');
+                s
+            }
+            CtorKind::Fn => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|field| match *field.kind {
+                        clean::StructFieldItem(ref ty) => format!(
+                            "{}{}",
+                            self.visibility(field.visibility, field.def_id),
+                            self.ty(ty)
+                        ),
+                        _ => "_".to_owned(),
+                    })
+                    .collect();
+                format!("({}){}", fields.join(", "), where_clause(false).unwrap_or_default())
+            }
+            CtorKind::Const => where_clause(false).unwrap_or_default(),
+        }
+    }
+
+    crate fn impl_(&self, i: &clean::Impl) -> String {
+        let mut s = format!("{}impl{} ", i.unsafety.print_with_space(), self.generics(&i.generics));
+        if let Some(ref trait_) = i.trait_ {
+            if let ty::ImplPolarity::Negative = i.polarity {
+                s.push('!');
+            }
+            s.push_str(&format!("{} for ", self.path(trait_)));
+        }
+        s.push_str(&self.ty(i.kind.as_blanket_ty().unwrap_or(&i.for_)));
+        s.push_str(&self.where_clause(&i.generics, false));
+        s
+    }
+
+    crate fn import(&self, import: &clean::Import) -> String {
+        let path = import.source.path.whole_name();
+        match import.kind {
+            clean::ImportKind::Simple(name) if name == import.source.path.last() => {
+                format!("use {};", path)
+            }
+            clean::ImportKind::Simple(name) => format!("use {} as {};", path, name),
+            clean::ImportKind::Glob if path.is_empty() => "use *;".to_owned(),
+            clean::ImportKind::Glob => format!("use {}::*;", path),
+        }
+    }
+
+    fn visibility(&self, vis: clean::Visibility, item_id: ItemId) -> String {
+        match item_id.as_def_id() {
+            Some(did) => vis.to_src_with_space(self.tcx, did).to_string(),
+            None if vis.is_public() => "pub ".to_owned(),
+            None => String::new(),
+        }
+    }
+
+    fn generics(&self, g: &clean::Generics) -> String {
+        let params: Vec<_> = g
+            .params
+            .iter()
+            .filter(|p| !p.is_synthetic_type_param())
+            .map(|p| self.generic_param(p))
+            .collect();
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        }
+    }
+
+    fn generic_param(&self, p: &clean::GenericParamDef) -> String {
+        match p.kind {
+            clean::GenericParamDefKind::Lifetime { ref outlives } => {
+                let mut s = p.name.to_string();
+                if !outlives.is_empty() {
+                    let outlives: Vec<_> = outlives.iter().map(|lt| lt.0.to_string()).collect();
+                    s.push_str(&format!(": {}", outlives.join(" + ")));
+                }
+                s
+            }
+            clean::GenericParamDefKind::Type { ref bounds, ref default, .. } => {
+                let mut s = p.name.to_string();
+                if !bounds.is_empty() {
+                    s.push_str(&format!(": {}", self.bounds(bounds)));
+                }
+                if let Some(ty) = default {
+                    s.push_str(&format!(" = {}", self.ty(ty)));
+                }
+                s
+            }
+            clean::GenericParamDefKind::Const { ref ty, ref default, .. } => {
+                let mut s = format!("const {}: {}", p.name, self.ty(ty));
+                if let Some(default) = default {
+                    s.push_str(&format!(" = {}", default));
+                }
+                s
+            }
+        }
+    }
+
+    /// Prints the where clause of `g`, if any, with each predicate on its own line.
+    fn where_clause(&self, g: &clean::Generics, end_newline: bool) -> String {
+        if g.where_predicates.is_empty() {
+            return String::new();
+        }
+        let predicates: Vec<_> = g
+            .where_predicates
+            .iter()
+            .map(|pred| match pred {
+                clean::WherePredicate::BoundPredicate { ty, bounds, bound_params } => {
+                    let for_prefix = if bound_params.is_empty() {
+                        String::new()
+                    } else {
+                        let params: Vec<_> =
+                            bound_params.iter().map(|lt| lt.0.to_string()).collect();
+                        format!("for<{}> ", params.join(", "))
+                    };
+                    format!("{}{}: {}", for_prefix, self.ty(ty), self.bounds(bounds))
+                }
+                clean::WherePredicate::RegionPredicate { lifetime, bounds } => {
+                    format!("{}: {}", lifetime.0, self.bounds(bounds))
+                }
+                clean::WherePredicate::EqPredicate { lhs, rhs } => {
+                    format!("{} == {}", self.ty(lhs), self.term(rhs))
+                }
+            })
+            .collect();
+        let mut s = format!("\nwhere\n    {}", predicates.join(",\n    "));
+        if end_newline {
+            s.push_str(",\n");
+        }
+        s
+    }
+
+    fn bounds(&self, bounds: &[clean::GenericBound]) -> String {
+        let mut seen = FxHashSet::default();
+        let bounds: Vec<_> =
+            bounds.iter().filter(|b| seen.insert(*b)).map(|b| self.bound(b)).collect();
+        bounds.join(" + ")
+    }
+
+    fn bound(&self, bound: &clean::GenericBound) -> String {
+        match bound {
+            clean::GenericBound::Outlives(lt) => lt.0.to_string(),
+            clean::GenericBound::TraitBound(poly_trait, modifier) => {
+                let modifier = match modifier {
+                    hir::TraitBoundModifier::None => "",
+                    hir::TraitBoundModifier::Maybe => "?",
+                    hir::TraitBoundModifier::MaybeConst => "~const",
+                };
+                format!("{}{}", modifier, self.poly_trait(poly_trait))
+            }
+        }
+    }
+
+    fn poly_trait(&self, poly_trait: &clean::PolyTrait) -> String {
+        let params: Vec<_> =
+            poly_trait.generic_params.iter().map(|p| self.generic_param(p)).collect();
+        let path = self.path(&poly_trait.trait_);
+        if params.is_empty() {
+            path
+        } else {
+            format!("for<{}> {}", params.join(", "), path)
+        }
+    }
+
+    /// Prints the last segment of `path`, or all of them for paths like `T::Output`.
+    fn path(&self, path: &clean::Path) -> String {
+        let segments = if path.is_assoc_ty() {
+            &path.segments[..]
+        } else {
+            &path.segments[path.segments.len() - 1..]
+        };
+        let segments: Vec<_> = segments
+            .iter()
+            .map(|seg| format!("{}{}", seg.name, self.generic_args(&seg.args)))
+            .collect();
+        segments.join("::")
+    }
+
+    fn generic_args(&self, args: &clean::GenericArgs) -> String {
+        match args {
+            clean::GenericArgs::AngleBracketed { args, bindings } => {
+                let args = args.iter().map(|arg| match arg {
+                    clean::GenericArg::Lifetime(lt) => lt.0.to_string(),
+                    clean::GenericArg::Type(ty) => self.ty(ty),
+                    clean::GenericArg::Const(ct) => ct.expr(self.tcx),
+                    clean::GenericArg::Infer => "_".to_owned(),
+                });
+                let bindings = bindings.iter().map(|binding| match binding.kind {
+                    clean::TypeBindingKind::Equality { ref term } => {
+                        format!("{} = {}", binding.name, self.term(term))
+                    }
+                    clean::TypeBindingKind::Constraint { ref bounds } => {
+                        format!("{}: {}", binding.name, self.bounds(bounds))
+                    }
+                });
+                let args: Vec<_> = args.chain(bindings).collect();
+                if args.is_empty() {
+                    String::new()
+                } else {
+                    format!("<{}>", args.join(", "))
+                }
+            }
+            clean::GenericArgs::Parenthesized { inputs, output } => {
+                let inputs: Vec<_> = inputs.iter().map(|ty| self.ty(ty)).collect();
+                match output {
+                    Some(ty) => format!("({}) -> {}", inputs.join(", "), self.ty(ty)),
+                    None => format!("({})", inputs.join(", ")),
+                }
+            }
+        }
+    }
+
+    fn term(&self, term: &clean::Term) -> String {
+        match term {
+            clean::Term::Type(ty) => self.ty(ty),
+            clean::Term::Constant(ct) => ct.expr(self.tcx),
+        }
+    }
+
+    crate fn ty(&self, ty: &clean::Type) -> String {
+        match *ty {
+            clean::Generic(name) => name.to_string(),
+            clean::Type::Path { ref path } => self.path(path),
+            clean::DynTrait(ref bounds, ref lt) => {
+                let mut bounds: Vec<_> = bounds.iter().map(|b| self.poly_trait(b)).collect();
+                bounds.extend(lt.iter().map(|lt| lt.0.to_string()));
+                format!("dyn {}", bounds.join(" + "))
+            }
+            clean::Infer => "_".to_owned(),
+            clean::Primitive(clean::PrimitiveType::Never) => "!".to_owned(),
+            clean::Primitive(prim) => prim.as_sym().to_string(),
+            clean::BareFunction(ref decl) => {
+                let params: Vec<_> =
+                    decl.generic_params.iter().map(|p| self.generic_param(p)).collect();
+                format!(
+                    "{}{}{:#}fn{}",
+                    if params.is_empty() {
+                        String::new()
+                    } else {
+                        format!("for<{}> ", params.join(", "))
+                    },
+                    decl.unsafety.print_with_space(),
+                    print_abi_with_space(decl.abi),
+                    self.fn_decl(&decl.decl, hir::IsAsync::NotAsync),
+                )
+            }
+            clean::Tuple(ref tys) => match &tys[..] {
+                [one] => format!("({},)", self.ty(one)),
+                tys => {
+                    let tys: Vec<_> = tys.iter().map(|ty| self.ty(ty)).collect();
+                    format!("({})", tys.join(", "))
+                }
+            },
+            clean::Slice(ref ty) => format!("[{}]", self.ty(ty)),
+            clean::Array(ref ty, ref n) => format!("[{}; {}]", self.ty(ty), n),
+            clean::RawPointer(m, ref ty) => {
+                let m = match m {
+                    hir::Mutability::Mut => "mut",
+                    hir::Mutability::Not => "const",
+                };
+                format!("*{} {}", m, self.ty(ty))
+            }
+            clean::BorrowedRef { ref lifetime, mutability, ref type_ } => {
+                let lt = lifetime.as_ref().map(|lt| format!("{} ", lt.0)).unwrap_or_default();
+                let m = mutability.print_with_space();
+                match **type_ {
+                    clean::DynTrait(ref bounds, ref trait_lt)
+                        if bounds.len() > 1 || trait_lt.is_some() =>
+                    {
+                        format!("&{}{}({})", lt, m, self.ty(type_))
+                    }
+                    _ => format!("&{}{}{}", lt, m, self.ty(type_)),
+                }
+            }
+            clean::ImplTrait(ref bounds) => format!("impl {}", self.bounds(bounds)),
+            clean::QPath { ref name, ref self_type, ref trait_, ref self_def_id } => {
+                let should_show_cast = !trait_.segments.is_empty()
+                    && self_def_id
+                        .zip(Some(trait_.def_id()))
+                        .map_or(!self_type.is_self_type(), |(id, trait_)| id != trait_);
+                if should_show_cast {
+                    format!("<{} as {}>::{}", self.ty(self_type), self.path(trait_), name)
+                } else {
+                    format!("{}::{}", self.ty(self_type), name)
+                }
+            }
+        }
+    }
+
+    fn fn_decl(&self, decl: &clean::FnDecl, asyncness: hir::IsAsync) -> String {
+        let mut args: Vec<_> = decl
+            .inputs
+            .values
+            .iter()
+            .map(|input| match input.to_self() {
+                Some(clean::SelfValue) => "self".to_owned(),
+                Some(clean::SelfBorrowed(lt, m)) => format!(
+                    "&{}{}self",
+                    lt.map(|lt| format!("{} ", lt.0)).unwrap_or_default(),
+                    m.print_with_space(),
+                ),
+                Some(clean::SelfExplicit(ref ty)) => format!("self: {}", self.ty(ty)),
+                None => {
+                    let mut arg = String::new();
+                    if input.is_const {
+                        arg.push_str("const ");
+                    }
+                    if !input.name.is_empty() {
+                        arg.push_str(&format!("{}: ", input.name));
+                    }
+                    arg.push_str(&self.ty(&input.type_));
+                    arg
+                }
+            })
+            .collect();
+        if decl.c_variadic {
+            args.push("...".to_owned());
+        }
+        let output = match asyncness {
+            hir::IsAsync::Async => decl.sugared_async_return_type(),
+            hir::IsAsync::NotAsync => decl.output.clone(),
+        };
+        let output = match output {
+            clean::Return(clean::Tuple(tys)) if tys.is_empty() => String::new(),
+            clean::Return(ty) => format!(" -> {}", self.ty(&ty)),
+            clean::DefaultReturn => String::new(),
+        };
+        format!("({}){}", args.join(", "), output)
+    }
+}
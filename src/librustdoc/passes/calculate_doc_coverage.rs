@@ -6,9 +6,10 @@
 use crate::passes::Pass;
 use crate::visit::DocVisitor;
 use rustc_hir as hir;
+use rustc_hir::def_id::DefId;
 use rustc_lint::builtin::MISSING_DOCS;
 use rustc_middle::lint::LintLevelSource;
-use rustc_middle::ty::DefIdTree;
+use rustc_middle::ty::{DefIdTree, TyCtxt};
 use rustc_session::lint;
 use rustc_span::FileName;
 use serde::Serialize;
@@ -27,6 +28,7 @@ fn calculate_doc_coverage(krate: clean::Crate, ctx: &mut DocContext<'_>) -> clea
     calc.visit_crate(&krate);
 
     calc.print_results();
+    calc.check_threshold();
 
     krate
 }
@@ -101,8 +103,27 @@ fn add_assign(&mut self, rhs: Self) {
     }
 }
 
+/// An item that should have documentation or an example, but doesn't.
+#[derive(Serialize, Debug)]
+struct MissingItem {
+    path: String,
+    line: usize,
+}
+
+#[derive(Default, Serialize, Debug)]
+struct FileCoverage {
+    #[serde(flatten)]
+    count: ItemCount,
+    /// Only filled in with `--coverage-details`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    undocumented: Vec<MissingItem>,
+    /// Only filled in with `--coverage-details`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    without_examples: Vec<MissingItem>,
+}
+
 struct CoverageCalculator<'a, 'b> {
-    items: BTreeMap<FileName, ItemCount>,
+    items: BTreeMap<FileName, FileCoverage>,
     ctx: &'a mut DocContext<'b>,
 }
 
@@ -116,6 +137,17 @@ fn limit_filename_len(filename: String) -> String {
     }
 }
 
+/// Returns the path of the item with `def_id`, starting with the name of its crate.
+fn item_path(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    let krate = tcx.crate_name(def_id.krate);
+    let path = tcx.def_path_str(def_id);
+    if path.is_empty() {
+        krate.to_string()
+    } else {
+        format!("{}::{}", krate, path)
+    }
+}
+
 impl<'a, 'b> CoverageCalculator<'a, 'b> {
     fn to_json(&self) -> String {
         serde_json::to_string(
@@ -123,7 +155,7 @@ fn to_json(&self) -> String {
                 .items
                 .iter()
                 .map(|(k, v)| (k.prefer_local().to_string(), v))
-                .collect::<BTreeMap<String, &ItemCount>>(),
+                .collect::<BTreeMap<String, &FileCoverage>>(),
         )
         .expect("failed to convert JSON data to string")
     }
@@ -159,7 +191,7 @@ fn print_table_record(
         );
         print_table_line();
 
-        for (file, &count) in &self.items {
+        for (file, &FileCoverage { count, .. }) in &self.items {
             if let Some(percentage) = count.percentage() {
                 print_table_record(
                     &limit_filename_len(file.prefer_local().to_string_lossy().into()),
@@ -180,6 +212,42 @@ fn print_table_record(
             total.examples_percentage().unwrap_or(0.0),
         );
         print_table_line();
+
+        if self.ctx.coverage_options.details {
+            self.print_missing_items("Items without documentation", |file| &file.undocumented);
+            self.print_missing_items("Items without examples", |file| &file.without_examples);
+        }
+    }
+
+    fn print_missing_items(&self, title: &str, items: impl Fn(&FileCoverage) -> &[MissingItem]) {
+        if self.items.values().all(|file| items(file).is_empty()) {
+            return;
+        }
+        println!("\n{}:", title);
+        for (file, coverage) in &self.items {
+            for item in items(coverage) {
+                println!("    {}:{}: {}", file.prefer_local(), item.line, item.path);
+            }
+        }
+    }
+
+    fn check_threshold(&self) {
+        let threshold = match self.ctx.coverage_options.threshold {
+            Some(threshold) => threshold,
+            None => return,
+        };
+        let mut total = ItemCount::default();
+        for file in self.items.values() {
+            total += file.count;
+        }
+        if let Some(percentage) = total.percentage() {
+            if percentage < threshold {
+                self.ctx.sess().err(&format!(
+                    "{:.1}% of public items are documented, which is below the threshold of {}%",
+                    percentage, threshold
+                ));
+            }
+        }
     }
 }
 
@@ -261,13 +329,28 @@ fn visit_item(&mut self, i: &clean::Item) {
                 let should_have_docs = !should_be_ignored
                     && (level != lint::Level::Allow || matches!(source, LintLevelSource::Default));
 
+                let should_have_example = should_have_doc_example(self.ctx, &i);
+                let missing_item = |ctx: &DocContext<'_>| MissingItem {
+                    path: item_path(ctx.tcx, i.def_id.expect_def_id()),
+                    line: i.span(ctx.tcx).lo(ctx.sess()).line,
+                };
+
                 debug!("counting {:?} {:?} in {:?}", i.type_(), i.name, filename);
-                self.items.entry(filename).or_default().count_item(
+                let file = self.items.entry(filename).or_default();
+                file.count.count_item(
                     has_docs,
                     has_doc_example,
-                    should_have_doc_example(self.ctx, &i),
+                    should_have_example,
                     should_have_docs,
                 );
+                if self.ctx.coverage_options.details {
+                    if should_have_docs && !has_docs {
+                        file.undocumented.push(missing_item(self.ctx));
+                    }
+                    if should_have_example && !has_doc_example {
+                        file.without_examples.push(missing_item(self.ctx));
+                    }
+                }
             }
         }
 
@@ -12,7 +12,7 @@
 use rustc_hir::def_id::{CrateNum, DefId, CRATE_DEF_ID};
 use rustc_middle::ty::{DefIdTree, Ty, TyCtxt};
 use rustc_middle::{bug, span_bug, ty};
-use rustc_session::lint::Lint;
+use rustc_session::lint::{Level, Lint};
 use rustc_span::hygiene::MacroKind;
 use rustc_span::symbol::{sym, Ident, Symbol};
 use rustc_span::{BytePos, DUMMY_SP};
@@ -28,8 +28,8 @@
 
 use crate::clean::{self, utils::find_nearest_parent_module, Crate, Item, ItemLink, PrimitiveType};
 use crate::core::DocContext;
-use crate::html::markdown::{markdown_links, MarkdownLink};
-use crate::lint::{BROKEN_INTRA_DOC_LINKS, PRIVATE_INTRA_DOC_LINKS};
+use crate::html::markdown::{markdown_code_spans, markdown_links, MarkdownLink};
+use crate::lint::{BROKEN_INTRA_DOC_LINKS, PRIVATE_INTRA_DOC_LINKS, STALE_CODE_REFERENCES};
 use crate::passes::Pass;
 use crate::visit::DocVisitor;
 
@@ -1023,6 +1023,12 @@ fn visit_item(&mut self, item: &Item) {
             self.mod_ids.push(item.def_id.expect_def_id());
         }
 
+        // Resolving all the inline code is only worth it if it's going to be reported.
+        let check_code_references =
+            DocContext::as_local_hir_id(self.cx.tcx, item.def_id).map_or(false, |hir_id| {
+                self.cx.tcx.lint_level_at_node(STALE_CODE_REFERENCES, hir_id).0 != Level::Allow
+            });
+
         // We want to resolve in the lexical scope of the documentation.
         // In the presence of re-exports, this is not the same as the module of the item.
         // Rather than merging all documentation into one, resolve it one attribute at a time
@@ -1043,6 +1049,11 @@ fn visit_item(&mut self, item: &Item) {
                     self.cx.cache.intra_doc_links.entry(item.def_id).or_default().push(link);
                 }
             }
+            if check_code_references {
+                for (code, range) in markdown_code_spans(&doc) {
+                    self.check_code_reference(&item, &doc, &self_name, parent_node, &code, range);
+                }
+            }
         }
 
         if item.is_mod() {
@@ -1466,6 +1477,61 @@ fn report_rawptr_assoc_feature_gate(&self, dox: &str, ori_link: &MarkdownLink, i
         .emit();
     }
 
+    /// Reports inline `code` in the documentation of `item` that looks like a path to an item,
+    /// like `Foo::bar` or `Foo::bar()`, but that doesn't resolve to one, which usually means that
+    /// the item was renamed or removed since the documentation was written.
+    fn check_code_reference(
+        &mut self,
+        item: &Item,
+        dox: &str,
+        self_name: &Option<String>,
+        parent_node: Option<DefId>,
+        code: &str,
+        range: Range<usize>,
+    ) {
+        let is_macro = code.ends_with('!');
+        let mut path_str =
+            code.strip_suffix("()").or_else(|| code.strip_suffix('!')).unwrap_or(code);
+        if !looks_like_item_path(path_str) {
+            return;
+        }
+
+        // This is resolved in the same scope as intra-doc links are, see `resolve_link`.
+        let base_node = if item.is_mod() && item.inner_docs(self.cx.tcx) {
+            self.mod_ids.last().copied()
+        } else {
+            parent_node
+        };
+        let Some(module_id) = base_node else { return };
+        let resolved_self;
+        if let Some(rest) = path_str.strip_prefix("Self::") {
+            let Some(name) = self_name else { return };
+            resolved_self = format!("{}::{}", name, rest);
+            path_str = &resolved_self;
+        }
+
+        let resolves = if is_macro {
+            self.resolve_macro(path_str, module_id).is_ok()
+        } else {
+            [TypeNS, ValueNS]
+                .into_iter()
+                .any(|ns| self.resolve(path_str, ns, module_id, &None).is_ok())
+                || self.resolve_macro(path_str, module_id).is_ok()
+        };
+        if resolves {
+            return;
+        }
+
+        let diag_info = DiagnosticInfo { item, dox, ori_link: code, link_range: range };
+        let msg = format!("`{}` does not resolve to an item", code);
+        report_diagnostic(self.cx.tcx, STALE_CODE_REFERENCES, &msg, &diag_info, |diag, sp| {
+            if let Some(sp) = sp {
+                diag.span_label(sp, "no item with this path is in scope");
+            }
+            diag.help("if the item was renamed or removed, update the documentation");
+        });
+    }
+
     fn resolve_with_disambiguator_cached(
         &mut self,
         key: ResolutionInfo,
@@ -1677,6 +1743,18 @@ fn should_ignore_link_with_disambiguators(link: &str) -> bool {
     link.contains(|ch: char| !(ch.is_alphanumeric() || ":_<>, !*&;@()".contains(ch)))
 }
 
+/// Returns true if `path_str` looks like a path to an item, such as `io::Error` or `Vec::new`.
+///
+/// Paths with a single segment aren't checked, since they're too often the names of local
+/// variables or arguments.
+fn looks_like_item_path(path_str: &str) -> bool {
+    path_str.contains("::")
+        && path_str.split("::").all(|segment| {
+            segment.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+        })
+}
+
 /// Returns true if we should ignore `path_str` due to it being unlikely
 /// that it is an intra-doc link.
 fn should_ignore_link(path_str: &str) -> bool {
@@ -9,7 +9,7 @@
 use serde::{Deserialize, Serialize};
 
 /// rustdoc format-version.
-pub const FORMAT_VERSION: u32 = 11;
+pub const FORMAT_VERSION: u32 = 12;
 
 /// A `Crate` is the root of the emitted JSON blob. It contains all type/documentation information
 /// about the language items in the local crate, as well as info about external items to allow
@@ -79,6 +79,17 @@ pub struct Item {
     /// Stringified versions of the attributes on this item (e.g. `"#[inline]"`)
     pub attrs: Vec<String>,
     pub deprecation: Option<Deprecation>,
+    /// The `#[stable]` or `#[unstable]` attribute of this item, which only crates with the
+    /// `staged_api` feature, like the standard library, can have.
+    pub stability: Option<Stability>,
+    /// The `#[must_use]` attribute of this item, if any.
+    pub must_use: Option<MustUse>,
+    /// Whether this item has the `#[track_caller]` attribute.
+    pub track_caller: bool,
+    /// The configuration this item is available on, from `#[doc(cfg)]` or, with
+    /// `#![feature(doc_auto_cfg)]`, from `#[cfg]`. This includes the configuration of the
+    /// modules and impls this item is in.
+    pub cfg: Option<Cfg>,
     #[serde(flatten)]
     pub inner: ItemEnum,
 }
@@ -99,6 +110,50 @@ pub struct Deprecation {
     pub note: Option<String>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Stability {
+    pub level: StabilityLevel,
+    /// The name of the feature the item is part of.
+    pub feature: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StabilityLevel {
+    Stable {
+        /// The version the item was stabilized in.
+        since: String,
+    },
+    Unstable {
+        reason: Option<String>,
+        /// The number of the tracking issue of the feature, if it has one.
+        issue: Option<u32>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MustUse {
+    /// The message given with `#[must_use = "..."]`, if any.
+    pub reason: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cfg {
+    /// Accepts all configurations.
+    True,
+    /// Denies all configurations.
+    False,
+    /// A configuration option, e.g. `unix` or `target_os = "linux"`.
+    Cfg {
+        name: String,
+        value: Option<String>,
+    },
+    Not(Box<Cfg>),
+    Any(Vec<Cfg>),
+    All(Vec<Cfg>),
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Visibility {
@@ -0,0 +1,19 @@
+#![feature(start)]
+#![allow(dead_code)]
+
+pub struct Padded {
+    a: u8,
+    b: u32,
+}
+
+pub enum Opt {
+    None,
+    Some(bool),
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    let _p = Padded { a: 0, b: 0 };
+    let _o = Opt::Some(true);
+    0
+}
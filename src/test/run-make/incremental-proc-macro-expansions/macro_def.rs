@@ -0,0 +1,18 @@
+#![feature(proc_macro_pure)]
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+use proc_macro::TokenStream;
+
+#[proc_macro]
+#[proc_macro_pure]
+pub fn double(input: TokenStream) -> TokenStream {
+    eprintln!("expanding double!({})", input);
+    format!("({0}) + ({0})", input).parse().unwrap()
+}
+
+#[proc_macro]
+pub fn triple(input: TokenStream) -> TokenStream {
+    eprintln!("expanding triple!({})", input);
+    format!("({0}) + ({0}) + ({0})", input).parse().unwrap()
+}
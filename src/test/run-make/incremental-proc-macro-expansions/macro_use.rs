@@ -0,0 +1,6 @@
+#[macro_use]
+extern crate macro_def;
+
+fn main() {
+    println!("{} {} {}", double!(1), double!(ARG), triple!(1));
+}
@@ -0,0 +1,23 @@
+#![feature(proc_macro_pure, proc_macro_tracked_env, track_path)]
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+use proc_macro::*;
+
+#[proc_macro]
+#[proc_macro_pure]
+pub fn read_schema(_: TokenStream) -> TokenStream {
+    eprintln!("expanding read_schema!");
+    let path = std::env::var("SCHEMA").unwrap();
+    tracked_path::path(&path);
+    let schema = std::fs::read_to_string(&path).unwrap();
+    format!("pub const SCHEMA: &str = {:?};", schema).parse().unwrap()
+}
+
+#[proc_macro]
+#[proc_macro_pure]
+pub fn read_table(_: TokenStream) -> TokenStream {
+    eprintln!("expanding read_table!");
+    let table = tracked_env::var("TABLE").unwrap();
+    format!("pub const TABLE: &str = {:?};", table).parse().unwrap()
+}
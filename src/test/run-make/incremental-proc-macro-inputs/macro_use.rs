@@ -0,0 +1,9 @@
+#[macro_use]
+extern crate macro_def;
+
+read_schema!();
+read_table!();
+
+fn main() {
+    println!("schema: {}table: {}", SCHEMA, TABLE);
+}
@@ -0,0 +1,10 @@
+pub fn g() {
+    let added = 0;
+}
+
+pub fn f() {
+        let a = 1;
+        let b = 2;
+}
+
+fn unused() {}
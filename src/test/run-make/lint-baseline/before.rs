@@ -0,0 +1,6 @@
+pub fn f() {
+    let a = 1;
+    let b = 2;
+}
+
+fn unused() {}
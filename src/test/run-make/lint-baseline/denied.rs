@@ -0,0 +1,7 @@
+#![deny(unused_variables)]
+
+pub fn f() {
+    let a = 1;
+}
+
+fn unused() {}
@@ -0,0 +1,6 @@
+extern crate macro_def;
+
+macro_def::read_undeclared_file!();
+macro_def::read_undeclared_metadata!();
+
+fn main() {}
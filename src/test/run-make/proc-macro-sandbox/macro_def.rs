@@ -0,0 +1,58 @@
+#![feature(proc_macro_tracked_env, track_path)]
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+use proc_macro::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[proc_macro]
+pub fn read_env(_: TokenStream) -> TokenStream {
+    let tracked = tracked_env::var("SANDBOX_VAR").unwrap();
+    let untracked = std::env::var("SANDBOX_VAR").is_ok();
+    format!("({:?}, {})", tracked, untracked).parse().unwrap()
+}
+
+// Declares a file with a relative path, then reads it with another path.
+#[proc_macro]
+pub fn read_file(_: TokenStream) -> TokenStream {
+    tracked_path::path("macro_def.rs");
+    std::fs::read_to_string("./macro_def.rs").unwrap().lines().count().to_string().parse().unwrap()
+}
+
+// Accesses a file without declaring it, which the sandbox denies and reports on Linux.
+#[proc_macro]
+pub fn read_undeclared_file(_: TokenStream) -> TokenStream {
+    let path = tracked_env::var("SANDBOX_FILE").unwrap();
+    assert!(std::fs::read_to_string(path).is_err());
+    TokenStream::new()
+}
+
+#[proc_macro]
+pub fn read_undeclared_metadata(_: TokenStream) -> TokenStream {
+    let path = tracked_env::var("SANDBOX_FILE").unwrap();
+    assert!(std::thread::spawn(|| std::fs::metadata(path)).join().unwrap().is_err());
+    TokenStream::new()
+}
+
+static INVOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+// Expands to the number of earlier invocations that weren't cached.
+#[proc_macro]
+pub fn count(_: TokenStream) -> TokenStream {
+    INVOCATIONS.fetch_add(1, Ordering::SeqCst).to_string().parse().unwrap()
+}
+
+#[proc_macro]
+pub fn exit(_: TokenStream) -> TokenStream {
+    std::process::exit(1)
+}
+
+// Writes to the stdout of the worker directly, and from a child process.
+#[proc_macro]
+pub fn write_stdout(_: TokenStream) -> TokenStream {
+    use std::io::Write;
+    std::io::stdout().write_all(b"written to stdout\n").unwrap();
+    std::io::stdout().flush().unwrap();
+    std::process::Command::new("/bin/sh").args(["-c", "echo written by a child"]).status().unwrap();
+    TokenStream::new()
+}
@@ -0,0 +1,8 @@
+extern crate macro_def;
+use macro_def::*;
+
+fn main() {
+    println!("{:?} {}", read_env!(), read_file!() > 0);
+    // The second invocation has the same input as the first one, so it reuses its expansion.
+    println!("{} {} {}", count!(), count!(), count!(x));
+}
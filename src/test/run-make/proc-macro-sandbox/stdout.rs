@@ -0,0 +1,5 @@
+extern crate macro_def;
+
+macro_def::write_stdout!();
+
+fn main() {}
@@ -0,0 +1,10 @@
+pub fn added() {}
+
+pub fn changed(x: u64) -> u64 {
+    x
+}
+
+pub enum E {
+    A,
+    B,
+}
@@ -0,0 +1,9 @@
+pub fn removed() {}
+
+pub fn changed(x: u32) -> u32 {
+    x
+}
+
+pub enum E {
+    A,
+}
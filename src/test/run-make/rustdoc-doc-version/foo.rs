@@ -0,0 +1,14 @@
+/// Unchanged between the versions.
+pub fn same() {}
+
+/// Takes an argument since 2.0.0.
+#[cfg(v2)]
+pub fn changed(_: u32) {}
+
+/// Takes no argument before 2.0.0.
+#[cfg(not(v2))]
+pub fn changed() {}
+
+/// Only in 2.0.0.
+#[cfg(v2)]
+pub fn added() {}
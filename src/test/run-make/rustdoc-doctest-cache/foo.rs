@@ -0,0 +1,6 @@
+/// ```
+/// assert_eq!(1 + 2, 3);
+/// ```
+pub fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
@@ -0,0 +1,10 @@
+//! Parses things.
+
+/// Tokenizes the input, which has to be valid Unicode.
+///
+/// ```
+/// foo::tokenize("a b").unwrap();
+/// ```
+pub fn tokenize(_input: &str) -> Option<()> {
+    None
+}
@@ -0,0 +1,24 @@
+#![crate_name = "foo"]
+
+pub mod shapes {
+    pub trait Shape {
+        fn area(&self) -> f64;
+    }
+
+    #[derive(Clone)]
+    pub struct Square(pub f64);
+
+    impl Shape for Square {
+        fn area(&self) -> f64 {
+            self.0 * self.0
+        }
+    }
+}
+
+pub use shapes::Square;
+
+impl shapes::Shape for f64 {
+    fn area(&self) -> f64 {
+        *self
+    }
+}
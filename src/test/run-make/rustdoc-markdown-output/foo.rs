@@ -0,0 +1,30 @@
+#![crate_name = "foo"]
+
+/// A struct, which is used by [`Baz`].
+///
+/// [`Baz`]: bar::Baz
+///
+/// ```
+/// # use foo::Foo;
+/// assert_eq!(Foo::new().field, 0);
+/// ```
+pub struct Foo {
+    /// A field.
+    pub field: u32,
+}
+
+impl Foo {
+    /// Creates a new `Foo`.
+    pub fn new() -> Foo {
+        Foo { field: 0 }
+    }
+}
+
+pub mod bar {
+    /// An enum, which refers to [the struct](crate::Foo) and to
+    /// [its constructor](crate::Foo::new).
+    pub enum Baz {
+        A,
+        B(u32),
+    }
+}
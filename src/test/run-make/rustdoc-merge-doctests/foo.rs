@@ -0,0 +1,37 @@
+/// ```
+/// assert_eq!(foo::add(1, 2), 3);
+/// ```
+pub fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+/// ```should_panic
+/// foo::fail();
+/// ```
+pub fn fail() {
+    panic!("failed");
+}
+
+/// ```compile_fail
+/// let x: u32 = "not a number";
+/// ```
+pub struct NotMerged;
+
+/// ```
+/// fn main() {
+///     foo::add(1, 1);
+/// }
+/// ```
+pub struct OwnMain;
+
+/// ```
+/// // Mentioning `fn main` doesn't keep a doctest from being merged.
+/// let source = "fn main() {}";
+/// assert!(source.starts_with("fn"));
+/// ```
+pub struct MainInComment;
+
+/// ```should_panic
+/// panic!("in the doctest");
+/// ```
+pub struct Panics;
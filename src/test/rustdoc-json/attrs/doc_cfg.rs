@@ -0,0 +1,24 @@
+#![feature(doc_cfg)]
+
+// @is doc_cfg.json "$.index[*][?(@.name=='unix_only')].cfg.cfg.name" \"unix\"
+// @is - "$.index[*][?(@.name=='unix_only')].cfg.cfg.value" null
+#[doc(cfg(unix))]
+pub fn unix_only() {}
+
+// @is - "$.index[*][?(@.name=='not_linux')].cfg.not.cfg.name" \"target_os\"
+// @is - "$.index[*][?(@.name=='not_linux')].cfg.not.cfg.value" \"linux\"
+#[doc(cfg(not(target_os = "linux")))]
+pub fn not_linux() {}
+
+// @is - "$.index[*][?(@.name=='everywhere')].cfg" null
+pub fn everywhere() {}
+
+#[doc(cfg(feature = "fancy"))]
+pub mod fancy {
+    // The configuration of the module applies to its items.
+    // @count - "$.index[*][?(@.name=='in_fancy')].cfg.all[*]" 2
+    // @is - "$.index[*][?(@.name=='in_fancy')].cfg.all[0].cfg.name" \"feature\"
+    // @is - "$.index[*][?(@.name=='in_fancy')].cfg.all[1].cfg.name" \"windows\"
+    #[doc(cfg(windows))]
+    pub fn in_fancy() {}
+}
@@ -0,0 +1,16 @@
+// @is must_use.json "$.index[*][?(@.name=='with_reason')].must_use.reason" \"use it\"
+// @is - "$.index[*][?(@.name=='with_reason')].track_caller" false
+#[must_use = "use it"]
+pub fn with_reason() -> u32 { 0 }
+
+// @is - "$.index[*][?(@.name=='WithoutReason')].must_use.reason" null
+#[must_use]
+pub struct WithoutReason;
+
+// @is - "$.index[*][?(@.name=='neither')].must_use" null
+// @is - "$.index[*][?(@.name=='neither')].track_caller" false
+pub fn neither() {}
+
+// @is - "$.index[*][?(@.name=='caller')].track_caller" true
+#[track_caller]
+pub fn caller() {}
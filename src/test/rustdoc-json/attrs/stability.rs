@@ -0,0 +1,18 @@
+#![feature(staged_api)]
+#![stable(feature = "rust1", since = "1.0.0")]
+
+// @is stability.json "$.index[*][?(@.name=='stable_fn')].stability.feature" \"rust1\"
+// @is - "$.index[*][?(@.name=='stable_fn')].stability.level.stable.since" \"1.2.0\"
+#[stable(feature = "rust1", since = "1.2.0")]
+pub fn stable_fn() {}
+
+// @is - "$.index[*][?(@.name=='unstable_fn')].stability.feature" \"foo_bar\"
+// @is - "$.index[*][?(@.name=='unstable_fn')].stability.level.unstable.issue" 123
+// @is - "$.index[*][?(@.name=='unstable_fn')].stability.level.unstable.reason" \"not done yet\"
+#[unstable(feature = "foo_bar", issue = "123", reason = "not done yet")]
+pub fn unstable_fn() {}
+
+// @is - "$.index[*][?(@.name=='no_issue_fn')].stability.level.unstable.issue" null
+// @is - "$.index[*][?(@.name=='no_issue_fn')].stability.level.unstable.reason" null
+#[unstable(feature = "foo_bar", issue = "none")]
+pub fn no_issue_fn() {}
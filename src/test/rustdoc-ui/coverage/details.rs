@@ -0,0 +1,17 @@
+// compile-flags:-Z unstable-options --show-coverage --coverage-details
+// check-pass
+
+//! Crate docs, without an example.
+
+/// Documented, with an example.
+///
+/// ```
+/// let x = 1;
+/// ```
+pub fn documented() {}
+
+pub fn undocumented() {}
+
+pub struct Undocumented {
+    pub field: u32,
+}
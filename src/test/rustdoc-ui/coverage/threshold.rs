@@ -0,0 +1,8 @@
+// compile-flags:-Z unstable-options --show-coverage --coverage-threshold 75
+
+//! Crate docs
+
+/// Documented
+pub fn documented() {}
+
+pub fn undocumented() {}
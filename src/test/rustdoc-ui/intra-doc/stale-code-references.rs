@@ -0,0 +1,23 @@
+#![deny(rustdoc::stale_code_references)]
+
+//! See `Config::new`, `Config::open()` and `std::vec::Vec`.
+//~^ ERROR `Config::open()` does not resolve to an item
+
+pub struct Config;
+
+impl Config {
+    /// Creates a `Config`, unlike `Self::default`.
+    //~^ ERROR `Self::default` does not resolve to an item
+    pub fn new() -> Config {
+        Config
+    }
+}
+
+/// Calls `std::println!`, `local::mac!`, `Config::new()` and [`Config::new`], with `config`
+/// and `a + b::c`.
+//~^^ ERROR `local::mac!` does not resolve to an item
+///
+/// ```text
+/// let x = `Config::gone`;
+/// ```
+pub fn f() {}
@@ -264,3 +264,35 @@ struct ErrorWithLifetime<'a> {
     span: Span,
     name: &'a str,
 }
+
+#[derive(SessionDiagnostic)]
+#[error = "E0123"]
+#[slug = "typeck-unrecognized-atomic-operation"]
+struct Translatable<'a> {
+    #[message]
+    #[label]
+    span: Span,
+    #[label = "other-label"]
+    other_span: Span,
+    #[suggestion(code = "{op}")]
+    sugg: Span,
+    op: &'a str,
+}
+
+#[derive(SessionDiagnostic)]
+#[error = "E0123"]
+struct LabelWithoutSlug {
+    #[label]
+    //~^ ERROR `#[label]` can only be used on translatable diagnostics
+    span: Span,
+}
+
+#[derive(SessionDiagnostic)]
+#[error = "E0123"]
+#[slug = "typeck-unrecognized-atomic-operation"]
+#[slug = "typeck-unrecognized-atomic-operation"]
+//~^ ERROR `slug` specified multiple times
+struct SlugSpecifiedTwice {
+    #[message]
+    span: Span,
+}
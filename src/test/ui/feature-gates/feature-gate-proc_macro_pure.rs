@@ -0,0 +1,14 @@
+// force-host
+// no-prefer-dynamic
+
+#![crate_type = "proc-macro"]
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+
+#[proc_macro_derive(A)]
+#[proc_macro_pure] //~ ERROR the `#[proc_macro_pure]` attribute is an experimental feature
+pub fn a(_: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
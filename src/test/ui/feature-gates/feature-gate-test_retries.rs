@@ -0,0 +1,5 @@
+// compile-flags: --test
+
+#[test]
+#[retries = "3"] //~ ERROR the `#[retries]` attribute is an experimental feature
+fn flaky() {}
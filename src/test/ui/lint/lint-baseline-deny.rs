@@ -0,0 +1,10 @@
+// Checks that a lint baseline is written even though a denied lint fails the
+// compilation, as the baseline records denied lints too.
+
+// compile-flags: -Z emit-lint-baseline={{build-base}}/lint-baseline-deny.json
+
+#![deny(unused_variables)]
+
+fn main() {
+    let x = 1; //~ ERROR unused variable: `x`
+}
@@ -4,12 +4,14 @@
 #![crate_type = "proc-macro"]
 #![crate_name = "proc_macro_api_tests"]
 #![feature(proc_macro_span)]
+#![feature(proc_macro_literal_value)]
 #![deny(dead_code)] // catch if a test function is never called
 
 extern crate proc_macro;
 
 mod cmp;
 mod parse;
+mod value;
 
 use proc_macro::TokenStream;
 
@@ -19,6 +21,7 @@ pub fn run(input: TokenStream) -> TokenStream {
 
     cmp::test();
     parse::test();
+    value::test();
 
     TokenStream::new()
 }
@@ -0,0 +1,76 @@
+use proc_macro::{Literal, LiteralKind};
+
+pub fn test() {
+    test_kind();
+    test_string_values();
+    test_number_values();
+    test_wrong_kind();
+}
+
+fn lit(s: &str) -> Literal {
+    s.parse().unwrap()
+}
+
+fn test_kind() {
+    assert_eq!(lit("b'a'").kind(), LiteralKind::Byte);
+    assert_eq!(lit("'a'").kind(), LiteralKind::Char);
+    assert_eq!(lit("1u8").kind(), LiteralKind::Integer);
+    assert_eq!(lit("1.5").kind(), LiteralKind::Float);
+    assert_eq!(lit("1f32").kind(), LiteralKind::Float);
+    assert_eq!(lit("\"a\"").kind(), LiteralKind::Str);
+    assert_eq!(lit("r##\"a\"##").kind(), LiteralKind::StrRaw(2));
+    assert_eq!(lit("b\"a\"").kind(), LiteralKind::ByteStr);
+    assert_eq!(lit("br\"a\"").kind(), LiteralKind::ByteStrRaw(0));
+    assert_eq!(Literal::string("a").kind(), LiteralKind::Str);
+
+    assert_eq!(lit("1u8").suffix().as_deref(), Some("u8"));
+    assert_eq!(lit("\"a\"custom").suffix().as_deref(), Some("custom"));
+    assert_eq!(lit("1").suffix(), None);
+}
+
+fn test_string_values() {
+    assert_eq!(lit(r#""a\tb\u{e9}\x41\"""#).str_value().as_deref(), Some("a\tbéA\""));
+    assert_eq!(lit(r##"r#"a\tb"#"##).str_value().as_deref(), Some(r"a\tb"));
+    assert_eq!(lit("\"a\\\n    b\"").str_value().as_deref(), Some("ab"));
+    assert_eq!(lit("\"a\"custom").str_value().as_deref(), Some("a"));
+    assert_eq!(Literal::string("a\"b\n").str_value().as_deref(), Some("a\"b\n"));
+    assert_eq!(lit(r#"b"a\xff\0""#).byte_str_value().as_deref(), Some(&b"a\xff\0"[..]));
+    assert_eq!(lit(r#"br"a\xff""#).byte_str_value().as_deref(), Some(&br"a\xff"[..]));
+    assert_eq!(Literal::byte_string(b"\x00\xff").byte_str_value().as_deref(), Some(&[0, 255][..]));
+    assert_eq!(lit(r"'\n'").char_value(), Some('\n'));
+    assert_eq!(lit(r"'\u{1F600}'").char_value(), Some('\u{1F600}'));
+    assert_eq!(Literal::character('é').char_value(), Some('é'));
+    assert_eq!(lit(r"b'\x7f'").byte_value(), Some(0x7f));
+}
+
+fn test_number_values() {
+    assert_eq!(lit("1_000u32").integer_value::<u32>(), Some(1000));
+    assert_eq!(lit("0xff").integer_value::<u8>(), Some(255));
+    assert_eq!(lit("0o17").integer_value::<u8>(), Some(15));
+    assert_eq!(lit("0b1010_1010").integer_value::<u8>(), Some(170));
+    assert_eq!(lit("0x100").integer_value::<u8>(), None);
+    assert_eq!(lit("-10").integer_value::<i32>(), Some(-10));
+    assert_eq!(lit("-10").integer_value::<u32>(), None);
+    assert_eq!(Literal::i64_suffixed(-42).integer_value::<i64>(), Some(-42));
+    assert_eq!(Literal::u128_unsuffixed(u128::MAX).integer_value::<u128>(), Some(u128::MAX));
+    assert_eq!(lit("10ulong").integer_value::<u64>(), Some(10));
+
+    assert_eq!(lit("1.5").float_value(), Some(1.5));
+    assert_eq!(lit("1_000.25e-2f32").float_value(), Some(10.0025));
+    assert_eq!(lit("-2.5").float_value(), Some(-2.5));
+    assert_eq!(Literal::f64_suffixed(0.125).float_value(), Some(0.125));
+    assert_eq!(lit("1f32").float_value(), Some(1.0));
+    assert_eq!(lit("1_000f64").float_value(), Some(1000.0));
+    assert_eq!(lit("0b1f32").float_value(), None);
+}
+
+fn test_wrong_kind() {
+    assert_eq!(lit("1").str_value(), None);
+    assert_eq!(lit("\"1\"").integer_value::<u32>(), None);
+    assert_eq!(lit("b\"a\"").str_value(), None);
+    assert_eq!(lit("\"a\"").byte_str_value(), None);
+    assert_eq!(lit("'a'").byte_value(), None);
+    assert_eq!(lit("b'a'").char_value(), None);
+    assert_eq!(lit("1").float_value(), None);
+    assert_eq!(lit("1f32").integer_value::<u32>(), None);
+}
@@ -0,0 +1,76 @@
+// force-host
+// no-prefer-dynamic
+
+#![crate_type = "proc-macro"]
+#![feature(proc_macro_diagnostic)]
+
+extern crate proc_macro;
+
+use proc_macro::{Applicability, Diagnostic, Level, TokenStream, TokenTree};
+
+/// Warns about every token of `input` under the lint `lint`, as if they were unused variables.
+fn lint_every_token(lint: &str, input: TokenStream) -> TokenStream {
+    for tree in input {
+        let message = format!("`{}` is never used", tree);
+        let mut diag = Diagnostic::spanned(tree.span(), Level::Warning, message).span_suggestion(
+            tree.span(),
+            "if this is intentional, prefix it with an underscore",
+            format!("_{}", tree),
+            Applicability::MachineApplicable,
+        );
+        diag.set_lint(lint);
+        assert_eq!(diag.lint(), Some(lint));
+        diag.emit();
+    }
+    TokenStream::new()
+}
+
+#[proc_macro]
+pub fn lint_unused(input: TokenStream) -> TokenStream {
+    lint_every_token("unused_variables", input)
+}
+
+#[proc_macro]
+pub fn lint_group(input: TokenStream) -> TokenStream {
+    lint_every_token("unused", input)
+}
+
+#[proc_macro]
+pub fn lint_unknown(input: TokenStream) -> TokenStream {
+    lint_every_token("no_such_lint", input)
+}
+
+/// Warns about every token of `input` under `unused_variables`, with children of every level.
+#[proc_macro]
+pub fn lint_with_children(input: TokenStream) -> TokenStream {
+    for tree in input {
+        let message = format!("`{}` is never used", tree);
+        let mut diag = Diagnostic::spanned(tree.span(), Level::Warning, message)
+            .error("this child is an error")
+            .warning("this child is a warning")
+            .note("this child is a note")
+            .help("this child is a help message");
+        diag.set_lint("unused_variables");
+        diag.emit();
+    }
+    TokenStream::new()
+}
+
+/// Warns about the `foo`s in `input`, suggesting to replace all of them with `bar`.
+#[proc_macro]
+pub fn rename(input: TokenStream) -> TokenStream {
+    let foos: Vec<_> = input
+        .into_iter()
+        .filter_map(|tree| match tree {
+            TokenTree::Ident(ident) if ident.to_string() == "foo" => Some(ident.span()),
+            _ => None,
+        })
+        .collect();
+    if !foos.is_empty() {
+        let parts = foos.iter().map(|&span| (span, "bar".to_string())).collect();
+        Diagnostic::spanned(foos, Level::Warning, "`foo` is deprecated")
+            .multipart_suggestion("use `bar` instead", parts, Applicability::MachineApplicable)
+            .emit();
+    }
+    TokenStream::new()
+}
@@ -0,0 +1,28 @@
+// aux-build:diagnostic-lints.rs
+
+#![deny(unused_variables)]
+
+extern crate diagnostic_lints;
+
+diagnostic_lints::lint_unused!(a); //~ ERROR `a` is never used
+
+#[allow(unused_variables)]
+mod allowed {
+    diagnostic_lints::lint_unused!(b);
+}
+
+#[warn(unused_variables)]
+mod warned {
+    diagnostic_lints::lint_unused!(c); //~ WARN `c` is never used
+}
+
+// The children which are errors or warnings are emitted as notes.
+#[warn(unused_variables)]
+mod children {
+    diagnostic_lints::lint_with_children!(f); //~ WARN `f` is never used
+}
+
+diagnostic_lints::lint_group!(d); //~ WARN `d` is never used
+diagnostic_lints::lint_unknown!(e); //~ WARN `e` is never used
+
+fn main() {}
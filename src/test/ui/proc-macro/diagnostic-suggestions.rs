@@ -0,0 +1,9 @@
+// aux-build:diagnostic-lints.rs
+// run-rustfix
+// check-pass
+
+extern crate diagnostic_lints;
+
+diagnostic_lints::rename!(foo + foo); //~ WARN `foo` is deprecated
+
+fn main() {}
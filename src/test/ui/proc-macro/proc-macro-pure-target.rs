@@ -0,0 +1,36 @@
+// Checks that `#[proc_macro_pure]` is only accepted on the functions defining proc macros.
+
+// force-host
+// no-prefer-dynamic
+
+#![crate_type = "proc-macro"]
+#![feature(proc_macro_pure)]
+#![allow(dead_code)]
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+
+#[proc_macro]
+#[proc_macro_pure]
+pub fn bang(input: TokenStream) -> TokenStream {
+    input
+}
+
+#[proc_macro_pure]
+#[proc_macro_attribute]
+pub fn attr(_: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+#[proc_macro_derive(Derive)]
+#[proc_macro_pure]
+pub fn derive(_: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
+
+#[proc_macro_pure] //~ ERROR should be applied to a function defining a proc macro
+fn helper() {}
+
+#[proc_macro_pure] //~ ERROR should be applied to a function defining a proc macro
+struct Helper;
@@ -0,0 +1,20 @@
+// compile-flags: --test
+
+#![feature(test, test_retries)]
+#![allow(dead_code)]
+
+extern crate test;
+
+#[retries = "2"] //~ ERROR `#[retries]` can only be used on `#[test]` functions
+fn not_a_test() {}
+
+#[retries = "2"] //~ ERROR `#[retries]` can only be used on `#[test]` functions
+struct NotATest;
+
+#[bench]
+#[retries = "2"] //~ ERROR `#[retries]` can only be used on `#[test]` functions
+fn bench(_: &mut test::Bencher) {}
+
+#[test]
+#[retries = "2"]
+fn a_test() {}
@@ -0,0 +1,22 @@
+// compile-flags: --test
+// run-flags: --test-threads=1
+// check-run-results
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+// ignore-emscripten no threads support
+// run-pass
+
+#![feature(test_retries)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+#[retries = "2"]
+#[should_panic]
+fn flaky() {
+    // Only panics as expected from the second attempt on.
+    if ATTEMPTS.fetch_add(1, Ordering::SeqCst) > 0 {
+        panic!();
+    }
+}
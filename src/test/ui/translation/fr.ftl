@@ -0,0 +1,4 @@
+# `.previous-use-label` is missing on purpose, it's taken from the English message.
+typeck-field-multiply-specified-in-initializer =
+    champ `{$ident}` spécifié plusieurs fois
+    .label = utilisé plusieurs fois
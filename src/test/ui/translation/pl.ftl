@@ -0,0 +1,9 @@
+# The variants are selected with the Polish plural rules, which have different forms for 1, for
+# 2 to 4, and for 5 or more.
+typeck-wrong-number-of-generic-arguments-to-intrinsic =
+    funkcja wewnętrzna ma { $found ->
+        [one] {$found} parametr
+        [few] {$found} parametry
+       *[many] {$found} parametrów
+    } typu, oczekiwano {$expected}
+    .label = oczekiwano {$expected}
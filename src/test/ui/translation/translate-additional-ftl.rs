@@ -0,0 +1,15 @@
+// Check that `-Z translate-additional-ftl` overrides the messages of translatable diagnostics,
+// and that messages and attributes missing from it fall back to English.
+// compile-flags: -Z translate-lang=fr -Z translate-additional-ftl={{src-base}}/translation/fr.ftl
+
+struct Foo {
+    x: i32,
+}
+
+fn main() {
+    let _ = Foo {
+        x: 0,
+        x: 0,
+        //~^ ERROR champ `x` spécifié plusieurs fois
+    };
+}
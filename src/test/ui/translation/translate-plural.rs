@@ -0,0 +1,16 @@
+// Check that numbers select the variants of translated messages with the plural rules of the
+// requested language.
+// compile-flags: -Z translate-lang=pl -Z translate-additional-ftl={{src-base}}/translation/pl.ftl
+
+#![feature(intrinsics)]
+
+extern "rust-intrinsic" {
+    fn size_of<T, U>() -> usize;
+    //~^ ERROR funkcja wewnętrzna ma 2 parametry typu, oczekiwano 1
+    fn transmute<T>(e: T) -> T;
+    //~^ ERROR funkcja wewnętrzna ma 1 parametr typu, oczekiwano 2
+    fn min_align_of<T, U, V, W, X>() -> usize;
+    //~^ ERROR funkcja wewnętrzna ma 5 parametrów typu, oczekiwano 1
+}
+
+fn main() {}
@@ -881,6 +881,8 @@ pub fn make_test_description<R: Read>(
         ignore,
         #[cfg(not(bootstrap))]
         ignore_message,
+        #[cfg(not(bootstrap))]
+        retries: None,
         should_panic,
         compile_fail: false,
         no_run: false,
@@ -510,11 +510,21 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         color: config.color,
         shuffle: false,
         shuffle_seed: None,
+        #[cfg(not(bootstrap))]
+        shard: None,
+        #[cfg(not(bootstrap))]
+        bench_save: None,
+        #[cfg(not(bootstrap))]
+        bench_baseline: None,
         test_threads: None,
         skip: vec![],
         list: false,
         options: test::Options::new(),
         time_options: None,
+        #[cfg(not(bootstrap))]
+        test_timeout: None,
+        #[cfg(not(bootstrap))]
+        retries: 0,
         force_run_in_process: false,
     }
 }
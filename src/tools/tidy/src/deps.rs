@@ -125,6 +125,7 @@
     "if_chain",
     "indexmap",
     "instant",
+    "intl_pluralrules",
     "itertools",
     "itoa",
     "jobserver",
@@ -203,6 +204,7 @@
     "thorin-dwp",
     "thread_local",
     "time",
+    "tinystr",
     "tinyvec",
     "tracing",
     "tracing-attributes",
@@ -215,6 +217,8 @@
     "unic-char-range",
     "unic-common",
     "unic-emoji-char",
+    "unic-langid",
+    "unic-langid-impl",
     "unic-ucd-version",
     "unicode-normalization",
     "unicode-script",